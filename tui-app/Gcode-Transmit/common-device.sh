@@ -29,16 +29,19 @@ eureka_resolve_printer_device() {
 }
 
 # Print path to eureka-serial if executable, else exit 1 from this helper's perspective.
+# EUREKA_SERIAL_BIN (set by the app) wins; an installed runtime home has no target/ directory.
 eureka_find_serial_bin() {
   local here="$1"
   local root
   root="$(cd "$here/../.." && pwd)"
   local cand
   for cand in \
+    "${EUREKA_SERIAL_BIN:-}" \
     "$here/eureka-serial" \
     "$root/target/release/eureka-serial" \
-    "$root/target/debug/eureka-serial"; do
-    if [ -x "$cand" ]; then
+    "$root/target/debug/eureka-serial" \
+    "$(command -v eureka-serial 2>/dev/null || true)"; do
+    if [ -n "$cand" ] && [ -x "$cand" ]; then
       printf '%s\n' "$cand"
       return 0
     fi
@@ -9,15 +9,26 @@ source "$SCRIPT_DIR/../common-device.sh"
 # Get the parent directory path
 PARENT_DIR="$(dirname "$SCRIPT_DIR")"
 
-prusa-slicer --export-gcode --load Ender-3_set.ini --output test.gcode "$PARENT_DIR/test.stl" &
-echo $! > "$SCRIPT_DIR/Gcode-Send-PID.pid"
-# echo $! > Gcode-Send-PID.pid
-wait $!
-if [ ! -f test.gcode ]; then
-  echo "Slicer did not produce test.gcode (check prusa-slicer, Ender-3_set.ini, and $PARENT_DIR/test.stl)"
+# The app passes its per-job files; run standalone, the legacy fixed names are used.
+MODEL_FILE="${EUREKA_MODEL_FILE:-$PARENT_DIR/test.stl}"
+GCODE_FILE="${EUREKA_GCODE_FILE:-test.gcode}"
+
+# The app slices with the configured backend (see tui-app/src/slicer) and sets EUREKA_PRESLICED;
+# run standalone, this falls back to PrusaSlicer with the bundled profile.
+if [ -z "${EUREKA_PRESLICED:-}" ]; then
+  prusa-slicer --export-gcode --load Ender-3_set.ini --output "$GCODE_FILE" "$MODEL_FILE" &
+  echo $! > "$SCRIPT_DIR/Gcode-Send-PID.pid"
+  # echo $! > Gcode-Send-PID.pid
+  wait $!
+fi
+if [ ! -f "$GCODE_FILE" ]; then
+  echo "Slicer did not produce $GCODE_FILE (check prusa-slicer, Ender-3_set.ini, and $MODEL_FILE)"
   exit 2
 fi
-rm -rf "$PARENT_DIR/test.stl"
+# Files of an app job are cleaned up by the app (kept when the print fails).
+if [ -z "${EUREKA_PRESLICED:-}" ]; then
+  rm -rf "$MODEL_FILE"
+fi
 
 if SERIAL_BIN="$(eureka_find_serial_bin "$SCRIPT_DIR")"; then
   if [ -z "${EUREKA_PRINTER_DEVICE:-}" ]; then
@@ -28,7 +39,7 @@ if SERIAL_BIN="$(eureka_find_serial_bin "$SCRIPT_DIR")"; then
     echo "No serial device found (set EUREKA_PRINTER_DEVICE or add udev symlink /dev/3Dprinter — see README.md)"
     exit 1
   fi
-  "$SERIAL_BIN" test.gcode &
+  "$SERIAL_BIN" "$GCODE_FILE" &
 else
   if [ ! -e /dev/3Dprinter ]; then
     echo "Legacy ./serial needs /dev/3Dprinter. Build eureka-serial: (cd tui-app && cargo build --release)"
@@ -39,7 +50,15 @@ fi
 echo $! > "$SCRIPT_DIR/Gcode-Send-PID.pid"
 # echo $! > Gcode-Send-PID.pid
 wait $!
-if [ -z "${EUREKA_KEEP_GCODE:-}" ]; then
-  rm -rf test.gcode
+serial_status=$?
+if [ -z "${EUREKA_KEEP_GCODE:-}" ] && [ -z "${EUREKA_PRESLICED:-}" ]; then
+  rm -rf "$GCODE_FILE"
+fi
+# 4 = the printer firmware halted (thermal runaway, kill(), ...); anything else is a serial failure.
+if [ "$serial_status" -eq 4 ]; then
+  exit 4
+fi
+if [ "$serial_status" -ne 0 ]; then
+  exit 3
 fi
 
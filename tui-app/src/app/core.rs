@@ -1,13 +1,18 @@
 use ratatui::widgets::ListState;
-use crate::wallet::{Wallet, SculptItem, PrinterInfo};
+use crate::wallet::{Wallet, SculptItem, PrinterInfo, SuiCoin};
 use crate::utils::{setup_for_read, shorten_id, NetworkState, format_sui_balance};
 use anyhow::Result;
 use std::sync::Arc;
 use std::vec::Vec;
 use sui_crypto::ed25519::Ed25519PrivateKey;
 use sui_rpc::Client as GrpcClient;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use super::print_job::PrintTask;
+use super::printer::telemetry::{PrintTelemetry, PrinterFirmware};
+use crate::config::EurekaConfig;
+use crate::seal::{KeyServerCheck, SealService};
+use crate::transactions::GasEstimate;
+use crate::walrus::{AggregatorHealth, DownloadProgress};
 
 #[derive(Clone, PartialEq)]
 pub enum RegistrationStatus {
@@ -53,9 +58,16 @@ pub struct App {
     pub tasks_state: ListState,
     pub is_confirming: bool,
     pub is_harvesting: bool,
+    pub is_withdrawing_fees: bool,  // withdraw_fees transaction in flight
+    pub gas_estimate: Option<Result<GasEstimate, String>>,  // simulated fee for the open toggle/harvest/merge prompt; None while simulating
     pub is_switching_network: bool,
+    pub is_cancelling_print: bool,
+    pub is_confirming_estop: bool,
     pub harvestable_rewards: String,
     pub sui_balance: u128,
+    pub sui_coins: Vec<SuiCoin>,  // largest first; empty until listed
+    pub is_confirming_merge: bool,  // dust merge prompt (M) open
+    pub is_merging_coins: bool,  // dust merge transaction in flight
     pub wal_balance: u128,
     pub network_state: NetworkState,
     pub error_message: Option<String>,
@@ -71,10 +83,20 @@ pub struct App {
     pub print_output: Vec<String>,  // store print output
     pub is_loading_sculpts: bool,  // Loading state for Sculpts
     pub is_toggling_mode: bool,    // True when switching between online/offline modes
+    pub telemetry: PrintTelemetry,  // State rebuilt from eureka-serial events
+    pub printer_firmware: Option<PrinterFirmware>,  // Last firmware detected by eureka-serial (M115)
+    pub config: EurekaConfig,  // ~/.config/eureka/config.yaml plus env overrides
+    pub download_progress: Option<DownloadProgress>,  // Walrus download in flight
+    pub walrus_health: Arc<AggregatorHealth>,  // Per-aggregator stats, shared by every download
+    pub show_diagnostics: bool,  // Aggregator diagnostics popup (D)
+    pub seal: Arc<SealService>,  // Seal decryptor and session key, reused across encrypted jobs
+    pub key_server_checks: Option<Vec<KeyServerCheck>>,  // Last key server probe; None while running
+    pub print_abort: Option<Arc<Notify>>,  // Set while a native print pipeline runs; kills slicer / sender
 }
 
 impl App {
     pub async fn new() -> Result<App> {
+        let config = EurekaConfig::load()?;
         let network_state = NetworkState::new();
         
         let (rpc, address, signer) = setup_for_read(&network_state).await?;
@@ -87,6 +109,7 @@ impl App {
         // Get balance and printer id
         let sui_balance = wallet.get_sui_balance(wallet.get_active_address().await?).await?;
         let wal_balance = wallet.get_walrus_balance(wallet.get_active_address().await?).await?;
+        let sui_coins = wallet.get_sui_coins(wallet.get_active_address().await?).await.unwrap_or_default();
         let printer_info = match wallet.get_printer_info(wallet.get_active_address().await?).await {
             Ok(info) => {
                 info
@@ -120,9 +143,16 @@ impl App {
             tasks_state: ListState::default(),
             is_confirming: false,
             is_harvesting: false,
+            is_withdrawing_fees: false,
+            gas_estimate: None,
+            is_cancelling_print: false,
+            is_confirming_estop: false,
             is_switching_network: false,
             harvestable_rewards: pool_balance_formatted,
             sui_balance,
+            sui_coins,
+            is_confirming_merge: false,
+            is_merging_coins: false,
             wal_balance,
             network_state,
             error_message: None,
@@ -138,6 +168,15 @@ impl App {
             print_output: vec![],
             is_loading_sculpts: true,  // Start loading
             is_toggling_mode: false,   // Not toggling initially
+            telemetry: PrintTelemetry::default(),
+            printer_firmware: None,
+            config,
+            download_progress: None,
+            walrus_health: Arc::new(AggregatorHealth::default()),
+            show_diagnostics: false,
+            seal: Arc::new(SealService::default()),
+            key_server_checks: None,
+            print_abort: None,
         };
         
         // Check if printer registration is needed
@@ -199,6 +238,9 @@ impl App {
         let address = self.wallet.get_active_address().await?;
         
         self.sui_balance = self.wallet.get_sui_balance(address).await?;
+        if let Ok(coins) = self.wallet.get_sui_coins(address).await {
+            self.sui_coins = coins;
+        }
         self.wal_balance = self.wallet.get_walrus_balance(address).await?;
         
         match self.wallet.get_printer_info(address).await {
@@ -253,7 +295,9 @@ impl App {
                     }
                 }
                 Ok(None) => {
-                    self.print_status = PrintStatus::Idle;
+                    if !matches!(self.print_status, PrintStatus::Error(_)) {
+                        self.print_status = PrintStatus::Idle;
+                    }
                     self.script_status = ScriptStatus::Idle;
                 }
                 Err(e) => {
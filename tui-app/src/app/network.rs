@@ -1,11 +1,49 @@
 use crate::app::core::App;
-use crate::constants::NETWORKS;
+use crate::constants::{NETWORKS, WALRUS_AGGREGATORS};
 use anyhow::Result;
 use futures;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use crate::config::SealNetworkConfig;
+use crate::seal::key_servers;
 use crate::utils::setup_for_read;
+use crate::walrus::WalrusClient;
 
 impl App {
+    /// Aggregators for the current network: `walrus.aggregators` when set, else the built-in list.
+    pub fn walrus_aggregators(&self) -> Vec<String> {
+        let network = self.network_state.get_current_network();
+        match self.config.walrus.aggregators.get(network) {
+            Some(hosts) if !hosts.is_empty() => hosts.clone(),
+            _ => WALRUS_AGGREGATORS[self.network_state.current_network]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        }
+    }
+
+    /// `seal.<network>` for the current network; defaults (no key server restriction) otherwise.
+    pub fn seal_key_servers(&self) -> SealNetworkConfig {
+        let network = self.network_state.get_current_network();
+        self.config.seal.networks.get(network).cloned().unwrap_or_default()
+    }
+
+    /// Probe the current network's configured key servers into `key_server_checks` (diagnostics view).
+    pub async fn check_key_servers(app: Arc<Mutex<App>>) {
+        let config = {
+            let mut g = app.lock().await;
+            g.key_server_checks = None;
+            g.seal_key_servers()
+        };
+        let checks = key_servers::check_all(&config.key_servers, Duration::from_secs(config.timeout_secs)).await;
+        app.lock().await.key_server_checks = Some(checks);
+    }
+
+    pub fn walrus_client(&self) -> Result<WalrusClient> {
+        WalrusClient::new(&self.config.walrus, self.walrus_aggregators(), Arc::clone(&self.walrus_health))
+    }
+
     #[allow(dead_code)]
     pub fn switch_network(&mut self) {
         self.network_state.next_network();
@@ -39,6 +77,7 @@ impl App {
         self.wallet = crate::wallet::Wallet::new(&self.network_state, rpc, address).await;
         self.wallet_address = crate::utils::shorten_id(&self.wallet.get_active_address().await?.to_string());
         self.sui_balance = self.wallet.get_sui_balance(self.wallet.get_active_address().await?).await?;
+        self.sui_coins = self.wallet.get_sui_coins(self.wallet.get_active_address().await?).await.unwrap_or_default();
         self.wal_balance = self.wallet.get_walrus_balance(self.wallet.get_active_address().await?).await?;
         
         // update printer info and reward balance
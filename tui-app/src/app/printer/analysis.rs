@@ -0,0 +1,323 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+/// Marlin's `DEFAULT_ACCELERATION` on an Ender-3, used until the file sets one with `M204`.
+const DEFAULT_ACCELERATION: f64 = 500.0;
+/// mm/min, until the first `F` word.
+const DEFAULT_FEEDRATE: f64 = 1500.0;
+
+/// Pre-scan of a sliced file, indexed by command number as eureka-serial counts them (non-empty
+/// lines once `;` comments are stripped), so `progress` events map straight onto it.
+#[derive(Debug, Clone, Default)]
+pub struct GcodeAnalysis {
+    /// Estimated seconds from the start to the end of each command.
+    command_end_secs: Vec<f32>,
+    /// Number of commands before each layer-change comment.
+    layer_starts: Vec<u64>,
+    /// `;LAYER_COUNT:` (Cura) or `; total layer number:` (Orca); otherwise the markers counted.
+    declared_layers: Option<u32>,
+    /// The slicer's own total, which accounts for junction speeds better than the model here.
+    pub slicer_estimate: Option<Duration>,
+}
+
+impl GcodeAnalysis {
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut analysis = GcodeAnalysis::default();
+        let mut machine = Machine::default();
+        let mut elapsed = 0.0f64;
+        for line in reader.lines() {
+            let line = line?;
+            let (code, comment) = match line.find(';') {
+                Some(i) => (line[..i].trim(), Some(line[i + 1..].trim())),
+                None => (line.trim(), None),
+            };
+            if let Some(comment) = comment {
+                analysis.read_comment(comment);
+            }
+            if !code.is_empty() {
+                elapsed += machine.execute(code);
+                analysis.command_end_secs.push(elapsed as f32);
+            }
+        }
+        Ok(analysis)
+    }
+
+    fn read_comment(&mut self, comment: &str) {
+        let commands = self.command_end_secs.len() as u64;
+        if comment == "LAYER_CHANGE" || comment.starts_with("LAYER:") {
+            self.layer_starts.push(commands);
+        } else if let Some(n) = comment.strip_prefix("LAYER_COUNT:") {
+            self.declared_layers = n.trim().parse().ok();
+        } else if let Some(n) = comment.strip_prefix("total layer number:") {
+            self.declared_layers = n.trim().parse().ok();
+        } else if let Some(secs) = comment.strip_prefix("TIME:") {
+            self.slicer_estimate = secs.trim().parse().ok().map(Duration::from_secs);
+        } else if let Some((_, value)) = comment.split_once("estimated printing time (normal mode) =") {
+            self.slicer_estimate = parse_duration(value);
+        } else if let Some((_, value)) = comment.split_once("total estimated time:") {
+            self.slicer_estimate = parse_duration(value);
+        }
+    }
+
+    pub fn commands(&self) -> u64 {
+        self.command_end_secs.len() as u64
+    }
+
+    pub fn layer_count(&self) -> Option<u32> {
+        self.declared_layers
+            .or_else(|| (!self.layer_starts.is_empty()).then_some(self.layer_starts.len() as u32))
+    }
+
+    /// Total time: the slicer's figure when it wrote one, otherwise the move model's.
+    pub fn total(&self) -> Duration {
+        self.slicer_estimate
+            .unwrap_or_else(|| Duration::from_secs_f32(self.modeled_total()))
+    }
+
+    /// Share of the print time covered by the first `sent` commands.
+    pub fn fraction_at(&self, sent: u64) -> Option<f64> {
+        let total = self.modeled_total();
+        if total <= 0.0 {
+            return None;
+        }
+        Some((self.modeled_at(sent) / total).clamp(0.0, 1.0) as f64)
+    }
+
+    /// Print time left after `sent` commands, scaled to the slicer's total when there is one.
+    pub fn remaining_at(&self, sent: u64) -> Option<Duration> {
+        let fraction = self.fraction_at(sent)?;
+        Some(self.total().mul_f64(1.0 - fraction))
+    }
+
+    /// 1-based layer the `sent`-th command belongs to; `None` before the first layer marker.
+    pub fn layer_at(&self, sent: u64) -> Option<u32> {
+        match self.layer_starts.partition_point(|&start| start < sent) {
+            0 => None,
+            n => Some(n as u32),
+        }
+    }
+
+    fn modeled_total(&self) -> f32 {
+        self.command_end_secs.last().copied().unwrap_or(0.0)
+    }
+
+    fn modeled_at(&self, sent: u64) -> f32 {
+        match sent.min(self.commands()) {
+            0 => 0.0,
+            n => self.command_end_secs[n as usize - 1],
+        }
+    }
+}
+
+/// Just enough machine state to time moves: position, modes, feedrate and acceleration.
+struct Machine {
+    position: [f64; 4],
+    absolute: bool,
+    relative_e: bool,
+    feedrate: f64,
+    acceleration: f64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 4],
+            absolute: true,
+            relative_e: false,
+            feedrate: DEFAULT_FEEDRATE,
+            acceleration: DEFAULT_ACCELERATION,
+        }
+    }
+}
+
+impl Machine {
+    /// Apply one command and return its estimated duration in seconds.
+    fn execute(&mut self, code: &str) -> f64 {
+        let mut words = code.split_whitespace();
+        let Some(command) = words.next() else {
+            return 0.0;
+        };
+        let args: Vec<(char, f64)> = words
+            .filter_map(|w| {
+                let mut chars = w.chars();
+                let letter = chars.next()?.to_ascii_uppercase();
+                Some((letter, chars.as_str().parse().ok()?))
+            })
+            .collect();
+        let arg = |letter: char| args.iter().find(|(l, _)| *l == letter).map(|(_, v)| *v);
+
+        match command.to_ascii_uppercase().as_str() {
+            "G0" | "G1" | "G2" | "G3" => {
+                if let Some(f) = arg('F') {
+                    self.feedrate = f;
+                }
+                let from = self.position;
+                for (i, axis) in ['X', 'Y', 'Z', 'E'].iter().enumerate() {
+                    if let Some(v) = arg(*axis) {
+                        let relative = if i == 3 { self.relative_e || !self.absolute } else { !self.absolute };
+                        self.position[i] = if relative { self.position[i] + v } else { v };
+                    }
+                }
+                let [dx, dy, dz, de] = [0, 1, 2, 3].map(|i| self.position[i] - from[i]);
+                let mut distance = (dx * dx + dy * dy + dz * dz).sqrt();
+                if let (true, Some(i), Some(j)) = (command.ends_with('2') || command.ends_with('3'), arg('I'), arg('J')) {
+                    distance = arc_length(dx, dy, i, j, command.ends_with('2'));
+                }
+                if distance == 0.0 {
+                    distance = de.abs();
+                }
+                move_time(distance, self.feedrate / 60.0, self.acceleration)
+            }
+            "G4" => arg('S').unwrap_or(0.0) + arg('P').unwrap_or(0.0) / 1000.0,
+            "G28" => {
+                for (i, axis) in ['X', 'Y', 'Z'].iter().enumerate() {
+                    if args.is_empty() || arg(*axis).is_some() {
+                        self.position[i] = 0.0;
+                    }
+                }
+                0.0
+            }
+            "G90" => {
+                self.absolute = true;
+                0.0
+            }
+            "G91" => {
+                self.absolute = false;
+                0.0
+            }
+            "G92" => {
+                for (i, axis) in ['X', 'Y', 'Z', 'E'].iter().enumerate() {
+                    if let Some(v) = arg(*axis) {
+                        self.position[i] = v;
+                    }
+                }
+                0.0
+            }
+            "M82" => {
+                self.relative_e = false;
+                0.0
+            }
+            "M83" => {
+                self.relative_e = true;
+                0.0
+            }
+            "M204" => {
+                if let Some(a) = arg('P').or(arg('S')).filter(|a| *a > 0.0) {
+                    self.acceleration = a;
+                }
+                0.0
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Trapezoidal profile from and back to standstill: conservative, since the planner keeps speed
+/// through junctions; the slicer's total corrects for that when present.
+fn move_time(distance: f64, speed: f64, acceleration: f64) -> f64 {
+    if distance <= 0.0 || speed <= 0.0 {
+        return 0.0;
+    }
+    let ramp = speed * speed / acceleration;
+    if distance >= ramp {
+        distance / speed + speed / acceleration
+    } else {
+        2.0 * (distance / acceleration).sqrt()
+    }
+}
+
+/// Length of a `G2` (clockwise) / `G3` arc from the current point, with the centre at `I`/`J`.
+fn arc_length(dx: f64, dy: f64, i: f64, j: f64, clockwise: bool) -> f64 {
+    let radius = (i * i + j * j).sqrt();
+    let start = (-j).atan2(-i);
+    let end = (dy - j).atan2(dx - i);
+    let mut sweep = end - start;
+    if clockwise && sweep >= 0.0 {
+        sweep -= std::f64::consts::TAU;
+    } else if !clockwise && sweep <= 0.0 {
+        sweep += std::f64::consts::TAU;
+    }
+    radius * sweep.abs()
+}
+
+/// `1d 2h 3m 4s` / `41m 7s` as written by PrusaSlicer and Orca.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let mut secs = 0u64;
+    let mut seen = false;
+    for part in text.split_whitespace() {
+        let (value, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+        let value: u64 = value.parse().ok()?;
+        secs += value
+            * match unit {
+                "d" => 86_400,
+                "h" => 3_600,
+                "m" => 60,
+                "s" => 1,
+                _ => return None,
+            };
+        seen = true;
+    }
+    seen.then(|| Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRUSA: &str = "\
+; generated by PrusaSlicer
+M204 S1000
+G28 ; home
+;LAYER_CHANGE
+;Z:0.2
+G1 Z0.2 F600
+G1 X100 E5 F3000
+;LAYER_CHANGE
+;Z:0.4
+G1 Z0.4 F600
+G1 X0 E10 F3000
+; estimated printing time (normal mode) = 1m 30s
+";
+
+    #[test]
+    fn test_layers_and_slicer_time() {
+        let a = GcodeAnalysis::from_reader(PRUSA.as_bytes()).unwrap();
+        assert_eq!(a.commands(), 6);
+        assert_eq!(a.slicer_estimate, Some(Duration::from_secs(90)));
+        assert_eq!(a.layer_count(), Some(2));
+        assert_eq!(a.layer_at(2), None);
+        assert_eq!(a.layer_at(3), Some(1));
+        assert_eq!(a.layer_at(5), Some(2));
+        assert_eq!(a.layer_at(6), Some(2));
+    }
+
+    #[test]
+    fn test_progress_follows_move_time() {
+        let a = GcodeAnalysis::from_reader(PRUSA.as_bytes()).unwrap();
+        // The two 100 mm moves dominate; the Z hops are short.
+        let half = a.fraction_at(4).unwrap();
+        assert!(half > 0.4 && half < 0.6, "{}", half);
+        assert_eq!(a.fraction_at(6), Some(1.0));
+        assert_eq!(a.remaining_at(0), Some(Duration::from_secs(90)));
+        assert_eq!(a.remaining_at(6), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_move_time_and_durations() {
+        // 100 mm at 50 mm/s, 500 mm/s²: 2 s cruising plus 0.1 s lost to ramps.
+        assert!((move_time(100.0, 50.0, 500.0) - 2.1).abs() < 1e-9);
+        // Too short to reach speed: triangle profile.
+        assert!((move_time(1.0, 50.0, 500.0) - 2.0 * (1.0f64 / 500.0).sqrt()).abs() < 1e-9);
+        // Half circle of radius 10, counter-clockwise.
+        assert!((arc_length(20.0, 0.0, 10.0, 0.0, false) - std::f64::consts::PI * 10.0).abs() < 1e-9);
+        assert_eq!(parse_duration(" 1h 2m 3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("1d 0h 0m 1s"), Some(Duration::from_secs(86_401)));
+        assert_eq!(parse_duration("soon"), None);
+    }
+}
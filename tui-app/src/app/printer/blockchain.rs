@@ -1,5 +1,8 @@
 use crate::app::{App, MessageType};
 use crate::app::{PrintStatus, ScriptStatus};
+use crate::eureka_error::{describe, EurekaError};
+use crate::transactions::GasEstimate;
+use crate::utils::format_sui_balance;
 use std::sync::Arc;
 use sui_sdk_types::Address;
 use tokio::sync::Mutex;
@@ -9,7 +12,7 @@ use tokio::sync::Mutex;
 pub(crate) async fn run_clear_stuck_print_job_from_selection(
     app: Arc<Mutex<App>>,
 ) -> Result<(), String> {
-    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state) = {
+    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         let idx = g
             .sculpt_state
@@ -26,6 +29,7 @@ pub(crate) async fn run_clear_stuck_print_job_from_selection(
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
     let address = wallet.address;
@@ -46,7 +50,8 @@ pub(crate) async fn run_clear_stuck_print_job_from_selection(
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -89,7 +94,7 @@ pub(crate) async fn run_clear_stuck_print_job_from_selection(
             Ok(())
         }
         Err(e) => {
-            let user_friendly_error = parse_blockchain_error(&e.to_string(), "clear stuck print job");
+            let user_friendly_error = parse_blockchain_error(&e, "clear stuck print job");
             let mut g = app.lock().await;
             g.print_output.push(format!(
                 "[LOG] Failed to clear stuck PrintJob: {}",
@@ -103,7 +108,7 @@ pub(crate) async fn run_clear_stuck_print_job_from_selection(
 
 /// Create print job from current offline sculpt selection — **no** `App` mutex held across network I/O.
 pub(crate) async fn run_create_print_job_from_selection(app: Arc<Mutex<App>>) -> Result<(), String> {
-    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state) = {
+    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         let idx = g
             .sculpt_state
@@ -120,6 +125,7 @@ pub(crate) async fn run_create_print_job_from_selection(app: Arc<Mutex<App>>) ->
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
     let address = wallet.address;
@@ -135,7 +141,8 @@ pub(crate) async fn run_create_print_job_from_selection(app: Arc<Mutex<App>>) ->
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -172,9 +179,9 @@ pub(crate) async fn run_create_print_job_from_selection(app: Arc<Mutex<App>>) ->
             Ok(())
         }
         Err(e) => {
-            let user_friendly_error = parse_blockchain_error(&e.to_string(), "create print job");
+            let user_friendly_error = parse_blockchain_error(&e, "create print job");
             let mut g = app.lock().await;
-            if user_friendly_error.contains("A print job already exists") {
+            if e.downcast_ref::<EurekaError>() == Some(&EurekaError::PrintJobExists) {
                 g.print_output.push(
                     "[LOG] A print job already exists, continuing with printing...".to_string(),
                 );
@@ -193,7 +200,7 @@ pub(crate) async fn run_create_print_job_from_selection(app: Arc<Mutex<App>>) ->
 
 /// Start print job from current sculpt selection — **no** `App` mutex held across network I/O.
 pub(crate) async fn run_start_print_job_from_selection(app: Arc<Mutex<App>>) -> Result<(), String> {
-    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state) = {
+    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         let idx = g
             .sculpt_state
@@ -210,6 +217,7 @@ pub(crate) async fn run_start_print_job_from_selection(app: Arc<Mutex<App>>) ->
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
     let address = wallet.address;
@@ -230,7 +238,8 @@ pub(crate) async fn run_start_print_job_from_selection(app: Arc<Mutex<App>>) ->
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -271,7 +280,7 @@ pub(crate) async fn run_start_print_job_from_selection(app: Arc<Mutex<App>>) ->
             Ok(())
         }
         Err(e) => {
-            let user_friendly_error = parse_blockchain_error(&e.to_string(), "start print job");
+            let user_friendly_error = parse_blockchain_error(&e, "start print job");
             let mut g = app.lock().await;
             g.set_message(MessageType::Error, user_friendly_error.clone());
             Err(user_friendly_error)
@@ -288,7 +297,7 @@ pub(crate) async fn run_start_print_job_for_active_task(
     let sculpt_id_str = task.sculpt_blob_id.clone();
     let sculpt_id = App::parse_object_id(&sculpt_id_str, "sculpt ID")?;
 
-    let (mut source_kiosk_id, wallet, sui_rpc, tx_signer, network_state) = {
+    let (mut source_kiosk_id, wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         let from_list = g
             .sculpt_items
@@ -303,6 +312,7 @@ pub(crate) async fn run_start_print_job_for_active_task(
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
 
@@ -331,7 +341,8 @@ pub(crate) async fn run_start_print_job_for_active_task(
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -375,7 +386,7 @@ pub(crate) async fn run_start_print_job_for_active_task(
             Ok(())
         }
         Err(e) => {
-            let user_friendly_error = parse_blockchain_error(&e.to_string(), "start print job");
+            let user_friendly_error = parse_blockchain_error(&e, "start print job");
             let mut g = app.lock().await;
             g.print_output.push(format!(
                 "[MOCK] start_print_job failed: {}",
@@ -391,7 +402,7 @@ pub(crate) async fn run_start_print_job_for_active_task(
 pub(crate) async fn run_complete_print_job_from_sculpt_selection(
     app: Arc<Mutex<App>>,
 ) -> Result<(), String> {
-    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state) = {
+    let (sculpt_id_str, source_kiosk_id, wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         let idx = g
             .sculpt_state
@@ -408,6 +419,7 @@ pub(crate) async fn run_complete_print_job_from_sculpt_selection(
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
     let address = wallet.address;
@@ -427,7 +439,8 @@ pub(crate) async fn run_complete_print_job_from_sculpt_selection(
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -478,7 +491,7 @@ pub(crate) async fn run_complete_print_job_from_sculpt_selection(
             Ok(())
         }
         Err(e) => {
-            let user_friendly_error = parse_blockchain_error(&e.to_string(), "complete print job");
+            let user_friendly_error = parse_blockchain_error(&e, "complete print job");
             let mut g = app.lock().await;
             g.set_message(MessageType::Error, user_friendly_error.clone());
             Err(user_friendly_error)
@@ -488,13 +501,14 @@ pub(crate) async fn run_complete_print_job_from_sculpt_selection(
 
 /// Transfer completed print job (mock / task flow) — **no** `App` mutex held across the transaction.
 pub(crate) async fn run_transfer_completed_print_job(app: Arc<Mutex<App>>) -> Result<(), String> {
-    let (wallet, sui_rpc, tx_signer, network_state) = {
+    let (wallet, sui_rpc, tx_signer, network_state, gas) = {
         let g = app.lock().await;
         (
             g.wallet.clone(),
             Arc::clone(&g.sui_rpc),
             g.tx_signer.clone(),
             g.network_state.clone(),
+            g.config.gas.clone(),
         )
     };
     let address = wallet.address;
@@ -514,7 +528,8 @@ pub(crate) async fn run_transfer_completed_print_job(app: Arc<Mutex<App>>) -> Re
         address,
         network_state,
     )
-    .with_printer_eureka_package(&info.eureka_package_id);
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
     {
         let mut g = app.lock().await;
         g.set_message(
@@ -555,7 +570,7 @@ pub(crate) async fn run_transfer_completed_print_job(app: Arc<Mutex<App>>) -> Re
         }
         Err(e) => {
             let user_friendly_error =
-                parse_blockchain_error(&e.to_string(), "transfer completed PrintJob");
+                parse_blockchain_error(&e, "transfer completed PrintJob");
             let mut g = app.lock().await;
             g.set_message(MessageType::Error, user_friendly_error.clone());
             Err(user_friendly_error)
@@ -563,34 +578,237 @@ pub(crate) async fn run_transfer_completed_print_job(app: Arc<Mutex<App>>) -> Re
     }
 }
 
-fn parse_blockchain_error(error_msg: &str, context: &str) -> String {
-    // PTB simulation uses MoveAbort(MoveLocation { ... function: ... }, <code>) — no "EPrintJobExists" string.
-    if error_msg.contains("MoveAbort") || error_msg.contains("MOVE_ABORT") {
-        if error_msg.contains("create_and_assign_print_job_internal")
-            || error_msg.contains("create_print_job_from_kiosk")
-        {
-            if error_msg.contains(", 2)") {
-                return "A print job already exists for this printer.".to_string();
-            }
-        }
+/// Withdraw the printer's fee pool (`eureka::withdraw_fees`) to the wallet, then reload the pool and
+/// SUI balances from chain — **no** `App` mutex held across the transaction.
+pub(crate) async fn run_withdraw_fees(app: Arc<Mutex<App>>) -> Result<(), String> {
+    let result = withdraw_fees(&app).await;
+    let mut g = app.lock().await;
+    g.is_withdrawing_fees = false;
+    match &result {
+        Ok(Some((tx_id, amount))) => g.set_message(
+            MessageType::Success,
+            format!("Withdrew {} (Tx: {})", format_sui_balance(*amount), tx_id),
+        ),
+        Ok(None) => g.set_message(MessageType::Info, "No fees to withdraw".to_string()),
+        Err(e) => g.set_message(MessageType::Error, e.clone()),
     }
+    result.map(|_| ())
+}
 
-    if error_msg.contains("TransactionEffects") && error_msg.contains("status") {
-        if error_msg.contains("EPrintJobExists") {
-            "A print job already exists for this printer.".to_string()
-        } else if error_msg.contains("EPrintJobNotStarted") {
-            "Print job has not been started yet. Please start the print job first.".to_string()
-        } else if error_msg.contains("EPrintJobCompleted") {
-            "Print job has already been completed.".to_string()
-        } else if error_msg.contains("ENotAuthorized") {
-            "Not authorized to complete this print job.".to_string()
-        } else if error_msg.contains("dynamic_field") && error_msg.contains("borrow_child_object") {
-            "Failed to find print job. Please make sure printer is properly registered.".to_string()
-        } else if error_msg.contains("insufficient gas") {
-            "Insufficient gas to execute transaction. Please add more SUI to your wallet.".to_string()
-        } else {
-            format!("Transaction failed on blockchain: {}", error_msg)
-        }
+/// Digest and amount withdrawn; `None` when the pool is empty.
+async fn withdraw_fees(app: &Arc<Mutex<App>>) -> Result<Option<(String, u128)>, String> {
+    let (wallet, sui_rpc, tx_signer, network_state, gas) = {
+        let g = app.lock().await;
+        (
+            g.wallet.clone(),
+            Arc::clone(&g.sui_rpc),
+            g.tx_signer.clone(),
+            g.network_state.clone(),
+            g.config.gas.clone(),
+        )
+    };
+    let address = wallet.address;
+    let info = wallet
+        .get_printer_info(address)
+        .await
+        .map_err(|e| format!("Failed to get printer info: {}", e))?;
+    if info.pool_balance == 0 {
+        app.lock().await.harvestable_rewards = format_sui_balance(0);
+        return Ok(None);
+    }
+    let cap_id = wallet
+        .get_printer_cap_id(address)
+        .await
+        .map_err(|e| format!("Failed to get PrinterCap ID: {}", e))?;
+    let printer_cap_id = App::parse_object_id(&cap_id, "printer cap ID")?;
+    let printer_object_id = App::parse_object_id(&info.id, "printer object ID")?;
+    let builder = crate::transactions::TransactionBuilder::new(
+        sui_rpc,
+        (*tx_signer).clone(),
+        address,
+        network_state,
+    )
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
+    {
+        let mut g = app.lock().await;
+        g.set_message(
+            MessageType::Info,
+            format!("Withdrawing {} in fees, waiting for blockchain confirmation...", format_sui_balance(info.pool_balance)),
+        );
+    }
+    // The whole pool at execution time is withdrawn, including fees paid since it was read above,
+    // so the amount reported is the one the transaction moved.
+    let (tx_id, amount) = builder
+        .withdraw_fees(printer_cap_id, printer_object_id)
+        .await
+        .map_err(|e| parse_blockchain_error(&e, "withdraw fees"))?;
+
+    let pool = wallet.get_printer_info(address).await.map(|i| i.pool_balance);
+    let sui = wallet.get_sui_balance(address).await;
+    let coins = wallet.get_sui_coins(address).await;
+    let mut g = app.lock().await;
+    g.print_output.push(format!(
+        "[LOG] Withdrew {} in fees (Tx: {})",
+        format_sui_balance(amount),
+        tx_id
+    ));
+    match pool {
+        Ok(pool) => g.harvestable_rewards = format_sui_balance(pool),
+        Err(e) => g.print_output.push(format!("[LOG] Could not refresh fee pool: {}", e)),
+    }
+    if let Ok(sui) = sui {
+        g.sui_balance = sui;
+    }
+    if let Ok(coins) = coins {
+        g.sui_coins = coins;
+    }
+    Ok(Some((tx_id, amount)))
+}
+
+/// Merge the wallet's dust SUI coins into one, then reload the coin list and balance. **No** `App`
+/// mutex held across the transaction.
+pub(crate) async fn run_merge_dust_coins(app: Arc<Mutex<App>>) -> Result<(), String> {
+    let result = merge_dust_coins(&app).await;
+    let mut g = app.lock().await;
+    g.is_merging_coins = false;
+    match &result {
+        Ok(Some((tx_id, merged))) => g.set_message(
+            MessageType::Success,
+            format!("Merged {} SUI coins (Tx: {})", merged, tx_id),
+        ),
+        Ok(None) => g.set_message(MessageType::Info, "No dust coins to merge".to_string()),
+        Err(e) => g.set_message(MessageType::Error, e.clone()),
+    }
+    result.map(|_| ())
+}
+
+async fn merge_dust_coins(app: &Arc<Mutex<App>>) -> Result<Option<(String, usize)>, String> {
+    let (wallet, sui_rpc, tx_signer, network_state, gas) = {
+        let g = app.lock().await;
+        (
+            g.wallet.clone(),
+            Arc::clone(&g.sui_rpc),
+            g.tx_signer.clone(),
+            g.network_state.clone(),
+            g.config.gas.clone(),
+        )
+    };
+    let address = wallet.address;
+    let builder = crate::transactions::TransactionBuilder::new(
+        sui_rpc,
+        (*tx_signer).clone(),
+        address,
+        network_state,
+    )
+    .with_gas(gas);
+    let merged = builder
+        .merge_dust_coins()
+        .await
+        .map_err(|e| parse_blockchain_error(&e, "merge coins"))?;
+
+    let coins = wallet.get_sui_coins(address).await;
+    let sui = wallet.get_sui_balance(address).await;
+    let mut g = app.lock().await;
+    if let Some((tx_id, count)) = &merged {
+        g.print_output.push(format!("[LOG] Merged {} SUI coins (Tx: {})", count, tx_id));
+    }
+    if let Ok(coins) = coins {
+        g.sui_coins = coins;
+    }
+    if let Ok(sui) = sui {
+        g.sui_balance = sui;
+    }
+    Ok(merged)
+}
+
+/// Transaction a confirmation prompt is about to sign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfirmedCall {
+    /// `eureka::update_printer_status` (online/offline toggle).
+    UpdatePrinterStatus,
+    /// `eureka::withdraw_fees` (harvest).
+    WithdrawFees,
+    /// `MergeCoins` of the wallet's dust SUI coins.
+    MergeCoins,
+}
+
+/// Simulate `call` for the fee line of its confirmation prompt. The result is dropped if the prompt
+/// was answered meanwhile.
+pub(crate) async fn run_estimate_gas(app: Arc<Mutex<App>>, call: ConfirmedCall) {
+    let estimate = estimate_gas(&app, call).await;
+    let mut g = app.lock().await;
+    let open = match call {
+        ConfirmedCall::UpdatePrinterStatus => g.is_confirming,
+        ConfirmedCall::WithdrawFees => g.is_harvesting,
+        ConfirmedCall::MergeCoins => g.is_confirming_merge,
+    };
+    if open {
+        g.gas_estimate = Some(estimate);
+    }
+}
+
+async fn estimate_gas(app: &Arc<Mutex<App>>, call: ConfirmedCall) -> Result<GasEstimate, String> {
+    let (wallet, sui_rpc, tx_signer, network_state, gas, printer_id) = {
+        let g = app.lock().await;
+        (
+            g.wallet.clone(),
+            Arc::clone(&g.sui_rpc),
+            g.tx_signer.clone(),
+            g.network_state.clone(),
+            g.config.gas.clone(),
+            g.printer_id.clone(),
+        )
+    };
+    let address = wallet.address;
+    if call == ConfirmedCall::MergeCoins {
+        // Not a printer call: no PrinterCap or package to look up.
+        let builder = crate::transactions::TransactionBuilder::new(sui_rpc, (*tx_signer).clone(), address, network_state)
+            .with_gas(gas);
+        return match builder.estimate_merge_dust_coins().await {
+            Ok(Some(estimate)) => Ok(estimate),
+            Ok(None) => Err("no dust coins to merge".to_string()),
+            Err(e) => Err(parse_blockchain_error(&e, "estimate gas")),
+        };
+    }
+    if printer_id == "No Printer ID" {
+        return Err("printer not registered".to_string());
+    }
+    let info = wallet
+        .get_printer_info(address)
+        .await
+        .map_err(|e| format!("Failed to get printer info: {}", e))?;
+    let cap_id = wallet
+        .get_printer_cap_id(address)
+        .await
+        .map_err(|e| format!("Failed to get PrinterCap ID: {}", e))?;
+    let printer_cap_id = App::parse_object_id(&cap_id, "printer cap ID")?;
+    let printer_object_id = App::parse_object_id(&info.id, "printer object ID")?;
+    let builder = crate::transactions::TransactionBuilder::new(
+        sui_rpc,
+        (*tx_signer).clone(),
+        address,
+        network_state,
+    )
+    .with_printer_eureka_package(&info.eureka_package_id)
+    .with_gas(gas);
+    let estimate = match call {
+        ConfirmedCall::UpdatePrinterStatus => builder.estimate_update_printer_status(printer_cap_id, printer_object_id).await,
+        ConfirmedCall::WithdrawFees => builder.estimate_withdraw_fees(printer_cap_id, printer_object_id).await,
+        ConfirmedCall::MergeCoins => unreachable!("merge is estimated without a printer, above"),
+    };
+    estimate.map_err(|e| parse_blockchain_error(&e, "estimate gas"))
+}
+
+/// Status line for a failed transaction. Move aborts arrive decoded as [`EurekaError`] and read
+/// with their recovery step; other failures keep their text.
+fn parse_blockchain_error(error: &anyhow::Error, context: &str) -> String {
+    if error.downcast_ref::<EurekaError>().is_some() {
+        return format!("Failed to {}: {}", context, describe(error));
+    }
+    let error_msg = error.to_string();
+    if error_msg.contains("InsufficientGas") || error_msg.contains("insufficient gas") {
+        "Insufficient gas to execute transaction. Please add more SUI to your wallet.".to_string()
     } else {
         format!("Failed to {}: {}", context, error_msg)
     }
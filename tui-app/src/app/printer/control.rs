@@ -0,0 +1,185 @@
+use crate::app::core::App;
+use crate::app::{MessageType, ScriptStatus};
+use crate::constants::SERIAL_CONTROL_SOCKET;
+use crate::home::eureka_home;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::Mutex;
+
+/// Commands understood by the eureka-serial control socket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrintControl {
+    Pause,
+    Resume,
+    Cancel,
+    EmergencyStop,
+}
+
+impl PrintControl {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrintControl::Pause => "pause",
+            PrintControl::Resume => "resume",
+            PrintControl::Cancel => "cancel",
+            PrintControl::EmergencyStop => "estop",
+        }
+    }
+}
+
+/// Socket eureka-serial listens on (passed as `EUREKA_CONTROL_SOCKET` by the print pipeline).
+pub fn control_socket_path() -> PathBuf {
+    eureka_home()
+        .join("Gcode-Transmit")
+        .join("main")
+        .join(SERIAL_CONTROL_SOCKET)
+}
+
+/// Send one command and wait for eureka-serial's `ok`, returning the warning of an `ok: …` reply.
+/// The effect itself arrives as an event.
+async fn send_control_command(command: PrintControl) -> Result<Option<String>, String> {
+    let path = control_socket_path();
+    let stream = UnixStream::connect(&path)
+        .await
+        .map_err(|e| format!("Printer control unavailable ({}): {}", path.display(), e))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", command.as_str()).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send {}: {}", command.as_str(), e))?;
+
+    let mut reply = String::new();
+    BufReader::new(reader)
+        .read_line(&mut reply)
+        .await
+        .map_err(|e| format!("No reply to {}: {}", command.as_str(), e))?;
+    match reply.trim() {
+        "ok" => Ok(None),
+        other => match other.strip_prefix("ok:") {
+            Some(warning) => Ok(Some(warning.trim().to_string())),
+            None => Err(other.to_string()),
+        },
+    }
+}
+
+impl App {
+    pub fn start_cancel_print_confirm(&mut self) {
+        if matches!(self.script_status, ScriptStatus::Running) {
+            self.is_cancelling_print = true;
+        } else {
+            self.set_message(MessageType::Error, "No print is running.".to_string());
+        }
+    }
+
+    pub fn abort_cancel_print(&mut self) {
+        self.is_cancelling_print = false;
+    }
+
+    pub fn start_estop_confirm(&mut self) {
+        if matches!(self.script_status, ScriptStatus::Running) {
+            // The e-stop takes over the Y key from whatever prompt was open.
+            self.is_cancelling_print = false;
+            self.is_confirming = false;
+            self.is_harvesting = false;
+            self.is_confirming_merge = false;
+            self.is_switching_network = false;
+            self.is_confirming_estop = true;
+        } else {
+            self.set_message(MessageType::Error, "No print is running.".to_string());
+        }
+    }
+
+    pub fn abort_estop(&mut self) {
+        self.is_confirming_estop = false;
+    }
+
+    /// `M112` through eureka-serial's own port handle, ahead of anything queued. The job is failed
+    /// by the `emergency_stop` / `failed` events that follow.
+    pub async fn emergency_stop(app: Arc<Mutex<App>>) {
+        {
+            let mut g = app.lock().await;
+            g.is_confirming_estop = false;
+            g.print_output.push("[CONTROL] Sending emergency stop (M112)".to_string());
+        }
+        let result = send_control_command(PrintControl::EmergencyStop).await;
+        let mut g = app.lock().await;
+        match result {
+            Ok(None) => g.set_message(
+                MessageType::Error,
+                "EMERGENCY STOP sent. Reset the printer before the next job.".to_string(),
+            ),
+            Ok(Some(warning)) => {
+                g.print_output.push(format!("[CONTROL] {}", warning));
+                g.set_message(
+                    MessageType::Error,
+                    "EMERGENCY STOP queued behind buffered commands (no EMERGENCY_PARSER). Cut printer power if unsafe."
+                        .to_string(),
+                );
+            }
+            Err(e) => {
+                g.print_output.push(format!("[CONTROL] Emergency stop failed: {}", e));
+                g.set_message(
+                    MessageType::Error,
+                    format!("Emergency stop NOT sent ({}). Cut printer power if unsafe.", e),
+                );
+            }
+        }
+    }
+
+    pub async fn pause_print(app: Arc<Mutex<App>>) {
+        {
+            let mut g = app.lock().await;
+            if !matches!(g.script_status, ScriptStatus::Running) || g.telemetry.paused {
+                g.set_message(MessageType::Error, "No running print to pause.".to_string());
+                return;
+            }
+            g.set_message(MessageType::Info, "Pausing print...".to_string());
+        }
+        Self::send_print_control(app, PrintControl::Pause).await;
+    }
+
+    pub async fn resume_print(app: Arc<Mutex<App>>) {
+        {
+            let mut g = app.lock().await;
+            if !g.telemetry.paused {
+                g.set_message(MessageType::Error, "Print is not paused.".to_string());
+                return;
+            }
+            g.set_message(MessageType::Info, "Resuming print...".to_string());
+        }
+        Self::send_print_control(app, PrintControl::Resume).await;
+    }
+
+    /// Clean cancel through eureka-serial. When nothing is listening (still slicing, legacy `serial`
+    /// binary or `EUREKA_FLOW_CONTROL=delay`) the native pipeline is aborted instead, or
+    /// `Gcode-Stop.sh` runs for the script pipeline.
+    pub async fn cancel_print(app: Arc<Mutex<App>>) {
+        {
+            let mut g = app.lock().await;
+            g.is_cancelling_print = false;
+            g.set_message(MessageType::Info, "Stopping print...".to_string());
+        }
+        if let Err(e) = send_control_command(PrintControl::Cancel).await {
+            let mut g = app.lock().await;
+            match g.print_abort.clone() {
+                Some(abort) => {
+                    g.print_output.push(format!("[CONTROL] {}; aborting the pipeline", e));
+                    abort.notify_one();
+                }
+                None => {
+                    g.print_output.push(format!("[CONTROL] {}; using stop script", e));
+                    let _ = g.run_stop_script().await;
+                }
+            }
+        }
+    }
+
+    async fn send_print_control(app: Arc<Mutex<App>>, command: PrintControl) {
+        if let Err(e) = send_control_command(command).await {
+            let mut g = app.lock().await;
+            g.print_output.push(format!("[CONTROL] {}", e));
+            g.set_message(MessageType::Error, e);
+        }
+    }
+}
@@ -119,7 +119,7 @@ pub async fn run_mock_print_script(
             );
             // Do not call `update_blockchain_on_completion` here: that path uses `complete_print_job`,
             // which requires an owned `Sculpt` (kiosk-listed sculpts fail PTB simulation). Real print
-            // (`run_print_script`) spawns completion itself; mock PrintJob flow (`T`) calls
+            // (`run_print_pipeline`) spawns completion itself; mock PrintJob flow (`T`) calls
             // `run_transfer_completed_print_job` after this returns.
             Ok(true)
         },
@@ -2,4 +2,8 @@ mod operations;
 pub mod ui;
 pub(crate) mod blockchain;
 mod monitoring;
-pub mod mock;
\ No newline at end of file
+pub mod mock;
+pub mod telemetry;
+pub mod analysis;
+pub mod control;
+pub mod pipeline;
\ No newline at end of file
@@ -1,10 +1,37 @@
 use crate::app::core::App;
-use crate::constants::{GCODE_CHECK_INTERVAL_MILLIS, GCODE_WAIT_ATTEMPTS};
-use crate::utils::crate_root;
+use crate::app::MessageType;
+use super::analysis::GcodeAnalysis;
+use super::telemetry::SerialEvent;
+use crate::constants::{GCODE_CHECK_INTERVAL_MILLIS, GCODE_WAIT_ATTEMPTS, PRINT_OUTPUT_MAX_LINES, SERIAL_EVENT_POLL_INTERVAL_MILLIS};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Handle on a running `App::setup_event_monitoring` task.
+pub(super) struct EventMonitor {
+    stop: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl EventMonitor {
+    pub(super) fn new(stop: oneshot::Sender<()>, task: JoinHandle<()>) -> Self {
+        Self { stop: Some(stop), task: Some(task) }
+    }
+
+    /// Read whatever eureka-serial wrote last and stop tailing; call once the sender has exited,
+    /// before looking at `App::telemetry`. Safe to call more than once.
+    pub(super) async fn finish(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
 
 impl App {
     async fn read_file_chunk(path: &Path, start: usize, end: usize) -> Option<String> {
@@ -24,14 +51,9 @@ impl App {
         }
     }
 
-    pub async fn setup_gcode_monitoring(app: Arc<Mutex<App>>) {
+    pub async fn setup_gcode_monitoring(app: Arc<Mutex<App>>, gcode_path: PathBuf) {
         let app_clone_for_monitor = Arc::clone(&app);
         let _gcode_monitor_handle = tokio::spawn(async move {
-            let gcode_path = crate_root()
-                .join("Gcode-Transmit")
-                .join("main")
-                .join("test.gcode");
-            
             let mut app_lock = app_clone_for_monitor.lock().await;
             app_lock.print_output.push(format!("[GCODE] Monitoring file: {}", gcode_path.display()));
             drop(app_lock);
@@ -78,6 +100,125 @@ impl App {
         });
     }
     
+    /// Tail the eureka-serial event file into `App::telemetry` until a terminal event arrives or
+    /// `stop` fires (the print script exited); the file is read once more after `stop` so the
+    /// final events are never lost.
+    pub fn setup_event_monitoring(
+        app: Arc<Mutex<App>>,
+        event_path: PathBuf,
+        mut stop: oneshot::Receiver<()>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut offset = 0;
+            let mut partial = String::new();
+            loop {
+                let stopping = !matches!(stop.try_recv(), Err(oneshot::error::TryRecvError::Empty));
+                let size = tokio::fs::metadata(&event_path)
+                    .await
+                    .map(|m| m.len() as usize)
+                    .unwrap_or(0);
+
+                if size > offset {
+                    if let Some(chunk) = Self::read_file_chunk(&event_path, offset, size).await {
+                        offset = size;
+                        partial.push_str(&chunk);
+                        // Keep a trailing half-written line for the next pass.
+                        let complete = match partial.rfind('\n') {
+                            Some(i) => partial.drain(..=i).collect::<String>(),
+                            None => String::new(),
+                        };
+
+                        let mut app_lock = app.lock().await;
+                        for event in complete.lines().filter_map(SerialEvent::parse) {
+                            if let SerialEvent::Started { file: Some(file), .. } = &event {
+                                Self::spawn_gcode_analysis(Arc::clone(&app), PathBuf::from(file));
+                            }
+                            let terminal = event.is_terminal();
+                            app_lock.handle_serial_event(event);
+                            if terminal {
+                                return;
+                            }
+                        }
+                    }
+                }
+
+                if stopping {
+                    return;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(SERIAL_EVENT_POLL_INTERVAL_MILLIS)).await;
+            }
+        })
+    }
+
+    /// Pre-scan the file eureka-serial just started streaming, for time-based progress, the
+    /// current layer and the remaining time.
+    fn spawn_gcode_analysis(app: Arc<Mutex<App>>, path: PathBuf) {
+        tokio::spawn(async move {
+            let scan_path = path.clone();
+            let result = tokio::task::spawn_blocking(move || GcodeAnalysis::from_file(&scan_path)).await;
+            let mut app_lock = app.lock().await;
+            let line = match result {
+                Ok(Ok(analysis)) => {
+                    let total = analysis.total().as_secs();
+                    let source = if analysis.slicer_estimate.is_some() { "slicer" } else { "estimated" };
+                    let layers = analysis
+                        .layer_count()
+                        .map(|n| format!(", {} layers", n))
+                        .unwrap_or_default();
+                    app_lock.telemetry.analysis = Some(Arc::new(analysis));
+                    format!("[GCODE] Print time {}h{:02}m ({}){}", total / 3600, (total % 3600) / 60, source, layers)
+                }
+                Ok(Err(e)) => format!("[GCODE] Cannot analyze {}: {}", path.display(), e),
+                Err(e) => format!("[GCODE] Analysis failed: {}", e),
+            };
+            app_lock.print_output.push(line);
+        });
+    }
+
+    fn handle_serial_event(&mut self, event: SerialEvent) {
+        let log = match &event {
+            SerialEvent::Started { device, total_lines, .. } => {
+                Some(format!("[SERIAL] Streaming {} lines to {}", total_lines, device))
+            }
+            SerialEvent::Firmware(firmware) => {
+                let enabled: Vec<&str> = firmware
+                    .capabilities
+                    .iter()
+                    .filter(|(_, on)| **on)
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                self.printer_firmware = Some(firmware.clone());
+                Some(format!("[SERIAL] Firmware: {} (caps: {})", firmware.label(), enabled.join(", ")))
+            }
+            SerialEvent::FirmwareError { message, fatal: true } => {
+                self.set_message(MessageType::Error, format!("Printer halted: {}", message));
+                Some(format!("[FIRMWARE] HALTED: {}", message))
+            }
+            SerialEvent::FirmwareError { message, .. } => Some(format!("[FIRMWARE] {}", message)),
+            SerialEvent::Paused => {
+                self.set_message(MessageType::Info, "Printer paused".to_string());
+                Some("[SERIAL] Printer paused".to_string())
+            }
+            SerialEvent::Resumed => Some("[SERIAL] Printer resumed".to_string()),
+            SerialEvent::Finished { lines } => Some(format!("[SERIAL] Sent {} lines", lines)),
+            SerialEvent::Cancelled { lines } => {
+                Some(format!("[SERIAL] Print cancelled after {} lines; heaters off, XY homed", lines))
+            }
+            SerialEvent::EmergencyStop => {
+                Some("[SERIAL] EMERGENCY STOP: M112 sent, printer halted".to_string())
+            }
+            SerialEvent::Failed { message } => Some(format!("[SERIAL] {}", message)),
+            SerialEvent::Progress { .. } | SerialEvent::Temperature { .. } => None,
+        };
+        self.telemetry.apply(event);
+        if let Some(line) = log {
+            self.print_output.push(line);
+            if self.print_output.len() > PRINT_OUTPUT_MAX_LINES {
+                self.print_output.remove(0);
+            }
+        }
+    }
+
     #[inline]
     pub fn clear_print_log(&mut self) {
         self.print_output.clear();
@@ -1,26 +1,24 @@
 use crate::app::core::App;
 use crate::app::{MessageType, ScriptStatus, PrintStatus};
-use crate::constants::PRINT_OUTPUT_MAX_LINES;
-use crate::utils::crate_root;
+use super::monitoring::EventMonitor;
+use super::pipeline::{ModelFile, PipelineMode};
+use super::telemetry::PrintTelemetry;
+use crate::home::eureka_home;
+use crate::private_file::PrivateFile;
+use crate::workspace::{JobState, JobWorkspace};
 use anyhow::Result;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::io::{BufReader, AsyncBufReadExt};
+use tokio::sync::{Mutex, Notify};
 
 impl App {
-    /// Run slicing test without printer connection
-    pub async fn run_slice_test(app: Arc<Mutex<App>>) -> Result<bool, String> {
+    /// Run slicing test without printer connection: slice `input_stl` into `output_gcode`.
+    pub async fn run_slice_test(app: Arc<Mutex<App>>, input_stl: &Path, output_gcode: &Path) -> Result<bool, String> {
         {
             let mut app_guard = app.lock().await;
             app_guard.print_output.push("[TEST] Starting slicing test...".to_string());
         }
         
-        let root = crate_root();
-        
-        // Use mock_print.stl directly instead of test_decryption/decrypted.stl
-        let input_stl = root.join("mock_print.stl");
-        let output_gcode = root.join("output.gcode");
-        
         // Check if input file exists
         if !input_stl.exists() {
             let error_msg = format!("Input STL not found at {}", input_stl.display());
@@ -29,112 +27,58 @@ impl App {
             return Err(error_msg);
         }
         
-        {
-            let mut app = app.lock().await;
-            app.print_output.push(format!("[TEST] Running PrusaSlicer on: {}", input_stl.display()));
-        }
-        
-        // Get PrusaSlicer config
-        let transmit_dir = root.join("Gcode-Transmit").join("main");
-        let config_file = transmit_dir.join("Ender-3_set.ini");
-        
-        if !config_file.exists() {
-            let error_msg = format!("Config file not found at {}", config_file.display());
-            let mut app = app.lock().await;
-            app.print_output.push(format!("[TEST] ERROR: {}", error_msg));
-            return Err(error_msg);
-        }
-        
-        // Run PrusaSlicer
-        let mut child = match tokio::process::Command::new("prusa-slicer")
-            .arg("--load")
-            .arg(&config_file)
-            .arg("--export-gcode")
-            .arg("--output")
-            .arg(&output_gcode)
-            .arg(&input_stl)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn() {
-                Ok(child) => child,
-                Err(e) => {
-                    let error_msg = format!("Failed to start PrusaSlicer: {}", e);
-                    let mut app = app.lock().await;
-                    app.print_output.push(format!("[TEST] ERROR: {}", error_msg));
-                    return Err(error_msg);
-                }
-            };
-        
-        let stdout = child.stdout.take().unwrap();
-        let stderr = child.stderr.take().unwrap();
-        let app_clone_stdout = Arc::clone(&app);
-        let app_clone_stderr = Arc::clone(&app);
-        
-        let stdout_handle = tokio::spawn(async move {
-            let mut reader = BufReader::new(stdout).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                let mut app = app_clone_stdout.lock().await;
-                app.print_output.push(format!("[TEST] {}", line));
-            }
-        });
-        
-        let stderr_handle = tokio::spawn(async move {
-            let mut reader = BufReader::new(stderr).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                let mut app = app_clone_stderr.lock().await;
-                app.print_output.push(format!("[TEST] {}", line));
+        match Self::run_slicer(Arc::clone(&app), input_stl, output_gcode, "TEST", None).await {
+            Ok(()) => {
+                let mut app = app.lock().await;
+                app.print_output.push(format!("[TEST] G-code saved to: {}", output_gcode.display()));
+                Ok(true)
             }
-        });
-        
-        let status = match child.wait().await {
-            Ok(status) => status,
             Err(e) => {
-                let error_msg = format!("PrusaSlicer execution failed: {}", e);
+                let error_msg = e.to_string();
                 let mut app = app.lock().await;
                 app.print_output.push(format!("[TEST] ERROR: {}", error_msg));
-                return Err(error_msg);
+                Err(error_msg)
             }
-        };
-        
-        let _ = tokio::join!(stdout_handle, stderr_handle);
-        
-        let mut app = app.lock().await;
-        if status.success() {
-            app.print_output.push(format!("[TEST] G-code saved to: {}", output_gcode.display()));
-            Ok(true)
-        } else {
-            let error_msg = format!("PrusaSlicer failed with exit code: {:?}", status.code());
-            app.print_output.push(format!("[TEST] ERROR: {}", error_msg));
-            Err(error_msg)
         }
     }
 
-    pub async fn run_print_script(app: Arc<Mutex<App>>) -> Result<bool, String> {
+    /// Slice and send `model` (downloaded for `job`), natively or through the scripts per
+    /// `config.pipeline`. `Ok(false)` when the print was cancelled.
+    pub async fn run_print_pipeline(app: Arc<Mutex<App>>, mut job: JobWorkspace, model: ModelFile) -> Result<bool, String> {
+        // G-code sliced from a decrypted model stays with it in the private runtime directory, so
+        // the startup sweep wipes it if the app dies mid-print.
+        let private_gcode = if job.encrypted() {
+            match PrivateFile::reserve(&format!("{}.gcode", job.id())) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    let error = format!("No private directory for the G-code: {:#}", e);
+                    let mut app_guard = app.lock().await;
+                    app_guard.print_output.push(format!("[ERROR] {}", error));
+                    app_guard.set_message(MessageType::Error, error.clone());
+                    let _ = job.finish(JobState::Failed, Some(error.clone()), &app_guard.print_output);
+                    return Err(error);
+                }
+            }
+        } else {
+            None
+        };
         {
             let mut app_guard = app.lock().await;
             app_guard.script_status = ScriptStatus::Running;
             app_guard.print_status = PrintStatus::Printing;
+            app_guard.telemetry = PrintTelemetry::default();
             app_guard.clear_print_log();
-            app_guard.set_message(MessageType::Info, "Starting print script...".to_string());
+            app_guard.print_output.push(format!("[JOB] {} in {}", job.id(), job.dir().display()));
+            app_guard.set_message(MessageType::Info, "Starting print...".to_string());
+        }
+        if let Err(e) = job.set_state(JobState::Printing, None) {
+            let mut app_guard = app.lock().await;
+            app_guard.print_output.push(format!("[JOB] Could not update job.json: {}", e));
         }
         
         let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<bool, String>>(1);
         let app_clone = Arc::clone(&app);
         
-        let root = crate_root();
-        
-        let transmit_dir = root.join("Gcode-Transmit");
-        {
-            if !transmit_dir.exists() || !transmit_dir.is_dir() {
-                let error_msg = format!("Gcode-Transmit directory does not exist at {}", transmit_dir.display());
-                let mut app_locked = app_clone.lock().await;
-                app_locked.print_output.push(format!("[ERROR] {}", error_msg));
-                app_locked.script_status = ScriptStatus::Failed(error_msg.clone());
-                app_locked.set_message(MessageType::Error, error_msg.clone());
-                return Err(error_msg);
-            }
-        }
-        
         {
             let app_guard = app.lock().await;
             let printer_ok = !app_guard.printer_id.eq("No Printer ID");
@@ -164,126 +108,83 @@ impl App {
             }
         }
         
-        App::setup_gcode_monitoring(Arc::clone(&app_clone)).await;
-        
-        tokio::spawn(async move {
-            let script_path = crate_root().join("Gcode-Transmit").join("Gcode-Process.sh");
-            let script_path_str = script_path.to_string_lossy();
-            let command = format!("{} --print", script_path_str);
-            
-            let mut child = match tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(&command)
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped())
-                .spawn() {
-                    Ok(child) => child,
-                    Err(e) => {
-                        let error_msg = format!("Failed to start script: {}", e);
-                        let mut app = app_clone.lock().await;
-                        app.script_status = ScriptStatus::Failed(error_msg.clone());
-                        app.set_message(MessageType::Error, error_msg.clone());
-                        let _ = tx.send(Err(error_msg)).await;
-                        return;
-                    }
-                };
+        let gcode = private_gcode
+            .as_ref()
+            .map(|file| file.path().to_path_buf())
+            .unwrap_or_else(|| job.gcode_path());
+        let event_path = job.event_path();
+        // Sliced G-code is cached per blob, except for encrypted models.
+        let cache_key = (!job.encrypted()).then(|| job.blob_id().to_string());
+        App::setup_gcode_monitoring(Arc::clone(&app_clone), gcode.clone()).await;
 
-            let stdout = child.stdout.take().unwrap();
-            let stderr = child.stderr.take().unwrap();
-            let app_clone_stdout = Arc::clone(&app_clone);
-            let app_clone_stderr = Arc::clone(&app_clone);
-
-            let stdout_handle = tokio::spawn(async move {
-                let mut reader = BufReader::new(stdout).lines();
-                        while let Ok(Some(line)) = reader.next_line().await {
-                            let mut app = app_clone_stdout.lock().await;
-                            app.print_output.push(format!("[STDOUT] {}", line));
-                            if app.print_output.len() > PRINT_OUTPUT_MAX_LINES {
-                                app.print_output.remove(0);
-                            }
-                        }
-            });
-
-            let stderr_handle = tokio::spawn(async move {
-                let mut reader = BufReader::new(stderr).lines();
-                        while let Ok(Some(line)) = reader.next_line().await {
-                            let mut app = app_clone_stderr.lock().await;
-                            app.print_output.push(format!("[STDERR] {}", line));
-                            if app.print_output.len() > PRINT_OUTPUT_MAX_LINES {
-                                app.print_output.remove(0);
-                            }
-                        }
-            });
+        let (stop_events, stop_events_rx) = tokio::sync::oneshot::channel();
+        let mut events = EventMonitor::new(
+            stop_events,
+            App::setup_event_monitoring(Arc::clone(&app_clone), event_path.clone(), stop_events_rx),
+        );
+        
+        let abort = Arc::new(Notify::new());
+        let mode = {
+            let mut app_guard = app.lock().await;
+            if app_guard.config.pipeline == PipelineMode::Native {
+                app_guard.print_abort = Some(Arc::clone(&abort));
+            }
+            app_guard.config.pipeline
+        };
 
-            let status = match child.wait().await {
-                Ok(status) => status,
-                Err(e) => {
-                    let error_msg = format!("Script execution failed: {}", e);
-                    let mut app = app_clone.lock().await;
-                    app.script_status = ScriptStatus::Failed(error_msg.clone());
-                    app.set_message(MessageType::Error, error_msg.clone());
-                    let _ = tx.send(Err(error_msg)).await;
-                    return;
+        tokio::spawn(async move {
+            let outcome = match mode {
+                PipelineMode::Native => {
+                    Self::run_native_pipeline(
+                        Arc::clone(&app_clone),
+                        model,
+                        &gcode,
+                        &event_path,
+                        &mut events,
+                        cache_key.as_deref(),
+                        abort,
+                    )
+                    .await
                 }
-            };
-
-            let _ = tokio::join!(stdout_handle, stderr_handle);
-
-            let mut app = app_clone.lock().await;
-            if status.success() {
-                app.script_status = ScriptStatus::Completed;
-                app.print_status = PrintStatus::Completed;
-                app.set_message(MessageType::Success, "Print completed successfully".to_string());
-                
-                let should_update_blockchain = 
-                    !app.printer_id.eq("No Printer ID") && 
-                    app.sculpt_state.selected()
-                        .map(|index| index < app.sculpt_items.len())
-                        .unwrap_or(false);
-                
-                if should_update_blockchain {
-                    let app_clone_for_completion = Arc::clone(&app_clone);
-                    tokio::spawn(async move {
-                        Self::update_blockchain_on_completion(app_clone_for_completion).await;
-                    });
+                PipelineMode::Scripts => {
+                    Self::run_script_pipeline(
+                        Arc::clone(&app_clone),
+                        model,
+                        &gcode,
+                        &event_path,
+                        &mut events,
+                        cache_key.as_deref(),
+                    )
+                    .await
                 }
-                
-                let _ = tx.send(Ok(true)).await;
-            } else {
-                let error_message = match status.code() {
-                    Some(1) => "Printer not connected",
-                    Some(2) => "Slicing process failed",
-                    Some(3) => "Serial communication failed",
-                    Some(code) => {
-                        app.print_output
-                        .push(format!("[ERROR] Unknown error code: {}", code));
-                        "Unknown error"
-                    },
-                    None => "Process terminated with unknown status",
-                };
-                
-                let error_code = status.code().unwrap_or(-1);
-                let full_error = format!("Print failed (code {}): {}", error_code, error_message);
-                
-                app.script_status = ScriptStatus::Failed(full_error.clone());
-                app.set_message(MessageType::Error, full_error.clone());
-                
-                let _ = tx.send(Err(full_error)).await;
+            };
+            events.finish().await;
+            let result = Self::finish_print(&app_clone, outcome).await;
+            let (state, error) = match &result {
+                Ok(true) => (JobState::Completed, None),
+                Ok(false) => (JobState::Cancelled, None),
+                Err(e) => (JobState::Failed, Some(e.clone())),
+            };
+            let log = app_clone.lock().await.print_output.clone();
+            if let Err(e) = job.finish(state, error, &log) {
+                let mut g = app_clone.lock().await;
+                g.print_output.push(format!("[JOB] Could not record job {}: {}", job.id(), e));
             }
+            drop(private_gcode);
+            let _ = tx.send(result).await;
         });
         
         match rx.recv().await {
             Some(result) => result,
-            None => Err("Communication channel with print script was closed unexpectedly".to_string())
+            None => Err("Communication channel with print pipeline was closed unexpectedly".to_string())
         }
         
     }
 
-    #[allow(dead_code)]
     pub async fn run_stop_script(&mut self) -> Result<()> {
         self.set_message(MessageType::Info, "Stopping print...".to_string());
         
-        let script_path = crate_root().join("Gcode-Transmit").join("Gcode-Process.sh");
+        let script_path = eureka_home().join("Gcode-Transmit").join("Gcode-Process.sh");
         
         if !script_path.exists() {
             let error_msg = format!("Script file does not exist: {}", script_path.display());
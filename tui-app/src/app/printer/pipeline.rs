@@ -0,0 +1,520 @@
+use crate::app::core::App;
+use crate::app::{MessageType, PrintStatus, ScriptStatus};
+use super::control::control_socket_path;
+use super::monitoring::EventMonitor;
+use crate::cache::BlobCache;
+use crate::private_file::PrivateFile;
+use crate::constants::PRINT_OUTPUT_MAX_LINES;
+use crate::slicer;
+use crate::home::eureka_home;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+
+/// The model handed to the slicer.
+pub enum ModelFile {
+    /// STL in the job directory.
+    Job(PathBuf),
+    /// Decrypted STL in the private runtime directory; wiped as soon as it has been sliced.
+    Decrypted(PrivateFile),
+}
+
+impl ModelFile {
+    pub fn path(&self) -> &Path {
+        match self {
+            ModelFile::Job(path) => path,
+            ModelFile::Decrypted(file) => file.path(),
+        }
+    }
+}
+
+/// eureka-serial's exit code when the firmware halted (see `bin/eureka_serial/main.rs`).
+const SERIAL_EXIT_FIRMWARE_HALT: i32 = 4;
+
+/// How a print is driven: natively from the app (default), or through `Gcode-Process.sh --print`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineMode {
+    #[default]
+    Native,
+    Scripts,
+}
+
+impl FromStr for PipelineMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "native" => Ok(Self::Native),
+            "scripts" | "script" | "bash" => Ok(Self::Scripts),
+            other => Err(format!("unknown pipeline '{}' (expected native or scripts)", other)),
+        }
+    }
+}
+
+/// Why a print did not complete, by stage.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineError {
+    PrinterNotConnected,
+    Slicing(String),
+    /// eureka-serial (or the print script) could not be started.
+    Launch(String),
+    Serial(String),
+    FirmwareHalted(String),
+    /// Stopped on request before or instead of a clean cancel through eureka-serial.
+    Cancelled,
+}
+
+impl PipelineError {
+    /// Map the legacy script exit codes (1 no printer, 2 slicing, 3 serial, 4 firmware halt).
+    pub fn from_script_exit(code: Option<i32>, detail: Option<&str>) -> Self {
+        let detail = |fallback: String| detail.map(str::to_string).unwrap_or(fallback);
+        match code {
+            Some(1) => PipelineError::PrinterNotConnected,
+            Some(2) => PipelineError::Slicing(detail("slicer exited with an error".to_string())),
+            Some(4) => PipelineError::FirmwareHalted(detail("no message from the firmware".to_string())),
+            Some(0) | Some(3) => PipelineError::Serial(detail("eureka-serial reported a failure".to_string())),
+            Some(code) => PipelineError::Serial(detail(format!("unknown exit code {}", code))),
+            None => PipelineError::Serial(detail("process terminated by a signal".to_string())),
+        }
+    }
+
+    /// Short form for the printer panel.
+    pub fn detail(&self) -> String {
+        match self {
+            PipelineError::PrinterNotConnected => "Printer not connected".to_string(),
+            PipelineError::Cancelled => "Print cancelled".to_string(),
+            PipelineError::Slicing(m)
+            | PipelineError::Launch(m)
+            | PipelineError::Serial(m)
+            | PipelineError::FirmwareHalted(m) => m.clone(),
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::PrinterNotConnected => write!(
+                f,
+                "Printer not connected (set EUREKA_PRINTER_DEVICE or add the /dev/3Dprinter udev symlink)"
+            ),
+            PipelineError::Slicing(m) => write!(f, "Slicing process failed: {}", m),
+            PipelineError::Launch(m) => write!(f, "Cannot start sender: {}", m),
+            PipelineError::Serial(m) => write!(f, "Serial communication failed: {}", m),
+            PipelineError::FirmwareHalted(m) => write!(f, "Printer firmware halted: {}", m),
+            PipelineError::Cancelled => write!(f, "Print cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// Same order as `common-device.sh`: `EUREKA_PRINTER_DEVICE`, the udev symlink, the usual
+/// Linux names, then macOS USB serial devices.
+pub fn resolve_printer_device() -> Option<PathBuf> {
+    if let Ok(p) = std::env::var("EUREKA_PRINTER_DEVICE") {
+        let p = PathBuf::from(p.trim());
+        if p.exists() {
+            return Some(p);
+        }
+    }
+    for d in ["/dev/3Dprinter", "/dev/ttyACM0", "/dev/ttyUSB0"] {
+        if Path::new(d).exists() {
+            return Some(PathBuf::from(d));
+        }
+    }
+    let mut mac: Vec<PathBuf> = std::fs::read_dir("/dev")
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.starts_with("tty.usbmodem") || name.starts_with("tty.usbserial")
+        })
+        .map(|e| e.path())
+        .collect();
+    mac.sort();
+    mac.into_iter().next()
+}
+
+/// Bundled PrusaSlicer-format profile, for backends without `slicer.profile`.
+fn default_slicer_profile() -> PathBuf {
+    eureka_home().join("Gcode-Transmit").join("main").join("Ender-3_set.ini")
+}
+
+/// eureka-serial next to the running binary (both land in `target/<profile>`), then the places
+/// `common-device.sh` looks, then `PATH`.
+fn find_serial_binary() -> Option<PathBuf> {
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("eureka-serial")));
+    let root = eureka_home();
+    let path_dirs = std::env::var_os("PATH")
+        .map(|p| std::env::split_paths(&p).map(|d| d.join("eureka-serial")).collect::<Vec<_>>())
+        .unwrap_or_default();
+    beside_exe
+        .into_iter()
+        .chain([
+            root.join("Gcode-Transmit").join("main").join("eureka-serial"),
+            root.join("target").join("release").join("eureka-serial"),
+            root.join("target").join("debug").join("eureka-serial"),
+        ])
+        .chain(path_dirs)
+        .find(|p| p.is_file())
+}
+
+impl App {
+    /// Resolve the printer, slice `model` into `gcode`, then stream it with eureka-serial.
+    /// `Ok(true)` when the print completed, `Ok(false)` when it was cancelled.
+    pub(super) async fn run_native_pipeline(
+        app: Arc<Mutex<App>>,
+        model: ModelFile,
+        gcode: &Path,
+        event_path: &Path,
+        events: &mut EventMonitor,
+        cache_key: Option<&str>,
+        abort: Arc<Notify>,
+    ) -> Result<bool, PipelineError> {
+        let device = resolve_printer_device().ok_or(PipelineError::PrinterNotConnected)?;
+        let sliced = Self::slice_model(&app, model.path(), gcode, cache_key, Some(abort.as_ref())).await;
+        // Only the G-code is needed from here on; a decrypted model is wiped now.
+        drop(model);
+        sliced?;
+
+        let serial = find_serial_binary().ok_or_else(|| {
+            PipelineError::Launch("eureka-serial not found; build it with `cargo build --release`".to_string())
+        })?;
+        let mut child = tokio::process::Command::new(&serial)
+            .arg(gcode)
+            .env("EUREKA_PRINTER_DEVICE", &device)
+            .env("EUREKA_EVENT_FILE", event_path)
+            .env("EUREKA_CONTROL_SOCKET", control_socket_path())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| PipelineError::Launch(format!("{}: {}", serial.display(), e)))?;
+        let output = Self::forward_child_output(&app, &mut child);
+
+        let finished = tokio::select! {
+            status = child.wait() => Some(status),
+            _ = abort.notified() => None,
+        };
+        let killed = match finished {
+            Some(_) => Ok(()),
+            None => child.kill().await,
+        };
+        let _ = tokio::join!(output.0, output.1);
+        events.finish().await;
+        if let Some(status) = finished {
+            let status = status.map_err(|e| PipelineError::Serial(e.to_string()))?;
+            return Self::serial_outcome(&app, status).await;
+        }
+        if let Err(e) = killed {
+            return Err(PipelineError::Serial(format!("could not stop eureka-serial: {}", e)));
+        }
+        // eureka-serial had no chance to run its cancel sequence; at least switch the heaters off.
+        let stop = tokio::task::spawn_blocking(move || stop_printer(&device)).await;
+        let mut g = app.lock().await;
+        match stop {
+            Ok(Ok(())) => g.print_output.push("[CONTROL] Sender stopped; heaters off, head lifted".to_string()),
+            Ok(Err(e)) => g.print_output.push(format!("[CONTROL] Sender stopped, but the printer did not take the stop sequence: {}", e)),
+            Err(e) => g.print_output.push(format!("[CONTROL] Stop sequence failed: {}", e)),
+        }
+        Err(PipelineError::Cancelled)
+    }
+
+    /// Interpret eureka-serial's exit together with the events it wrote.
+    async fn serial_outcome(app: &Arc<Mutex<App>>, status: ExitStatus) -> Result<bool, PipelineError> {
+        let g = app.lock().await;
+        let detail = g.telemetry.error_detail().map(str::to_string);
+        if status.success() && g.telemetry.cancelled {
+            return Ok(false);
+        }
+        if status.success() && g.telemetry.failure.is_none() {
+            return Ok(true);
+        }
+        Err(match status.code() {
+            Some(SERIAL_EXIT_FIRMWARE_HALT) => {
+                PipelineError::FirmwareHalted(detail.unwrap_or_else(|| "no message from the firmware".to_string()))
+            }
+            Some(code) => PipelineError::Serial(detail.unwrap_or_else(|| format!("eureka-serial exited with code {}", code))),
+            None => PipelineError::Serial(detail.unwrap_or_else(|| "eureka-serial was killed".to_string())),
+        })
+    }
+
+    /// Legacy path: `Gcode-Process.sh --print` after slicing here (`EUREKA_PRESLICED`).
+    pub(super) async fn run_script_pipeline(
+        app: Arc<Mutex<App>>,
+        model: ModelFile,
+        gcode: &Path,
+        event_path: &Path,
+        events: &mut EventMonitor,
+        cache_key: Option<&str>,
+    ) -> Result<bool, PipelineError> {
+        let sliced = Self::slice_model(&app, model.path(), gcode, cache_key, None).await;
+        let model_path = model.path().to_path_buf();
+        // Only the G-code is needed from here on; a decrypted model is wiped now.
+        drop(model);
+        sliced?;
+
+        let script_path = eureka_home().join("Gcode-Transmit").join("Gcode-Process.sh");
+        if !script_path.exists() {
+            return Err(PipelineError::Launch(format!("Script file does not exist: {}", script_path.display())));
+        }
+        let command = format!("{} --print", script_path.to_string_lossy());
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .env("EUREKA_MODEL_FILE", model_path)
+            .env("EUREKA_GCODE_FILE", gcode)
+            .env("EUREKA_EVENT_FILE", event_path)
+            .env("EUREKA_CONTROL_SOCKET", control_socket_path())
+            .env("EUREKA_PRESLICED", "1")
+            .envs(find_serial_binary().map(|bin| ("EUREKA_SERIAL_BIN", bin)))
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| PipelineError::Launch(format!("Failed to start script: {}", e)))?;
+        let output = Self::forward_child_output(&app, &mut child);
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| PipelineError::Launch(format!("Script execution failed: {}", e)))?;
+        let _ = tokio::join!(output.0, output.1);
+        events.finish().await;
+
+        let g = app.lock().await;
+        // eureka-serial reports its own failure even when a wrapper script swallows the exit code.
+        if status.success() && g.telemetry.cancelled {
+            Ok(false)
+        } else if status.success() && g.telemetry.failure.is_none() {
+            Ok(true)
+        } else {
+            Err(PipelineError::from_script_exit(status.code(), g.telemetry.error_detail()))
+        }
+    }
+
+    /// `run_slicer`, unless G-code for the blob `cache_key` and the current slicer setup is in the
+    /// local cache. Fresh G-code is cached; encrypted models pass no key.
+    async fn slice_model(
+        app: &Arc<Mutex<App>>,
+        model: &Path,
+        gcode: &Path,
+        cache_key: Option<&str>,
+        abort: Option<&Notify>,
+    ) -> Result<(), PipelineError> {
+        let cache = match cache_key {
+            Some(blob_id) => {
+                let g = app.lock().await;
+                BlobCache::open(&g.config.cache).and_then(|cache| {
+                    let fingerprint = slicer::fingerprint(&g.config.slicer, &default_slicer_profile()).ok()?;
+                    Some((cache, blob_id, fingerprint))
+                })
+            }
+            None => None,
+        };
+        if let Some((cache, blob_id, fingerprint)) = &cache {
+            if let Some(cached) = cache.gcode(blob_id, fingerprint) {
+                if tokio::fs::copy(&cached, gcode).await.is_ok() {
+                    let mut g = app.lock().await;
+                    g.print_output.push(format!("[CACHE] G-code for this model and slicer profile ({}) from the local cache", fingerprint));
+                    return Ok(());
+                }
+            }
+        }
+        Self::run_slicer(Arc::clone(app), model, gcode, "SLICE", abort).await?;
+        if let Some((cache, blob_id, fingerprint)) = cache {
+            if let Err(e) = cache.store_gcode(blob_id, &fingerprint, gcode) {
+                let mut g = app.lock().await;
+                g.print_output.push(format!("[CACHE] G-code not cached: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Slice `model` into `output` with the backend from `config.slicer`, streaming the slicer's
+    /// own output into the print log under `[tag]`. `abort` kills the slicer.
+    pub(super) async fn run_slicer(
+        app: Arc<Mutex<App>>,
+        model: &Path,
+        output: &Path,
+        tag: &'static str,
+        abort: Option<&Notify>,
+    ) -> Result<(), PipelineError> {
+        let slicer = {
+            let app = app.lock().await;
+            slicer::from_config(&app.config.slicer, &default_slicer_profile())
+        }
+        .map_err(|e| PipelineError::Slicing(format!("slicer configuration: {}", e)))?;
+
+        // Scratch space for backends that pick their own output names.
+        let workdir = output.with_extension("slicing");
+        let _ = tokio::fs::remove_dir_all(&workdir).await;
+        tokio::fs::create_dir_all(&workdir)
+            .await
+            .map_err(|e| PipelineError::Slicing(format!("cannot create {}: {}", workdir.display(), e)))?;
+        {
+            let mut app = app.lock().await;
+            app.print_output.push(format!("[{}] Running {} on: {}", tag, slicer.name(), model.display()));
+        }
+
+        let spawned = slicer
+            .command(model, output, &workdir)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&workdir).await;
+                return Err(PipelineError::Slicing(format!("failed to start {}: {}", slicer.name(), e)));
+            }
+        };
+        let (stdout_handle, stderr_handle) = Self::forward_tagged_output(&app, &mut child, tag, tag);
+
+        let status = match abort {
+            Some(abort) => tokio::select! {
+                status = child.wait() => Some(status),
+                _ = abort.notified() => None,
+            },
+            None => Some(child.wait().await),
+        };
+        let result = match status {
+            None => {
+                let _ = child.kill().await;
+                Err(PipelineError::Cancelled)
+            }
+            Some(Ok(status)) if status.success() => slicer
+                .locate_output(output, &workdir)
+                .map(|_| ())
+                .map_err(|e| PipelineError::Slicing(e.to_string())),
+            Some(Ok(status)) => Err(PipelineError::Slicing(format!(
+                "{} failed with exit code: {:?}",
+                slicer.name(),
+                status.code()
+            ))),
+            Some(Err(e)) => Err(PipelineError::Slicing(format!("{} execution failed: {}", slicer.name(), e))),
+        };
+        let _ = tokio::join!(stdout_handle, stderr_handle);
+        let _ = tokio::fs::remove_dir_all(&workdir).await;
+        result
+    }
+
+    /// Pipe a child's stdout / stderr into the print log as `[STDOUT]` / `[STDERR]`.
+    fn forward_child_output(app: &Arc<Mutex<App>>, child: &mut Child) -> (JoinHandle<()>, JoinHandle<()>) {
+        Self::forward_tagged_output(app, child, "STDOUT", "STDERR")
+    }
+
+    fn forward_tagged_output(
+        app: &Arc<Mutex<App>>,
+        child: &mut Child,
+        out_tag: &'static str,
+        err_tag: &'static str,
+    ) -> (JoinHandle<()>, JoinHandle<()>) {
+        (
+            forward_lines(Arc::clone(app), child.stdout.take(), out_tag),
+            forward_lines(Arc::clone(app), child.stderr.take(), err_tag),
+        )
+    }
+
+    /// Record the pipeline's result on `App` (status, message, blockchain completion) and turn it
+    /// into the `Ok(true)` / `Ok(false)` / `Err` the download flow expects.
+    pub(super) async fn finish_print(app: &Arc<Mutex<App>>, outcome: Result<bool, PipelineError>) -> Result<bool, String> {
+        let mut g = app.lock().await;
+        g.print_abort = None;
+        match outcome {
+            Ok(false) | Err(PipelineError::Cancelled) => {
+                g.script_status = ScriptStatus::Idle;
+                g.print_status = PrintStatus::Idle;
+                g.set_message(MessageType::Info, "Print cancelled".to_string());
+                Ok(false)
+            }
+            Ok(true) => {
+                g.script_status = ScriptStatus::Completed;
+                g.print_status = PrintStatus::Completed;
+                g.set_message(MessageType::Success, "Print completed successfully".to_string());
+
+                let should_update_blockchain = !g.printer_id.eq("No Printer ID")
+                    && g.sculpt_state
+                        .selected()
+                        .map(|index| index < g.sculpt_items.len())
+                        .unwrap_or(false);
+                if should_update_blockchain {
+                    let app_clone_for_completion = Arc::clone(app);
+                    tokio::spawn(async move {
+                        Self::update_blockchain_on_completion(app_clone_for_completion).await;
+                    });
+                }
+                Ok(true)
+            }
+            Err(e) => {
+                let full_error = format!("Print failed: {}", e);
+                g.print_output.push(format!("[ERROR] {}", e));
+                g.script_status = ScriptStatus::Failed(full_error.clone());
+                g.print_status = PrintStatus::Error(e.detail());
+                g.set_message(MessageType::Error, full_error.clone());
+                Err(full_error)
+            }
+        }
+    }
+}
+
+fn forward_lines<R>(app: Arc<Mutex<App>>, stream: Option<R>, tag: &'static str) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let Some(stream) = stream else {
+            return;
+        };
+        let mut reader = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            let mut app = app.lock().await;
+            app.print_output.push(format!("[{}] {}", tag, line));
+            if app.print_output.len() > PRINT_OUTPUT_MAX_LINES {
+                app.print_output.remove(0);
+            }
+        }
+    })
+}
+
+/// What `Gcode-Stop.sh` sends after killing the sender: heaters off, lift, move the bed forward.
+fn stop_printer(device: &Path) -> Result<(), String> {
+    use std::io::Write;
+    let mut port = serialport::new(device.to_string_lossy(), 115_200)
+        .timeout(Duration::from_secs(2))
+        .open()
+        .map_err(|e| format!("{}: {}", device.display(), e))?;
+    for cmd in ["G21", "M104 S0", "M140 S0", "G1 Z180 F1500", "G1 Y220 F1500"] {
+        port.write_all(format!("{}\n", cmd).as_bytes())
+            .map_err(|e| format!("{}: {}", cmd, e))?;
+    }
+    port.flush().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_exit_codes() {
+        assert_eq!(PipelineError::from_script_exit(Some(1), None), PipelineError::PrinterNotConnected);
+        assert_eq!(
+            PipelineError::from_script_exit(Some(4), Some("Error:Thermal Runaway")),
+            PipelineError::FirmwareHalted("Error:Thermal Runaway".to_string())
+        );
+        assert!(matches!(PipelineError::from_script_exit(Some(2), None), PipelineError::Slicing(_)));
+        assert!(matches!(PipelineError::from_script_exit(Some(0), None), PipelineError::Serial(_)));
+        assert_eq!("Scripts".parse(), Ok(PipelineMode::Scripts));
+    }
+}
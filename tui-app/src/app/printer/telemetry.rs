@@ -0,0 +1,295 @@
+use super::analysis::GcodeAnalysis;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// One line of the `EUREKA_EVENT_FILE` stream written by `eureka-serial` (see `bin/eureka_serial/events.rs`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SerialEvent {
+    Started {
+        device: String,
+        /// Absolute path of the G-code being streamed (older eureka-serial builds omit it).
+        #[serde(default)]
+        file: Option<String>,
+        total_lines: u64,
+    },
+    Firmware(PrinterFirmware),
+    Progress { line: u64, total: u64 },
+    Temperature {
+        hotend: f32,
+        hotend_target: f32,
+        bed: Option<f32>,
+        bed_target: Option<f32>,
+    },
+    FirmwareError {
+        message: String,
+        #[serde(default)]
+        fatal: bool,
+    },
+    Paused,
+    Resumed,
+    Finished { lines: u64 },
+    Cancelled { lines: u64 },
+    EmergencyStop,
+    Failed { message: String },
+}
+
+impl SerialEvent {
+    /// `None` for blank lines, half-written lines and unknown event kinds.
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line.trim()).ok()
+    }
+
+    /// After `finished` / `cancelled` / `failed` nothing else is written for this run.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            SerialEvent::Finished { .. } | SerialEvent::Cancelled { .. } | SerialEvent::Failed { .. }
+        )
+    }
+}
+
+/// Firmware identity and `M115` capabilities, as detected by eureka-serial at connect time.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PrinterFirmware {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub machine: Option<String>,
+    #[serde(default)]
+    pub capabilities: BTreeMap<String, bool>,
+    /// Whether the end of the print is awaited with `M400`.
+    #[serde(default)]
+    pub completion_wait: bool,
+}
+
+impl PrinterFirmware {
+    /// `Marlin 2.1.2.1`, `Klipper`, or `unknown firmware`.
+    pub fn label(&self) -> String {
+        match (&self.name, &self.version) {
+            (Some(name), Some(version)) => format!("{} {}", name, version),
+            (Some(name), None) => name.clone(),
+            _ => "unknown firmware".to_string(),
+        }
+    }
+
+    pub fn capability(&self, name: &str) -> Option<bool> {
+        self.capabilities.get(name).copied()
+    }
+}
+
+/// Latest hotend / bed readings reported by `eureka-serial`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrinterTemperatures {
+    pub hotend: f32,
+    pub hotend_target: f32,
+    pub bed: Option<f32>,
+    pub bed_target: Option<f32>,
+}
+
+impl PrinterTemperatures {
+    pub fn format_hotend(&self) -> String {
+        format!("{:.0}/{:.0}°C", self.hotend, self.hotend_target)
+    }
+
+    pub fn format_bed(&self) -> String {
+        match (self.bed, self.bed_target) {
+            (Some(cur), Some(target)) => format!("{:.0}/{:.0}°C", cur, target),
+            _ => "--".to_string(),
+        }
+    }
+}
+
+/// Typed state of the current serial job, rebuilt from `SerialEvent`s for every print.
+#[derive(Debug, Clone, Default)]
+pub struct PrintTelemetry {
+    pub device: Option<String>,
+    pub lines_sent: u64,
+    pub total_lines: u64,
+    pub temperatures: Option<PrinterTemperatures>,
+    /// Last error reported by the firmware, verbatim.
+    pub firmware_error: Option<String>,
+    /// First message the firmware halted with (thermal runaway, `kill() called`, …), verbatim.
+    pub firmware_halt: Option<String>,
+    /// Message from `failed`, i.e. why `eureka-serial` gave up.
+    pub failure: Option<String>,
+    pub paused: bool,
+    pub finished: bool,
+    /// Stopped on request through the control socket (not a failure).
+    pub cancelled: bool,
+    /// `M112` was sent; the board stays halted until it is reset.
+    pub emergency_stopped: bool,
+    pub started_at: Option<Instant>,
+    /// Pre-scan of the file being printed; attached shortly after `started`.
+    pub analysis: Option<Arc<GcodeAnalysis>>,
+}
+
+impl PrintTelemetry {
+    pub fn apply(&mut self, event: SerialEvent) {
+        match event {
+            SerialEvent::Started { device, total_lines, .. } => {
+                *self = PrintTelemetry {
+                    device: Some(device),
+                    total_lines,
+                    temperatures: self.temperatures,
+                    started_at: Some(Instant::now()),
+                    ..Default::default()
+                };
+            }
+            // Kept on `App` rather than per job; see `App::handle_serial_event`.
+            SerialEvent::Firmware(_) => {}
+            SerialEvent::Progress { line, total } => {
+                self.lines_sent = line;
+                self.total_lines = total;
+            }
+            SerialEvent::Temperature { hotend, hotend_target, bed, bed_target } => {
+                self.temperatures = Some(PrinterTemperatures { hotend, hotend_target, bed, bed_target });
+            }
+            SerialEvent::FirmwareError { message, fatal } => {
+                // Marlin follows the cause (e.g. thermal runaway) with a generic `Printer halted`.
+                if fatal && self.firmware_halt.is_none() {
+                    self.firmware_halt = Some(message.clone());
+                }
+                self.firmware_error = Some(message);
+            }
+            SerialEvent::Paused => self.paused = true,
+            SerialEvent::Resumed => self.paused = false,
+            SerialEvent::Finished { lines } => {
+                self.lines_sent = lines;
+                self.paused = false;
+                self.finished = true;
+            }
+            SerialEvent::Cancelled { lines } => {
+                self.lines_sent = lines;
+                self.paused = false;
+                self.cancelled = true;
+            }
+            SerialEvent::EmergencyStop => {
+                self.paused = false;
+                self.emergency_stopped = true;
+            }
+            SerialEvent::Failed { message } => {
+                self.paused = false;
+                self.failure = Some(message);
+            }
+        }
+    }
+
+    /// Share of the estimated print time behind the sent position, or of lines sent when the
+    /// file could not be analyzed. `None` before `started`.
+    pub fn progress(&self) -> Option<f64> {
+        if self.total_lines == 0 {
+            return None;
+        }
+        if let Some(fraction) = self.analysis().and_then(|a| a.fraction_at(self.lines_sent)) {
+            return Some(fraction);
+        }
+        Some((self.lines_sent as f64 / self.total_lines as f64).min(1.0))
+    }
+
+    /// Remaining time from the analysis; otherwise a linear extrapolation from the send rate.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.finished || self.cancelled {
+            return None;
+        }
+        if let Some(remaining) = self.analysis().and_then(|a| a.remaining_at(self.lines_sent)) {
+            return Some(remaining);
+        }
+        let progress = self.progress()?;
+        if progress <= 0.0 {
+            return None;
+        }
+        let elapsed = self.started_at?.elapsed().as_secs_f64();
+        Some(Duration::from_secs_f64(elapsed / progress - elapsed))
+    }
+
+    /// Current layer and, when known, the layer count.
+    pub fn layer(&self) -> Option<(u32, Option<u32>)> {
+        let analysis = self.analysis()?;
+        Some((analysis.layer_at(self.lines_sent)?, analysis.layer_count()))
+    }
+
+    /// The analysis, if it describes the file eureka-serial is actually streaming.
+    fn analysis(&self) -> Option<&GcodeAnalysis> {
+        self.analysis
+            .as_deref()
+            .filter(|a| a.commands() == self.total_lines)
+    }
+
+    /// Best description of why the job stopped, preferring the firmware's own words
+    /// (except after an emergency stop, where the firmware's `kill()` message says nothing new).
+    pub fn error_detail(&self) -> Option<&str> {
+        if self.emergency_stopped {
+            return self.failure.as_deref().or(self.firmware_error.as_deref());
+        }
+        self.firmware_halt
+            .as_deref()
+            .or(self.firmware_error.as_deref())
+            .or(self.failure.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_serial_events() {
+        assert_eq!(
+            SerialEvent::parse(r#"{"event":"progress","line":12,"total":400}"#),
+            Some(SerialEvent::Progress { line: 12, total: 400 })
+        );
+        assert_eq!(
+            SerialEvent::parse(r#"{"event":"temperature","hotend":210.5,"hotend_target":210.0,"bed":null,"bed_target":null}"#),
+            Some(SerialEvent::Temperature { hotend: 210.5, hotend_target: 210.0, bed: None, bed_target: None })
+        );
+        assert_eq!(SerialEvent::parse(r#"{"event":"paused"}"#), Some(SerialEvent::Paused));
+        let Some(SerialEvent::Firmware(fw)) = SerialEvent::parse(
+            r#"{"event":"firmware","name":"Marlin","version":"2.1.2.1","machine":null,"capabilities":{"EMERGENCY_PARSER":false},"completion_wait":true}"#,
+        ) else {
+            panic!("firmware event not parsed");
+        };
+        assert_eq!(fw.label(), "Marlin 2.1.2.1");
+        assert_eq!(fw.capability("EMERGENCY_PARSER"), Some(false));
+        assert_eq!(SerialEvent::parse(r#"{"event":"progress","li"#), None);
+        assert_eq!(SerialEvent::parse(r#"{"event":"unknown"}"#), None);
+    }
+
+    #[test]
+    fn test_telemetry_tracks_progress_and_errors() {
+        let mut t = PrintTelemetry::default();
+        t.apply(SerialEvent::Started { device: "/dev/ttyACM0".to_string(), file: None, total_lines: 200 });
+        t.apply(SerialEvent::Progress { line: 50, total: 200 });
+        assert_eq!(t.progress(), Some(0.25));
+
+        t.apply(SerialEvent::FirmwareError { message: "Error:Probing Failed".to_string(), fatal: false });
+        t.apply(SerialEvent::Failed { message: "No response from printer".to_string() });
+        assert_eq!(t.error_detail(), Some("Error:Probing Failed"));
+
+        t.apply(SerialEvent::FirmwareError { message: "Error:Thermal Runaway, system stopped! Heater_ID: 0".to_string(), fatal: true });
+        t.apply(SerialEvent::FirmwareError { message: "Error:Printer halted. kill() called!".to_string(), fatal: true });
+        assert_eq!(t.error_detail(), Some("Error:Thermal Runaway, system stopped! Heater_ID: 0"));
+    }
+
+    #[test]
+    fn test_progress_uses_matching_analysis() {
+        let gcode = ";LAYER_CHANGE\nG1 X10 F600\n;LAYER_CHANGE\nG1 X100 F600\n;TIME:60\n";
+        let analysis = Arc::new(GcodeAnalysis::from_reader(gcode.as_bytes()).unwrap());
+        let mut t = PrintTelemetry::default();
+        t.apply(SerialEvent::Started { device: "/dev/pts/3".to_string(), file: None, total_lines: 2 });
+        t.analysis = Some(Arc::clone(&analysis));
+        t.apply(SerialEvent::Progress { line: 1, total: 2 });
+        // The first move is a ninth of the distance, so well under half the time.
+        assert!(t.progress().unwrap() < 0.2);
+        assert_eq!(t.layer(), Some((1, Some(2))));
+        assert!(t.eta().unwrap() > Duration::from_secs(45));
+
+        // A different file (line counts disagree) falls back to lines sent.
+        t.apply(SerialEvent::Started { device: "/dev/pts/3".to_string(), file: None, total_lines: 4 });
+        t.analysis = Some(analysis);
+        t.apply(SerialEvent::Progress { line: 1, total: 4 });
+        assert_eq!(t.progress(), Some(0.25));
+        assert_eq!(t.layer(), None);
+    }
+}
@@ -1,13 +1,16 @@
 use crate::app::core::App;
 use crate::app::{MessageType, RegistrationStatus};
+use crate::eureka_error::describe;
 use anyhow::Result;
 use std::sync::Arc;
 use sui_sdk_types::Address;
 use tokio::sync::Mutex;
 
 impl App {
+    /// Open the prompt; the caller spawns `run_estimate_gas` for its fee line.
     pub fn start_toggle_confirm(&mut self) {
         self.is_confirming = true;
+        self.gas_estimate = None;
     }
 
     pub fn confirm_toggle_immediate(&mut self) {
@@ -36,7 +39,8 @@ impl App {
                         self.wallet.address,
                         self.network_state.clone(),
                     )
-                    .with_printer_eureka_package(&info.eureka_package_id);
+                    .with_printer_eureka_package(&info.eureka_package_id)
+                    .with_gas(self.config.gas.clone());
                     match self.wallet.get_printer_cap_id(address).await {
                         Ok(cap_id) => {
                             
@@ -63,7 +67,7 @@ impl App {
                                     }
                                 }
                                 Err(e) => {
-                                    self.set_message(MessageType::Error, format!("Failed to update printer status: {}", e));
+                                    self.set_message(MessageType::Error, format!("Failed to update printer status: {}", describe(&e)));
                                 }
                             }
                         }
@@ -156,7 +160,7 @@ pub fn start_printer_registration_background(
         // Spawn background task to avoid blocking UI event loop
         tokio::spawn(async move {
             // Clone necessary data
-            let (sui_rpc, tx_signer, network_state, printer_alias, registry_id, address) = {
+            let (sui_rpc, tx_signer, network_state, printer_alias, registry_id, address, gas) = {
                 let app_guard = app.lock().await;
                 if app_guard.registration_status != RegistrationStatus::Submitting {
                     return; // Not in submitting state, skip
@@ -168,6 +172,7 @@ pub fn start_printer_registration_background(
                     app_guard.printer_alias.clone(),
                     app_guard.network_state.get_current_package_ids().eureka_printer_registry_id,
                     app_guard.wallet.address,
+                    app_guard.config.gas.clone(),
                 )
             };
             
@@ -195,7 +200,8 @@ pub fn start_printer_registration_background(
                 tx_signer,
                 address,
                 network_state.clone(),
-            );
+            )
+            .with_gas(gas);
 
             match builder.register_printer(registry, &printer_alias).await {
                 Ok(tx_digest) => {
@@ -78,20 +78,53 @@ impl App {
         }
     }
 
+    /// A Y/N prompt (or the network picker) is waiting for its answer. Only the e-stop prompt may
+    /// open over another one.
+    pub fn prompt_open(&self) -> bool {
+        self.is_confirming_estop
+            || self.is_cancelling_print
+            || self.is_confirming
+            || self.is_harvesting
+            || self.is_confirming_merge
+            || self.is_switching_network
+    }
+
+    /// Open the prompt; the caller spawns `run_estimate_gas` for its fee line.
     pub fn start_harvest_confirm(&mut self) {
         self.is_harvesting = true;
+        self.gas_estimate = None;
     }
 
+    /// Close the prompt; the caller spawns `run_withdraw_fees`.
     pub fn confirm_harvest(&mut self) {
         self.is_harvesting = false;
-        self.success_message = Some("Harvest completed successfully!".to_string());
-        self.harvestable_rewards = "0.00 SUI".to_string();
+        self.is_withdrawing_fees = true;
     }
 
     pub fn cancel_harvest(&mut self) {
         self.is_harvesting = false;
     }
 
+    /// Open the prompt; the caller spawns `run_estimate_gas` for its fee line.
+    pub fn start_merge_confirm(&mut self) {
+        self.is_confirming_merge = true;
+        self.gas_estimate = None;
+    }
+
+    /// Close the prompt; the caller spawns `run_merge_dust_coins`.
+    pub fn confirm_merge(&mut self) {
+        self.is_confirming_merge = false;
+        self.is_merging_coins = true;
+        self.set_message(
+            crate::app::MessageType::Info,
+            "Merging dust coins, waiting for blockchain confirmation...".to_string(),
+        );
+    }
+
+    pub fn cancel_merge(&mut self) {
+        self.is_confirming_merge = false;
+    }
+
     pub fn get_tech_animation(&self) -> String {
         let time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -131,6 +164,7 @@ impl App {
                     crate::app::ScriptStatus::Failed(_) => "║▒▓░ SCRIPT ERROR ░▓▒║".to_string(),
                 }
             }
+            crate::app::PrintStatus::Printing if self.telemetry.paused => "║▒▓░ PRINT PAUSED ░▓▒║".to_string(),
             crate::app::PrintStatus::Printing => {
                 match frame {
                     0 => "║▓▒░ PRINTING ░▒▓║".to_string(),
@@ -0,0 +1,161 @@
+//! Virtual Marlin printer on a pseudo-terminal, for running the serial path without hardware.
+//!
+//! Opens a PTY, symlinks its slave to argv[1] (default `/tmp/eureka-printer`) and answers like
+//! Marlin: `N`/checksum framing with `Resend:`, `M105`/`M155` temperature reports from simulated
+//! heating curves, blocking `M109`/`M190`, `M114`, `M115` capabilities and `M112`. Opening the port
+//! resets the virtual board, as DTR does on a real one.
+//!
+//! ```text
+//! cargo run --bin eureka-printer-sim
+//! EUREKA_PRINTER_DEVICE=/tmp/eureka-printer cargo run   # or Gcode-Send.sh / eureka-serial
+//! ```
+//!
+//! Fault injection:
+//! - `EUREKA_SIM_RESEND_EVERY=N` — reject every N-th numbered line with a checksum error
+//! - `EUREKA_SIM_BUSY_EVERY=N` — `echo:busy: processing` before every N-th `ok`
+//! - `EUREKA_SIM_THERMAL_RUNAWAY_AFTER=N` — halt with a thermal runaway after N commands
+//! - `EUREKA_SIM_NO_AUTOREPORT=1` — no `AUTOREPORT_TEMP` (host falls back to `M105` polling)
+//! - `EUREKA_SIM_SPEED=F` — run heaters F times faster than real time (default 1)
+//!
+//! Set `EUREKA_SIM_QUIET=1` to stop echoing the traffic.
+
+mod marlin;
+mod pty;
+
+use std::env;
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+
+use marlin::{Marlin, SimConfig};
+use pty::{Pty, Readiness};
+
+const DEFAULT_LINK: &str = "/tmp/eureka-printer";
+const TICK: Duration = Duration::from_millis(100);
+
+fn main() -> anyhow::Result<()> {
+    let link = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_LINK));
+    let config = config_from_env();
+    let speed: f32 = env_parse("EUREKA_SIM_SPEED", 1.0);
+    let quiet = matches!(
+        env::var("EUREKA_SIM_QUIET").as_deref(),
+        Ok("1") | Ok("true")
+    );
+
+    let mut pty = Pty::open().context("Failed to open a pseudo-terminal")?;
+    let _ = std::fs::remove_file(&link);
+    std::os::unix::fs::symlink(&pty.slave_path, &link)
+        .with_context(|| format!("Failed to link {}", link.display()))?;
+    println!(
+        "Virtual printer on {} (linked from {})",
+        pty.slave_path.display(),
+        link.display()
+    );
+    println!("Run with EUREKA_PRINTER_DEVICE={}", link.display());
+
+    let mut marlin = Marlin::new(config.clone());
+    let mut connected = false;
+    let mut halt_reported = false;
+    let mut pending = Vec::new();
+    let mut last_tick = Instant::now();
+    let mut buf = [0u8; 1024];
+
+    loop {
+        let readiness = pty.wait(TICK)?;
+        if matches!(readiness, Readiness::Disconnected) {
+            if connected {
+                println!("-- host disconnected");
+                connected = false;
+                pending.clear();
+            }
+            // POLLHUP stays set until a client opens the slave; don't spin on it.
+            std::thread::sleep(TICK);
+            continue;
+        }
+        if !connected {
+            println!("-- host connected, resetting board");
+            connected = true;
+            marlin = Marlin::new(config.clone());
+            halt_reported = false;
+            last_tick = Instant::now();
+            let banner = marlin.boot_banner();
+            send(&mut pty, &banner, quiet)?;
+        }
+
+        if matches!(readiness, Readiness::Readable) {
+            match pty.master.read(&mut buf) {
+                Ok(n) => pending.extend_from_slice(&buf[..n]),
+                // EIO: the client closed the slave between poll and read.
+                Err(e) if e.raw_os_error() == Some(libc::EIO) => continue,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e).context("PTY read failed"),
+            }
+            while let Some(pos) = pending.iter().position(|b| *b == b'\n' || *b == b'\r') {
+                let line: Vec<u8> = pending.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line[..line.len() - 1]).into_owned();
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if !quiet {
+                    println!("> {}", line);
+                }
+                let replies = marlin.receive(&line);
+                send(&mut pty, &replies, quiet)?;
+            }
+        }
+
+        let dt = last_tick.elapsed().as_secs_f32();
+        if dt >= TICK.as_secs_f32() {
+            last_tick = Instant::now();
+            let reports = marlin.tick(dt * speed);
+            send(&mut pty, &reports, quiet)?;
+        }
+        if marlin.halted() && !halt_reported {
+            // Like a killed board, it only answers again after a reset (reopening the port).
+            println!("-- board halted; reconnect to reset");
+            halt_reported = true;
+        }
+    }
+}
+
+fn send(pty: &mut Pty, lines: &[String], quiet: bool) -> anyhow::Result<()> {
+    for line in lines {
+        if !quiet {
+            println!("< {}", line);
+        }
+        match pty.master.write_all(format!("{}\n", line).as_bytes()) {
+            Ok(()) => {}
+            // The host went away mid-reply; the next poll notices.
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => return Ok(()),
+            Err(e) => return Err(e).context("PTY write failed"),
+        }
+    }
+    Ok(())
+}
+
+fn config_from_env() -> SimConfig {
+    let every = |name: &str| Some(env_parse::<u64>(name, 0)).filter(|n| *n > 0);
+    SimConfig {
+        resend_every: every("EUREKA_SIM_RESEND_EVERY"),
+        busy_every: every("EUREKA_SIM_BUSY_EVERY"),
+        thermal_runaway_after: env::var("EUREKA_SIM_THERMAL_RUNAWAY_AFTER")
+            .ok()
+            .and_then(|s| s.trim().parse().ok()),
+        no_autoreport: matches!(
+            env::var("EUREKA_SIM_NO_AUTOREPORT").as_deref(),
+            Ok("1") | Ok("true")
+        ),
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(default)
+}
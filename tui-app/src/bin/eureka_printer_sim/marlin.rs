@@ -0,0 +1,400 @@
+//! Just enough Marlin for `eureka-serial`: `N`/checksum framing with resend requests, heaters that
+//! follow a heating curve, `M105`/`M155` reports, `M109`/`M190` waits, `M114`, `M115` and `M112`.
+//! Time only advances through [`Marlin::tick`], so behavior is deterministic for a given input.
+
+use std::collections::VecDeque;
+
+const AMBIENT: f32 = 22.0;
+/// Marlin's `TEMP_WINDOW` / `TEMP_BED_WINDOW`: `M109`/`M190` return once within this of the target.
+const TEMP_WINDOW: f32 = 1.0;
+/// How often `M109`/`M190` print the temperature while waiting, like Marlin does.
+const WAIT_REPORT_SECS: f32 = 1.0;
+
+/// Faults and firmware variations to simulate.
+#[derive(Debug, Clone, Default)]
+pub struct SimConfig {
+    /// Reject every n-th numbered line with a checksum error (forces a resend).
+    pub resend_every: Option<u64>,
+    /// Print `echo:busy: processing` before the `ok` of every n-th command.
+    pub busy_every: Option<u64>,
+    /// Halt with a thermal runaway after this many accepted commands.
+    pub thermal_runaway_after: Option<u64>,
+    /// Behave like a build without `AUTOREPORT_TEMP` (`M155` unknown, host must poll).
+    pub no_autoreport: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Heater {
+    current: f32,
+    target: f32,
+    /// Maximum heating rate, °C/s.
+    rate: f32,
+    /// Cooling time constant, s.
+    tau: f32,
+}
+
+impl Heater {
+    fn new(rate: f32, tau: f32) -> Self {
+        Self {
+            current: AMBIENT,
+            target: 0.0,
+            rate,
+            tau,
+        }
+    }
+
+    /// Full power up to the target (slowing in the last few degrees), Newtonian cooling down to it.
+    fn step(&mut self, dt: f32) {
+        if self.target > self.current {
+            let gap = self.target - self.current;
+            let rate = if gap < 5.0 { self.rate * (gap / 5.0).max(0.1) } else { self.rate };
+            self.current = (self.current + rate * dt).min(self.target);
+        } else {
+            let floor = self.target.max(AMBIENT);
+            if self.current > floor {
+                self.current = floor + (self.current - floor) * (-dt / self.tau).exp();
+            }
+        }
+    }
+
+    fn reached(&self) -> bool {
+        (self.current - self.target).abs() <= TEMP_WINDOW
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Wait {
+    Hotend,
+    Bed,
+}
+
+pub struct Marlin {
+    config: SimConfig,
+    last_line: u32,
+    numbered_seen: u64,
+    accepted: u64,
+    hotend: Heater,
+    bed: Heater,
+    autoreport_secs: Option<f32>,
+    since_report: f32,
+    /// `M109`/`M190` in progress; later commands wait in `queue` like Marlin's command buffer.
+    waiting: Option<Wait>,
+    queue: VecDeque<String>,
+    position: [f32; 4],
+    absolute: bool,
+    relative_e: bool,
+    halted: bool,
+}
+
+impl Marlin {
+    pub fn new(config: SimConfig) -> Self {
+        Self {
+            config,
+            last_line: 0,
+            numbered_seen: 0,
+            accepted: 0,
+            hotend: Heater::new(3.0, 40.0),
+            bed: Heater::new(0.8, 120.0),
+            autoreport_secs: None,
+            since_report: 0.0,
+            waiting: None,
+            queue: VecDeque::new(),
+            position: [0.0; 4],
+            absolute: true,
+            relative_e: false,
+            halted: false,
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Lines printed at power-up.
+    pub fn boot_banner(&self) -> Vec<String> {
+        vec![
+            "start".to_string(),
+            "echo:Marlin 2.1.2.1 (eureka-printer-sim)".to_string(),
+        ]
+    }
+
+    /// Handle one received line; returns the lines to send back.
+    pub fn receive(&mut self, raw: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        if self.halted {
+            return out;
+        }
+        let Some(cmd) = self.unframe(raw.trim(), &mut out) else {
+            return out;
+        };
+        // The emergency parser acts on M112 even while the buffer is blocked.
+        if cmd.split_whitespace().next() == Some("M112") {
+            self.halt("Printer halted. kill() called!", &mut out);
+            return out;
+        }
+        if self.waiting.is_some() {
+            self.queue.push_back(cmd);
+        } else {
+            self.execute(&cmd, &mut out);
+        }
+        out
+    }
+
+    /// Advance simulated time by `dt` seconds; returns reports and deferred `ok`s.
+    pub fn tick(&mut self, dt: f32) -> Vec<String> {
+        let mut out = Vec::new();
+        if self.halted {
+            return out;
+        }
+        self.hotend.step(dt);
+        self.bed.step(dt);
+        self.since_report += dt;
+
+        if let Some(wait) = self.waiting {
+            if self.since_report >= WAIT_REPORT_SECS {
+                self.since_report = 0.0;
+                out.push(format!("{} W:?", self.temperature_report()));
+            }
+            let done = match wait {
+                Wait::Hotend => self.hotend.reached(),
+                Wait::Bed => self.bed.reached(),
+            };
+            if done {
+                self.waiting = None;
+                out.push("ok".to_string());
+                while self.waiting.is_none() {
+                    let Some(cmd) = self.queue.pop_front() else {
+                        break;
+                    };
+                    self.execute(&cmd, &mut out);
+                }
+            }
+        } else if let Some(interval) = self.autoreport_secs {
+            if self.since_report >= interval {
+                self.since_report = 0.0;
+                out.push(self.temperature_report());
+            }
+        }
+        out
+    }
+
+    /// Check `N<n> … *<checksum>` framing; `None` when the line was rejected (error already queued).
+    fn unframe(&mut self, line: &str, out: &mut Vec<String>) -> Option<String> {
+        if line.is_empty() {
+            return None;
+        }
+        let Some(rest) = line.strip_prefix('N') else {
+            // Unnumbered commands are accepted as-is, as in Marlin.
+            return Some(line.to_string());
+        };
+        let (payload, checksum) = match line.rsplit_once('*') {
+            Some((p, c)) => (p, c.trim().parse::<u8>().ok()),
+            None => (line, None),
+        };
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let number: u32 = digits.parse().ok()?;
+        let cmd = payload[1 + digits.len()..].trim().to_string();
+        let is_m110 = cmd.starts_with("M110");
+
+        if number != self.last_line + 1 && !is_m110 {
+            self.request_resend("Line Number is not Last Line Number+1", out);
+            return None;
+        }
+        let expected = payload.bytes().fold(0u8, |acc, b| acc ^ b);
+        match checksum {
+            None => {
+                self.request_resend("No Checksum with line number", out);
+                return None;
+            }
+            Some(c) if c != expected => {
+                self.request_resend("checksum mismatch", out);
+                return None;
+            }
+            _ => {}
+        }
+        self.numbered_seen += 1;
+        if let Some(n) = self.config.resend_every {
+            if n > 0 && self.numbered_seen.is_multiple_of(n) && !is_m110 {
+                self.request_resend("checksum mismatch", out);
+                return None;
+            }
+        }
+        self.last_line = number;
+        Some(cmd)
+    }
+
+    fn request_resend(&self, reason: &str, out: &mut Vec<String>) {
+        out.push(format!("Error:{}, Last Line: {}", reason, self.last_line));
+        out.push(format!("Resend: {}", self.last_line + 1));
+        out.push("ok".to_string());
+    }
+
+    fn execute(&mut self, cmd: &str, out: &mut Vec<String>) {
+        let mut words = cmd.split_whitespace();
+        let Some(code) = words.next() else {
+            out.push("ok".to_string());
+            return;
+        };
+        let args: Vec<&str> = words.collect();
+        let arg = |letter: char| -> Option<f32> {
+            args.iter()
+                .find_map(|a| a.strip_prefix(letter))
+                .and_then(|v| v.parse().ok())
+        };
+
+        self.accepted += 1;
+        if let Some(limit) = self.config.thermal_runaway_after {
+            if self.accepted > limit {
+                out.push("Error:Thermal Runaway, system stopped! Heater_ID: 0".to_string());
+                self.halt("Printer halted. kill() called!", out);
+                return;
+            }
+        }
+        if let Some(n) = self.config.busy_every {
+            if n > 0 && self.accepted.is_multiple_of(n) {
+                out.push("echo:busy: processing".to_string());
+            }
+        }
+
+        match code {
+            "M110" => {
+                if let Some(n) = arg('N') {
+                    self.last_line = n as u32;
+                }
+            }
+            "M105" => {
+                out.push(format!("ok {}", self.temperature_report()));
+                return;
+            }
+            "M155" => {
+                if self.config.no_autoreport {
+                    out.push("echo:Unknown command: \"M155\"".to_string());
+                } else {
+                    let secs = arg('S').unwrap_or(0.0);
+                    self.autoreport_secs = (secs > 0.0).then_some(secs);
+                    self.since_report = 0.0;
+                }
+            }
+            "M104" => self.hotend.target = arg('S').unwrap_or(0.0),
+            "M140" => self.bed.target = arg('S').unwrap_or(0.0),
+            "M109" | "M190" => {
+                let heater = if code == "M109" { &mut self.hotend } else { &mut self.bed };
+                heater.target = arg('S').or(arg('R')).unwrap_or(heater.target);
+                if !heater.reached() {
+                    self.waiting = Some(if code == "M109" { Wait::Hotend } else { Wait::Bed });
+                    self.since_report = 0.0;
+                    return;
+                }
+            }
+            "M114" => out.push(format!(
+                "X:{:.2} Y:{:.2} Z:{:.2} E:{:.2} Count X:0 Y:0 Z:0",
+                self.position[0], self.position[1], self.position[2], self.position[3]
+            )),
+            "M115" => out.extend(self.firmware_report()),
+            "G90" => self.absolute = true,
+            "G91" => self.absolute = false,
+            "M82" => self.relative_e = false,
+            "M83" => self.relative_e = true,
+            "G28" => {
+                let all = !['X', 'Y', 'Z'].iter().any(|c| args.iter().any(|a| a.starts_with(*c)));
+                for (i, axis) in ['X', 'Y', 'Z'].iter().enumerate() {
+                    if all || args.iter().any(|a| a.starts_with(*axis)) {
+                        self.position[i] = 0.0;
+                    }
+                }
+            }
+            "G92" => {
+                for (i, axis) in ['X', 'Y', 'Z', 'E'].iter().enumerate() {
+                    if let Some(v) = arg(*axis) {
+                        self.position[i] = v;
+                    }
+                }
+            }
+            "G0" | "G1" => {
+                for (i, axis) in ['X', 'Y', 'Z', 'E'].iter().enumerate() {
+                    if let Some(v) = arg(*axis) {
+                        let relative = if i == 3 { self.relative_e || !self.absolute } else { !self.absolute };
+                        self.position[i] = if relative { self.position[i] + v } else { v };
+                    }
+                }
+            }
+            _ => {}
+        }
+        out.push("ok".to_string());
+    }
+
+    fn halt(&mut self, reason: &str, out: &mut Vec<String>) {
+        out.push(format!("Error:{}", reason));
+        self.halted = true;
+        self.hotend.target = 0.0;
+        self.bed.target = 0.0;
+    }
+
+    fn temperature_report(&self) -> String {
+        format!(
+            "T:{:.2} /{:.2} B:{:.2} /{:.2} @:0 B@:0",
+            self.hotend.current, self.hotend.target, self.bed.current, self.bed.target
+        )
+    }
+
+    fn firmware_report(&self) -> Vec<String> {
+        let autoreport = if self.config.no_autoreport { 0 } else { 1 };
+        vec![
+            "FIRMWARE_NAME:Marlin 2.1.2.1 (eureka-printer-sim) SOURCE_CODE_URL:github.com/MarlinFirmware/Marlin PROTOCOL_VERSION:1.0 MACHINE_TYPE:Virtual Ender-3 EXTRUDER_COUNT:1".to_string(),
+            format!("Cap:AUTOREPORT_TEMP:{}", autoreport),
+            "Cap:EMERGENCY_PARSER:1".to_string(),
+            "Cap:HOST_ACTION_COMMANDS:1".to_string(),
+            "Cap:SDCARD:0".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framed(n: u32, cmd: &str) -> String {
+        let payload = format!("N{} {}", n, cmd);
+        let cs = payload.bytes().fold(0u8, |acc, b| acc ^ b);
+        format!("{}*{}", payload, cs)
+    }
+
+    #[test]
+    fn test_framing_and_resend() {
+        let mut m = Marlin::new(SimConfig::default());
+        assert_eq!(m.receive(&framed(0, "M110 N0")), vec!["ok"]);
+        assert_eq!(m.receive(&framed(1, "G1 X10")), vec!["ok"]);
+        // Skipped line 2.
+        let out = m.receive(&framed(3, "G1 X20"));
+        assert_eq!(out[1], "Resend: 2");
+        // Bad checksum.
+        let out = m.receive("N2 G1 X20*0");
+        assert_eq!(out[1], "Resend: 2");
+        assert_eq!(m.receive(&framed(2, "G1 X20")), vec!["ok"]);
+    }
+
+    #[test]
+    fn test_m109_waits_for_heating_curve() {
+        let mut m = Marlin::new(SimConfig::default());
+        assert!(m.receive("M109 S200").is_empty());
+        assert!(m.receive("G1 X1").is_empty(), "queued behind M109");
+        let mut oks = 0;
+        for _ in 0..2000 {
+            oks += m.tick(0.1).iter().filter(|l| *l == "ok").count();
+        }
+        assert_eq!(oks, 2);
+    }
+
+    #[test]
+    fn test_thermal_runaway_halts() {
+        let mut m = Marlin::new(SimConfig {
+            thermal_runaway_after: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(m.receive("G28"), vec!["ok"]);
+        let out = m.receive("G1 X1");
+        assert!(out[0].contains("Thermal Runaway"));
+        assert!(m.halted());
+        assert!(m.receive("G1 X2").is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+//! Pseudo-terminal the simulator serves on. Only the master side is kept open, so the master
+//! reports `POLLHUP` whenever no client has the slave (`/dev/pts/N`) open; that is how a connect
+//! or disconnect is noticed.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct Pty {
+    pub master: File,
+    pub slave_path: PathBuf,
+}
+
+/// What the master saw while waiting for input.
+pub enum Readiness {
+    Readable,
+    Idle,
+    /// No client has the slave open.
+    Disconnected,
+}
+
+impl Pty {
+    pub fn open() -> io::Result<Self> {
+        let mut master = -1;
+        let mut slave = -1;
+        // SAFETY: both out-pointers are valid; name, termios and winsize may be null.
+        let rc = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if rc != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: openpty succeeded, so both descriptors are open and owned by us from here on.
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        make_raw(&slave)?;
+        let slave_path = tty_name(&slave)?;
+        drop(slave);
+        Ok(Self { master, slave_path })
+    }
+
+    pub fn wait(&self, timeout: Duration) -> io::Result<Readiness> {
+        let mut fds = libc::pollfd {
+            fd: self.master.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: one valid pollfd.
+        let rc = unsafe { libc::poll(&mut fds, 1, timeout.as_millis() as libc::c_int) };
+        if rc < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::Interrupted => Ok(Readiness::Idle),
+                _ => Err(err),
+            };
+        }
+        Ok(if fds.revents & libc::POLLHUP != 0 {
+            Readiness::Disconnected
+        } else if fds.revents & libc::POLLIN != 0 {
+            Readiness::Readable
+        } else {
+            Readiness::Idle
+        })
+    }
+}
+
+/// No echo, no CR/LF translation, no line discipline: bytes pass through as on a USB CDC port.
+/// Clients like `eureka-serial` set their own modes on open as well.
+fn make_raw(tty: &File) -> io::Result<()> {
+    // SAFETY: termios is plain data, filled by tcgetattr before use.
+    unsafe {
+        let mut t: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(tty.as_raw_fd(), &mut t) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut t);
+        if libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &t) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn tty_name(tty: &File) -> io::Result<PathBuf> {
+    let mut buf = [0 as libc::c_char; 256];
+    // SAFETY: buf is writable for its full length, which is what we pass.
+    let rc = unsafe { libc::ttyname_r(tty.as_raw_fd(), buf.as_mut_ptr(), buf.len()) };
+    if rc != 0 {
+        return Err(io::Error::from_raw_os_error(rc));
+    }
+    // SAFETY: ttyname_r NUL-terminates on success.
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(PathBuf::from(name.to_string_lossy().into_owned()))
+}
@@ -0,0 +1,202 @@
+//! Control channel for a running job: a Unix socket at `EUREKA_CONTROL_SOCKET` accepting one
+//! command per line (`pause`, `resume`, `cancel`, `estop`). Each line is answered with `ok` or
+//! `error: …`; whether the command could be applied is reported through the event stream. In
+//! delay mode only `estop` is served; the rest are refused so the TUI falls back to its own stop.
+//!
+//! `estop` does not wait for the host loop: the listener thread writes `M112` straight to its own
+//! handle on the port, then flags the host so the job ends as failed. Firmware that reported
+//! `Cap:EMERGENCY_PARSER:0` only reads it after the commands already in its buffer, so the reply is
+//! then `ok: …` with that warning instead of a plain `ok`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+use serialport::SerialPort;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlCommand {
+    Pause,
+    Resume,
+    Cancel,
+    EmergencyStop,
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.trim().to_ascii_lowercase().as_str() {
+            "pause" => Some(Self::Pause),
+            "resume" => Some(Self::Resume),
+            "cancel" | "stop" => Some(Self::Cancel),
+            "estop" | "m112" => Some(Self::EmergencyStop),
+            _ => None,
+        }
+    }
+}
+
+/// Listening socket; the socket file is removed when this is dropped.
+pub struct ControlSocket {
+    path: PathBuf,
+    commands: Receiver<ControlCommand>,
+    emergency_stop: Arc<AtomicBool>,
+    /// Assumed until `M115` says otherwise; see [`ControlSocket::set_emergency_parser`].
+    emergency_parser: Arc<AtomicBool>,
+}
+
+impl ControlSocket {
+    /// `None` when `EUREKA_CONTROL_SOCKET` is unset or the socket cannot be bound (the job still runs).
+    /// `estop_port` is a second handle on the printer port (`try_clone`), used only for `M112`.
+    /// `job_commands`: whether the host loop applies `pause`, `resume` and `cancel`.
+    pub fn from_env(mut estop_port: Option<Box<dyn SerialPort>>, job_commands: bool) -> Option<Self> {
+        let path = PathBuf::from(std::env::var("EUREKA_CONTROL_SOCKET").ok()?.trim());
+        if path.as_os_str().is_empty() {
+            return None;
+        }
+        // A socket left behind by a killed run would make bind fail.
+        let _ = std::fs::remove_file(&path);
+        let listener = match UnixListener::bind(&path) {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("Cannot bind control socket {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let (tx, rx) = mpsc::channel();
+        let emergency_stop = Arc::new(AtomicBool::new(false));
+        let emergency_parser = Arc::new(AtomicBool::new(true));
+        let flag = Arc::clone(&emergency_stop);
+        let parser = Arc::clone(&emergency_parser);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if serve(stream, &tx, &mut estop_port, &flag, &parser, job_commands).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(Self {
+            path,
+            commands: rx,
+            emergency_stop,
+            emergency_parser,
+        })
+    }
+
+    /// Whether the firmware handles `M112` as soon as it arrives (`Cap:EMERGENCY_PARSER`).
+    pub fn set_emergency_parser(&self, present: bool) {
+        self.emergency_parser.store(present, Ordering::SeqCst);
+    }
+
+    pub fn try_recv(&self) -> Option<ControlCommand> {
+        self.commands.try_recv().ok()
+    }
+
+    /// Set once `M112` has been written; the printer is halted and needs a reset.
+    pub fn emergency_stopped(&self) -> bool {
+        self.emergency_stop.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Handle one client until it disconnects; `Err` only when the host side has gone away.
+fn serve(
+    stream: UnixStream,
+    tx: &Sender<ControlCommand>,
+    estop_port: &mut Option<Box<dyn SerialPort>>,
+    emergency_stop: &AtomicBool,
+    emergency_parser: &AtomicBool,
+    job_commands: bool,
+) -> Result<(), mpsc::SendError<ControlCommand>> {
+    let Ok(mut writer) = stream.try_clone() else {
+        return Ok(());
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ControlCommand::parse(&line) {
+            Some(ControlCommand::EmergencyStop) => match send_m112(estop_port) {
+                Ok(()) => {
+                    emergency_stop.store(true, Ordering::SeqCst);
+                    estop_reply(emergency_parser.load(Ordering::SeqCst))
+                }
+                Err(e) => format!("error: M112 not sent: {}", e),
+            },
+            Some(cmd) if job_commands => {
+                tx.send(cmd)?;
+                "ok".to_string()
+            }
+            Some(_) => format!("error: {} not supported in delay mode", line.trim()),
+            None => format!("error: unknown command '{}'", line.trim()),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn estop_reply(emergency_parser: bool) -> String {
+    if emergency_parser {
+        "ok".to_string()
+    } else {
+        "ok: firmware has no EMERGENCY_PARSER; M112 runs after the commands it has already queued"
+            .to_string()
+    }
+}
+
+/// Unnumbered so it is accepted regardless of the line counter; the leading newline terminates any
+/// line the host thread may be halfway through writing.
+fn send_m112(port: &mut Option<Box<dyn SerialPort>>) -> std::io::Result<()> {
+    let port = port
+        .as_mut()
+        .ok_or_else(|| std::io::Error::other("no second handle on the serial port"))?;
+    port.write_all(b"\nM112\n")?;
+    port.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask(client: &mut UnixStream, reader: &mut BufReader<UnixStream>, command: &str) -> String {
+        writeln!(client, "{}", command).unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        reply.trim_end().to_string()
+    }
+
+    #[test]
+    fn test_delay_mode_refuses_job_commands() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            serve(server, &tx, &mut None, &AtomicBool::new(false), &AtomicBool::new(true), false).unwrap();
+        });
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+
+        assert_eq!(ask(&mut client, &mut reader, "pause"), "error: pause not supported in delay mode");
+        assert_eq!(ask(&mut client, &mut reader, "cancel"), "error: cancel not supported in delay mode");
+        assert!(ask(&mut client, &mut reader, "estop").starts_with("error: M112 not sent"));
+        drop(reader);
+        drop(client);
+        handle.join().unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_estop_reply_warns_without_emergency_parser() {
+        assert_eq!(estop_reply(true), "ok");
+        assert!(estop_reply(false).starts_with("ok: firmware has no EMERGENCY_PARSER"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Machine-readable progress for the TUI: one JSON object per line, appended to the file named by
+//! `EUREKA_EVENT_FILE`. Every object carries an `event` tag:
+//!
+//! - `started` — `device`, `file` (absolute path of the G-code being sent), `total_lines`
+//! - `firmware` — `name`, `version`, `machine` (each may be `null`), `capabilities` (`Cap:` flags
+//!   from `M115`), `completion_wait` (whether `M400` is used)
+//! - `progress` — `line`, `total` (throttled; the last one always matches the final count)
+//! - `temperature` — `hotend`, `hotend_target`, `bed`, `bed_target` (bed fields may be `null`)
+//! - `firmware_error` — `message`, verbatim from the firmware; `fatal` when the firmware halted
+//!   (the run then fails with exit code 4)
+//! - `paused` / `resumed` — after a control command, `//action:pause(d)` / `//action:resume(d)`
+//!   host actions, or while the firmware reports `busy: paused for user`
+//! - `finished` — `lines`
+//! - `cancelled` — `lines` sent before a `cancel` control command
+//! - `emergency_stop` — `M112` was sent; a `failed` event follows
+//! - `failed` — `message`
+//!
+//! Without `EUREKA_EVENT_FILE` every call is a no-op.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::protocol::{FirmwareInfo, Temperatures};
+
+/// Minimum gap between `progress` events; the TUI polls the file far less often than lines are acked.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct EventSink {
+    file: Option<File>,
+    last_progress: Option<Instant>,
+}
+
+impl EventSink {
+    pub fn from_env() -> Self {
+        let file = std::env::var("EUREKA_EVENT_FILE")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .and_then(|p| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&p)
+                    .map_err(|e| eprintln!("Cannot open event file {}: {}", p, e))
+                    .ok()
+            });
+        Self {
+            file,
+            last_progress: None,
+        }
+    }
+
+    pub fn started(&mut self, device: &str, file: &str, total_lines: u64) {
+        self.emit(json!({ "event": "started", "device": device, "file": file, "total_lines": total_lines }));
+    }
+
+    pub fn firmware(&mut self, info: &FirmwareInfo, completion_wait: bool) {
+        self.emit(json!({
+            "event": "firmware",
+            "name": info.name,
+            "version": info.version,
+            "machine": info.machine,
+            "capabilities": info.capabilities,
+            "completion_wait": completion_wait,
+        }));
+    }
+
+    /// Throttled unless `force` is set (used for the final count).
+    pub fn progress(&mut self, line: u64, total: u64, force: bool) {
+        if !force
+            && self
+                .last_progress
+                .is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.last_progress = Some(Instant::now());
+        self.emit(json!({ "event": "progress", "line": line, "total": total }));
+    }
+
+    pub fn temperature(&mut self, t: &Temperatures) {
+        self.emit(json!({
+            "event": "temperature",
+            "hotend": t.hotend,
+            "hotend_target": t.hotend_target,
+            "bed": t.bed,
+            "bed_target": t.bed_target,
+        }));
+    }
+
+    pub fn firmware_error(&mut self, message: &str, fatal: bool) {
+        self.emit(json!({ "event": "firmware_error", "message": message, "fatal": fatal }));
+    }
+
+    pub fn paused(&mut self) {
+        self.emit(json!({ "event": "paused" }));
+    }
+
+    pub fn resumed(&mut self) {
+        self.emit(json!({ "event": "resumed" }));
+    }
+
+    pub fn finished(&mut self, lines: u64) {
+        self.emit(json!({ "event": "finished", "lines": lines }));
+    }
+
+    pub fn cancelled(&mut self, lines: u64) {
+        self.emit(json!({ "event": "cancelled", "lines": lines }));
+    }
+
+    pub fn emergency_stop(&mut self) {
+        self.emit(json!({ "event": "emergency_stop" }));
+    }
+
+    pub fn failed(&mut self, message: &str) {
+        self.emit(json!({ "event": "failed", "message": message }));
+    }
+
+    fn emit(&mut self, event: Value) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        // A single write per event keeps lines whole for a reader tailing the file.
+        let mut line = event.to_string();
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            eprintln!("Event file write failed, disabling events: {}", e);
+            self.file = None;
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! Ack-driven G-code streaming: every command is framed with a line number and checksum, at most
+//! `window` commands (and `rx_budget` bytes) are unacknowledged at once, and `Resend` / `rs`
+//! requests rewind to the requested line from the history buffer.
+//!
+//! On connect the firmware is identified with `M115`; its `Cap:` lines (and probes for firmware that
+//! does not report them) decide between `M155` autoreport and `M105` polling, whether `M400` is
+//! used to wait for the end of the print, and whether an `estop` is answered with a warning that
+//! `M112` waits behind the firmware's queue (no `EMERGENCY_PARSER`).
+//! Readings, progress, firmware errors and host-action pauses are reported through [`EventSink`].
+//!
+//! Commands from the [`ControlSocket`] are checked between lines: pause drains the queue, records
+//! the position with `M114` and parks; resume returns and restores the job's modes; cancel cools
+//! down, homes XY and releases the steppers. An emergency stop (`M112`, written by the control
+//! thread itself) aborts whatever the host is waiting on.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::time::{Duration, Instant};
+
+use serialport::SerialPort;
+
+use crate::control::{ControlCommand, ControlSocket};
+use crate::events::EventSink;
+use crate::park::{
+    parse_position, pause_sequence, resume_sequence, MotionModes, ParkConfig, Position, CANCEL_SEQUENCE,
+};
+use crate::protocol::{
+    classify_notice, clean_line, frame, parse_host_action, parse_reply, parse_temperatures,
+    FirmwareInfo, HostAction, Notice, Reply,
+};
+
+/// Framed lines kept for resend requests; Marlin never asks for more than its buffer depth back.
+const HISTORY_LEN: usize = 256;
+/// How long to wait for the boot banner (`start`) after opening the port resets the board.
+const STARTUP_QUIET: Duration = Duration::from_millis(2000);
+/// The firmware reported a fatal condition (`Error:Printer halted. kill() called!`, thermal runaway,
+/// `!!` …) and will not answer again until reset. Carries the firmware's line verbatim.
+#[derive(Debug)]
+pub struct FirmwareHalt(pub String);
+
+impl fmt::Display for FirmwareHalt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Printer firmware halted: {}", self.0)
+    }
+}
+
+impl std::error::Error for FirmwareHalt {}
+
+pub const EMERGENCY_STOP_MESSAGE: &str = "Emergency stop (M112) sent; reset the printer before the next job";
+/// `M400` returns only after the planner is empty, so allow for long final moves.
+const FINISH_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub struct HostConfig {
+    pub window: usize,
+    pub rx_budget: usize,
+    pub response_timeout: Duration,
+    /// Interval for `M155 S<n>` autoreport, or between `M105` polls when autoreport is missing.
+    pub temp_interval: Duration,
+    pub park: ParkConfig,
+}
+
+/// How [`Host::stream`] ended.
+pub enum StreamEnd {
+    Completed(u64),
+    Cancelled(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TempReporting {
+    Autoreport,
+    Polling,
+}
+
+/// One framed command waiting for its `ok`.
+struct InFlight {
+    line_number: u32,
+    bytes: usize,
+}
+
+pub struct Host<'a> {
+    port: Box<dyn SerialPort>,
+    config: HostConfig,
+    rx: Vec<u8>,
+    next_line: u32,
+    history: VecDeque<(u32, String)>,
+    in_flight: VecDeque<InFlight>,
+    /// While set, the next lines to transmit come from `history` starting at this number.
+    resend_from: Option<u32>,
+    /// Every in-flight line sent after a rejected one repeats the same `Resend: N`; skip those.
+    duplicate_resends: (u32, usize),
+    temp_reporting: TempReporting,
+    last_temp_poll: Instant,
+    events: &'a mut EventSink,
+    control: Option<ControlSocket>,
+    /// Modes set by the job itself, restored after a pause.
+    modes: MotionModes,
+    /// Last `M114` report.
+    position: Option<Position>,
+    firmware: FirmwareInfo,
+    /// `M400` answered without `Unknown command`.
+    completion_wait: bool,
+    /// Set by any `Unknown command` reply; see [`Host::probe`].
+    rejected: bool,
+    /// Inside `M0` / `M600` waiting for the user (`echo:busy: paused for user`).
+    waiting_for_user: bool,
+}
+
+impl<'a> Host<'a> {
+    pub fn new(
+        mut port: Box<dyn SerialPort>,
+        config: HostConfig,
+        events: &'a mut EventSink,
+        control: Option<ControlSocket>,
+    ) -> io::Result<Self> {
+        port.set_timeout(Duration::from_millis(100))?;
+        Ok(Self {
+            port,
+            config,
+            rx: Vec::new(),
+            next_line: 0,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            in_flight: VecDeque::new(),
+            resend_from: None,
+            duplicate_resends: (0, 0),
+            temp_reporting: TempReporting::Autoreport,
+            last_temp_poll: Instant::now(),
+            events,
+            control,
+            modes: MotionModes::default(),
+            position: None,
+            firmware: FirmwareInfo::default(),
+            completion_wait: true,
+            rejected: false,
+            waiting_for_user: false,
+        })
+    }
+
+    /// Wait for the boot banner, then reset the firmware's line counter with `M110 N0`.
+    pub fn connect(&mut self) -> anyhow::Result<()> {
+        let mut deadline = Instant::now() + STARTUP_QUIET;
+        while Instant::now() < deadline {
+            match self.read_line(deadline)? {
+                Some(line) if line.trim() == "start" => break,
+                // Boot chatter keeps coming; keep waiting until the board goes quiet.
+                Some(_) => deadline = Instant::now() + STARTUP_QUIET,
+                None => break,
+            }
+        }
+        self.next_line = 0;
+        self.send_new("M110 N0")?;
+        self.next_line = 1;
+        self.wait_until_idle(self.config.response_timeout)?;
+        self.identify()?;
+        self.enable_temperature_reports()?;
+        if let Some(control) = &self.control {
+            control.set_emergency_parser(self.firmware.capability("EMERGENCY_PARSER") != Some(false));
+        }
+        self.events.firmware(&self.firmware, self.completion_wait);
+        Ok(())
+    }
+
+    /// `M115` for name, version and `Cap:` flags, then check `M400` (an empty planner answers at once).
+    fn identify(&mut self) -> anyhow::Result<()> {
+        self.probe("M115")?;
+        self.completion_wait = self.probe("M400")?;
+        match (&self.firmware.name, &self.firmware.version) {
+            (Some(name), Some(version)) => println!("Firmware: {} {}", name, version),
+            (Some(name), None) => println!("Firmware: {}", name),
+            _ => println!("Firmware did not identify itself (M115)"),
+        }
+        if !self.completion_wait {
+            println!("Firmware rejected M400; not waiting for motion to finish at the end");
+        }
+        Ok(())
+    }
+
+    /// Send `cmd`, wait for its `ok`, and report whether the firmware accepted it.
+    fn probe(&mut self, cmd: &str) -> anyhow::Result<bool> {
+        self.rejected = false;
+        self.send_new(cmd)?;
+        self.wait_until_idle(self.config.response_timeout)?;
+        Ok(!self.rejected)
+    }
+
+    /// `M155` autoreport when `Cap:AUTOREPORT_TEMP` allows it (or is not reported and `M155` is
+    /// accepted); otherwise `M105` polling.
+    fn enable_temperature_reports(&mut self) -> anyhow::Result<()> {
+        let secs = self.config.temp_interval.as_secs().max(1);
+        let autoreport = match self.firmware.capability("AUTOREPORT_TEMP") {
+            Some(false) => false,
+            _ => self.probe(&format!("M155 S{}", secs))?,
+        };
+        if !autoreport {
+            self.temp_reporting = TempReporting::Polling;
+        }
+        Ok(())
+    }
+
+    fn poll_temperature_if_due(&mut self) -> anyhow::Result<()> {
+        if self.temp_reporting != TempReporting::Polling
+            || self.last_temp_poll.elapsed() < self.config.temp_interval
+        {
+            return Ok(());
+        }
+        let bytes = frame(self.next_line, "M105").len() + 1;
+        if self.has_room(bytes) {
+            self.send_new("M105")?;
+            self.last_temp_poll = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Stream every non-comment line of `reader`, keeping the firmware's queue full but not overrun.
+    /// `total` is only used for `progress` events.
+    pub fn stream<R: BufRead>(&mut self, reader: R, total: u64) -> anyhow::Result<StreamEnd> {
+        let mut source = reader.lines();
+        let mut pending: Option<String> = None;
+        let mut exhausted = false;
+        let mut sent: u64 = 0;
+
+        loop {
+            match self.next_control() {
+                Some(ControlCommand::Pause) => {
+                    if !self.pause_until_resumed()? {
+                        self.cancel()?;
+                        return Ok(StreamEnd::Cancelled(sent));
+                    }
+                }
+                Some(ControlCommand::Cancel) => {
+                    self.cancel()?;
+                    return Ok(StreamEnd::Cancelled(sent));
+                }
+                Some(ControlCommand::Resume) | Some(ControlCommand::EmergencyStop) | None => {}
+            }
+            self.poll_temperature_if_due()?;
+            loop {
+                if let Some(n) = self.resend_from {
+                    if !self.has_room(self.history_bytes(n)) {
+                        break;
+                    }
+                    self.resend(n)?;
+                    continue;
+                }
+                if pending.is_none() && !exhausted {
+                    pending = next_command(&mut source)?;
+                    exhausted = pending.is_none();
+                }
+                let Some(cmd) = pending.as_deref() else {
+                    break;
+                };
+                let bytes = frame(self.next_line, cmd).len() + 1;
+                if !self.has_room(bytes) {
+                    break;
+                }
+                self.modes.observe(cmd);
+                self.send_new(cmd)?;
+                pending = None;
+                sent += 1;
+                self.events.progress(sent, total, false);
+            }
+
+            if exhausted && self.in_flight.is_empty() && self.resend_from.is_none() {
+                self.events.progress(sent, total, true);
+                return Ok(StreamEnd::Completed(sent));
+            }
+            self.pump(Instant::now() + self.config.response_timeout)?;
+        }
+    }
+
+    /// Send `M400` and wait for its `ok`, i.e. until all buffered motion has finished.
+    /// Skipped when the firmware rejected `M400` at connect time.
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        if !self.completion_wait {
+            return Ok(());
+        }
+        self.send_new("M400")?;
+        self.wait_until_idle(FINISH_TIMEOUT)
+    }
+
+    fn next_control(&self) -> Option<ControlCommand> {
+        self.control.as_ref().and_then(ControlSocket::try_recv)
+    }
+
+    /// Send each command and wait for its `ok` before the next (short sequences only).
+    fn run_sequence<S: AsRef<str>>(&mut self, cmds: &[S]) -> anyhow::Result<()> {
+        for cmd in cmds {
+            self.send_new(cmd.as_ref())?;
+            self.wait_until_idle(FINISH_TIMEOUT)?;
+        }
+        Ok(())
+    }
+
+    /// Park the head and idle until `resume` (`Ok(true)`) or `cancel` (`Ok(false)`).
+    fn pause_until_resumed(&mut self) -> anyhow::Result<bool> {
+        self.wait_until_idle(FINISH_TIMEOUT)?;
+        self.position = None;
+        self.run_sequence(&["M400", "M114"])?;
+        let at = self
+            .position
+            .ok_or_else(|| anyhow::anyhow!("Printer did not report its position (M114); cannot pause"))?;
+        self.run_sequence(&pause_sequence(&self.config.park))?;
+        self.events.paused();
+
+        loop {
+            match self.next_control() {
+                Some(ControlCommand::Resume) => {
+                    self.run_sequence(&resume_sequence(&at, &self.modes, &self.config.park))?;
+                    self.events.resumed();
+                    return Ok(true);
+                }
+                Some(ControlCommand::Cancel) => return Ok(false),
+                Some(ControlCommand::Pause) | Some(ControlCommand::EmergencyStop) | None => {}
+            }
+            // Keep temperature reports flowing while parked.
+            self.poll_temperature_if_due()?;
+            if let Some(line) = self.read_line(Instant::now() + Duration::from_millis(200))? {
+                self.handle_line(line)?;
+            }
+        }
+    }
+
+    /// Stop after the queued commands: heaters and fan off, lift, home XY, steppers off.
+    fn cancel(&mut self) -> anyhow::Result<()> {
+        self.wait_until_idle(FINISH_TIMEOUT)?;
+        self.run_sequence(&CANCEL_SEQUENCE)
+    }
+
+    fn wait_until_idle(&mut self, timeout: Duration) -> anyhow::Result<()> {
+        while !self.in_flight.is_empty() || self.resend_from.is_some() {
+            if let Some(n) = self.resend_from {
+                if self.has_room(self.history_bytes(n)) {
+                    self.resend(n)?;
+                    continue;
+                }
+            }
+            self.pump(Instant::now() + timeout)?;
+        }
+        Ok(())
+    }
+
+    fn has_room(&self, bytes: usize) -> bool {
+        if self.in_flight.is_empty() {
+            return true;
+        }
+        let queued: usize = self.in_flight.iter().map(|f| f.bytes).sum();
+        self.in_flight.len() < self.config.window && queued + bytes <= self.config.rx_budget
+    }
+
+    fn history_bytes(&self, n: u32) -> usize {
+        self.history
+            .iter()
+            .find(|(num, _)| *num == n)
+            .map(|(_, framed)| framed.len() + 1)
+            .unwrap_or(0)
+    }
+
+    fn send_new(&mut self, cmd: &str) -> anyhow::Result<()> {
+        let framed = frame(self.next_line, cmd);
+        self.write_framed(self.next_line, &framed)?;
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back((self.next_line, framed));
+        self.next_line += 1;
+        Ok(())
+    }
+
+    fn resend(&mut self, n: u32) -> anyhow::Result<()> {
+        let framed = self
+            .history
+            .iter()
+            .find(|(num, _)| *num == n)
+            .map(|(_, f)| f.clone())
+            .ok_or_else(|| anyhow::anyhow!("Firmware requested line {} which is no longer buffered", n))?;
+        self.write_framed(n, &framed)?;
+        self.resend_from = if n + 1 < self.next_line { Some(n + 1) } else { None };
+        Ok(())
+    }
+
+    fn write_framed(&mut self, line_number: u32, framed: &str) -> anyhow::Result<()> {
+        let mut out = String::with_capacity(framed.len() + 1);
+        out.push_str(framed);
+        out.push('\n');
+        self.port
+            .write_all(out.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Write failed: {}", e))?;
+        self.port
+            .flush()
+            .map_err(|e| anyhow::anyhow!("Flush failed: {}", e))?;
+        self.in_flight.push_back(InFlight {
+            line_number,
+            bytes: out.len(),
+        });
+        Ok(())
+    }
+
+    /// Read and act on one firmware reply.
+    fn pump(&mut self, deadline: Instant) -> anyhow::Result<()> {
+        let line = self.read_line(deadline)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No response from printer for {}s with {} command(s) unacknowledged",
+                self.config.response_timeout.as_secs(),
+                self.in_flight.len()
+            )
+        })?;
+        self.handle_line(line)
+    }
+
+    fn handle_line(&mut self, line: String) -> anyhow::Result<()> {
+        if self.firmware.absorb(&line) {
+            return Ok(());
+        }
+        if let Some(position) = parse_position(&line) {
+            self.position = Some(position);
+        }
+        if let Some(temps) = parse_temperatures(&line) {
+            self.events.temperature(&temps);
+        }
+        match parse_host_action(&line) {
+            Some(HostAction::Paused) => self.events.paused(),
+            Some(HostAction::Resumed) => self.events.resumed(),
+            None => {}
+        }
+
+        match parse_reply(&line) {
+            Reply::Ok => {
+                self.in_flight.pop_front();
+                if self.waiting_for_user {
+                    self.waiting_for_user = false;
+                    self.events.resumed();
+                }
+            }
+            Reply::Resend(n) => self.handle_resend(n)?,
+            Reply::Other(text) => match classify_notice(&text) {
+                Some(Notice::Halted) => {
+                    eprintln!("{}", text);
+                    self.events.firmware_error(&text, true);
+                    return Err(FirmwareHalt(text).into());
+                }
+                Some(Notice::Error) => {
+                    eprintln!("{}", text);
+                    self.events.firmware_error(&text, false);
+                }
+                Some(Notice::WaitingForUser) if !self.waiting_for_user => {
+                    self.waiting_for_user = true;
+                    self.events.paused();
+                }
+                // Keepalives only matter because every line read restarts the response timeout.
+                Some(Notice::Busy) | Some(Notice::WaitingForUser) => {}
+                None => {
+                    if text.contains("Unknown command") {
+                        self.rejected = true;
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn handle_resend(&mut self, n: u32) -> anyhow::Result<()> {
+        let (dup_line, remaining) = self.duplicate_resends;
+        if dup_line == n && remaining > 0 {
+            self.duplicate_resends = (n, remaining - 1);
+            return Ok(());
+        }
+        if n >= self.next_line {
+            // Nothing sent at or after that number; the firmware is ahead of us.
+            return Ok(());
+        }
+        let later = self
+            .in_flight
+            .iter()
+            .filter(|f| f.line_number > n)
+            .count();
+        self.duplicate_resends = (n, later);
+        self.resend_from = Some(n);
+        Ok(())
+    }
+
+    /// One `\n`-terminated line, or `None` if `deadline` passes first.
+    /// Fails as soon as an emergency stop has been sent.
+    fn read_line(&mut self, deadline: Instant) -> io::Result<Option<String>> {
+        let mut buf = [0u8; 256];
+        loop {
+            if self.control.as_ref().is_some_and(ControlSocket::emergency_stopped) {
+                self.events.emergency_stop();
+                return Err(io::Error::other(EMERGENCY_STOP_MESSAGE));
+            }
+            if let Some(pos) = self.rx.iter().position(|&b| b == b'\n') {
+                let raw: Vec<u8> = self.rx.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&raw).trim().to_string();
+                if line.is_empty() {
+                    continue;
+                }
+                return Ok(Some(line));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            match self.port.read(&mut buf) {
+                Ok(0) => {}
+                Ok(n) => self.rx.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn next_command<B: BufRead>(source: &mut io::Lines<B>) -> io::Result<Option<String>> {
+    for line in source.by_ref() {
+        let line = line?;
+        if let Some(cmd) = clean_line(&line) {
+            return Ok(Some(cmd.to_string()));
+        }
+    }
+    Ok(None)
+}
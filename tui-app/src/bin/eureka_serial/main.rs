@@ -0,0 +1,291 @@
+//! Sends `test.gcode` (or path from argv[1]) to the printer over USB serial.
+//! Honors `EUREKA_PRINTER_DEVICE`; otherwise tries /dev/3Dprinter, /dev/ttyACM0, /dev/ttyUSB0.
+//! Optional: `EUREKA_PRINTER_BAUD` (default 115200).
+//!
+//! Flow control (`EUREKA_FLOW_CONTROL`):
+//! - `ack` (default): lines are sent as `N<line> <cmd>*<checksum>`; up to `EUREKA_SERIAL_WINDOW`
+//!   commands (default 4) and `EUREKA_SERIAL_RX_BUFFER` bytes (default 127, Marlin's RX ring) may be
+//!   unacknowledged. `Resend: N` / `rs N` rewind to line `N`. `EUREKA_SERIAL_TIMEOUT_SECS`
+//!   (default 60) bounds the wait for any reply while commands are outstanding.
+//! - `delay`: legacy mode — plain lines with a fixed `EUREKA_LINE_DELAY_MS` (default 5) sleep and no
+//!   reply handling.
+//!
+//! Temperatures: `M155 S<EUREKA_TEMP_INTERVAL_SECS>` autoreport (default 2s), falling back to `M105`
+//! polling at the same interval.
+//!
+//! Events: when `EUREKA_EVENT_FILE` is set, JSON-lines events (started, progress, temperature,
+//! firmware error, paused/resumed, finished/cancelled/failed) are appended to it; see `events.rs`.
+//!
+//! Control: when `EUREKA_CONTROL_SOCKET` is set, `pause`, `resume`, `cancel` and `estop` lines on
+//! that Unix socket control the job (delay mode only honors `estop`); see `control.rs`. Pause parks at `EUREKA_PARK_X` /
+//! `EUREKA_PARK_Y` (default 0/220) after lifting `EUREKA_PARK_LIFT_MM` (default 10) and retracting
+//! `EUREKA_PAUSE_RETRACT_MM` (default 2).
+//!
+//! Firmware errors: `Error:` lines are reported as events; a halt (`kill() called`, thermal runaway,
+//! MAXTEMP/MINTEMP, `!!` …) aborts at once with exit code 4 instead of waiting for an `ok` that
+//! will never come.
+//!
+//! After the last G-code line (default): sends `M400`, then waits for its `ok` so the host does not
+//! finish before motion stops. Skipped automatically when the firmware rejects `M400` at connect
+//! (ack mode); set `EUREKA_SKIP_PRINT_COMPLETION_WAIT=1` or `true` to skip it anyway.
+
+mod control;
+mod events;
+mod host;
+mod park;
+mod protocol;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use serialport::SerialPort;
+
+use events::EventSink;
+use control::ControlSocket;
+use host::{FirmwareHalt, Host, HostConfig, StreamEnd, EMERGENCY_STOP_MESSAGE};
+use protocol::{classify_notice, Notice};
+use park::ParkConfig;
+
+fn skip_print_completion_wait() -> bool {
+    match env::var("EUREKA_SKIP_PRINT_COMPLETION_WAIT")
+        .ok()
+        .as_deref()
+        .map(str::trim)
+    {
+        Some("1") | Some("true") | Some("TRUE") | Some("True") => true,
+        _ => false,
+    }
+}
+
+/// Drop stale RX data so a following `ok` is from `M400`, not an earlier command.
+fn drain_receive_buffer(port: &mut dyn SerialPort) -> io::Result<()> {
+    let prev = port.timeout();
+    port.set_timeout(Duration::from_millis(25))?;
+    let mut buf = [0u8; 512];
+    loop {
+        match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(_) => continue,
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => break,
+            Err(e) => {
+                let _ = port.set_timeout(prev);
+                return Err(e);
+            }
+        }
+    }
+    port.set_timeout(prev)?;
+    Ok(())
+}
+
+/// Exit status for [`FirmwareHalt`], so scripts can tell a printer fault from a host failure.
+const EXIT_FIRMWARE_HALT: i32 = 4;
+
+/// Wait for Marlin-style `ok` / `ok …` line after `M400` (long timeout: motion may be large).
+/// A halt reported by the firmware ends the wait immediately.
+fn wait_for_ok_line(port: &mut dyn SerialPort, events: &mut EventSink) -> anyhow::Result<()> {
+    port.set_timeout(Duration::from_secs(300))?;
+    let mut reader = BufReader::new(port);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let t = line.trim();
+        if t == "ok" || t.starts_with("ok ") {
+            break;
+        }
+        match classify_notice(t) {
+            Some(Notice::Halted) => {
+                events.firmware_error(t, true);
+                return Err(FirmwareHalt(t.to_string()).into());
+            }
+            Some(Notice::Error) => events.firmware_error(t, false),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn resolve_device() -> Option<String> {
+    if let Ok(p) = env::var("EUREKA_PRINTER_DEVICE") {
+        if Path::new(&p).exists() {
+            return Some(p);
+        }
+    }
+    for d in ["/dev/3Dprinter", "/dev/ttyACM0", "/dev/ttyUSB0"] {
+        if Path::new(d).exists() {
+            return Some(d.to_string());
+        }
+    }
+    None
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+fn baud() -> u32 {
+    env_parse("EUREKA_PRINTER_BAUD", 115_200)
+}
+
+fn line_delay_ms() -> u64 {
+    env_parse("EUREKA_LINE_DELAY_MS", 5)
+}
+
+fn use_delay_flow_control() -> bool {
+    matches!(
+        env::var("EUREKA_FLOW_CONTROL")
+            .ok()
+            .as_deref()
+            .map(str::trim),
+        Some("delay") | Some("DELAY")
+    )
+}
+
+fn host_config() -> HostConfig {
+    HostConfig {
+        window: env_parse("EUREKA_SERIAL_WINDOW", 4usize).max(1),
+        rx_budget: env_parse("EUREKA_SERIAL_RX_BUFFER", 127usize),
+        response_timeout: Duration::from_secs(env_parse("EUREKA_SERIAL_TIMEOUT_SECS", 60u64)),
+        temp_interval: Duration::from_secs(env_parse("EUREKA_TEMP_INTERVAL_SECS", 2u64).max(1)),
+        park: ParkConfig {
+            x: env_parse("EUREKA_PARK_X", 0.0),
+            y: env_parse("EUREKA_PARK_Y", 220.0),
+            lift: env_parse("EUREKA_PARK_LIFT_MM", 10.0),
+            retract: env_parse("EUREKA_PAUSE_RETRACT_MM", 2.0),
+        },
+    }
+}
+
+/// Number of lines that will actually be sent, for `progress` events.
+fn count_commands(path: &str) -> io::Result<u64> {
+    let mut count = 0;
+    for line in BufReader::new(File::open(path)?).lines() {
+        if protocol::clean_line(&line?).is_some() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Fallback: write plain lines with a fixed sleep and never read replies until `M400`.
+fn stream_with_delay(
+    port: &mut Box<dyn SerialPort>,
+    reader: BufReader<File>,
+    total: u64,
+    events: &mut EventSink,
+    control: Option<ControlSocket>,
+) -> anyhow::Result<u64> {
+    let delay = Duration::from_millis(line_delay_ms());
+    let mut sent: u64 = 0;
+    for line in reader.lines() {
+        if control.as_ref().is_some_and(ControlSocket::emergency_stopped) {
+            events.emergency_stop();
+            anyhow::bail!(EMERGENCY_STOP_MESSAGE);
+        }
+        let line = line?;
+        let Some(cmd) = protocol::clean_line(&line) else {
+            continue;
+        };
+        let mut out = cmd.to_string();
+        out.push('\n');
+        port
+            .write_all(out.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Write failed: {}", e))?;
+        port
+            .flush()
+            .map_err(|e| anyhow::anyhow!("Flush failed: {}", e))?;
+        std::thread::sleep(delay);
+        sent += 1;
+        events.progress(sent, total, false);
+    }
+    events.progress(sent, total, true);
+
+    if !skip_print_completion_wait() {
+        drain_receive_buffer(&mut **port)?;
+        port
+            .write_all(b"M400\n")
+            .map_err(|e| anyhow::anyhow!("Write M400 failed: {}", e))?;
+        port
+            .flush()
+            .map_err(|e| anyhow::anyhow!("Flush after M400 failed: {}", e))?;
+        wait_for_ok_line(&mut **port, events).context(
+            "Waiting for ok after M400 failed (set EUREKA_SKIP_PRINT_COMPLETION_WAIT=1 to skip)",
+        )?;
+    }
+    Ok(sent)
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut events = EventSink::from_env();
+    let result = run(&mut events);
+    match &result {
+        Ok(StreamEnd::Completed(sent)) => events.finished(*sent),
+        Ok(StreamEnd::Cancelled(sent)) => events.cancelled(*sent),
+        Err(e) => {
+            events.failed(&format!("{:#}", e));
+            if e.downcast_ref::<FirmwareHalt>().is_some() {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(EXIT_FIRMWARE_HALT);
+            }
+        }
+    }
+    result.map(|_| ())
+}
+
+fn run(events: &mut EventSink) -> anyhow::Result<StreamEnd> {
+    let gcode_path = env::args().nth(1).unwrap_or_else(|| "test.gcode".to_string());
+    let port_name = resolve_device().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No serial device (set EUREKA_PRINTER_DEVICE or use /dev/3Dprinter per README)"
+        )
+    })?;
+
+    let baud = baud();
+    let mut port = serialport::new(&port_name, baud)
+        .timeout(Duration::from_millis(500))
+        .open()
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", port_name, e))?;
+
+    let file = File::open(&gcode_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", gcode_path, e))?;
+    let reader = BufReader::new(file);
+    let total = count_commands(&gcode_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", gcode_path, e))?;
+    let absolute = std::fs::canonicalize(&gcode_path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| gcode_path.clone());
+    events.started(&port_name, &absolute, total);
+    let delay_mode = use_delay_flow_control();
+    let control = ControlSocket::from_env(port.try_clone().ok(), !delay_mode);
+
+    if delay_mode {
+        return stream_with_delay(&mut port, reader, total, events, control).map(StreamEnd::Completed);
+    }
+
+    let mut host = Host::new(port, host_config(), events, control)?;
+    host.connect()
+        .with_context(|| format!("Printer handshake (M110) failed on {}", port_name))?;
+    let sent = match host.stream(reader, total)? {
+        StreamEnd::Completed(sent) => sent,
+        StreamEnd::Cancelled(sent) => {
+            println!("Print cancelled after {} G-code lines", sent);
+            return Ok(StreamEnd::Cancelled(sent));
+        }
+    };
+    println!("Sent {} G-code lines to {}", sent, port_name);
+
+    if !skip_print_completion_wait() {
+        host.finish().context(
+            "Waiting for ok after M400 failed (set EUREKA_SKIP_PRINT_COMPLETION_WAIT=1 to skip)",
+        )?;
+    }
+
+    Ok(StreamEnd::Completed(sent))
+}
@@ -0,0 +1,149 @@
+//! Host-side pause / resume / cancel sequences. Parking is done with plain `G1` moves rather than
+//! `M125`/`M600` so it works on firmware built without `PARK_HEAD_ON_PAUSE` or `ADVANCED_PAUSE_FEATURE`.
+
+/// Travel feedrate for the park and return moves (mm/min).
+const TRAVEL_FEEDRATE: u32 = 6000;
+const Z_FEEDRATE: u32 = 600;
+const RETRACT_FEEDRATE: u32 = 2400;
+
+pub const CANCEL_SEQUENCE: [&str; 8] = [
+    "M104 S0", // hotend off
+    "M140 S0", // bed off
+    "M107",    // part fan off
+    "G91",
+    "G1 Z10 F600", // clear the part before homing
+    "G90",
+    "G28 X Y",
+    "M84", // steppers off
+];
+
+pub struct ParkConfig {
+    pub x: f32,
+    pub y: f32,
+    /// Relative Z lift before moving to (x, y).
+    pub lift: f32,
+    /// Filament retracted while parked, pushed back on resume.
+    pub retract: f32,
+}
+
+/// Positioning modes and feedrate of the job, as set by the lines streamed so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionModes {
+    pub absolute: bool,
+    pub relative_e: bool,
+    pub feedrate: Option<f32>,
+}
+
+impl Default for MotionModes {
+    fn default() -> Self {
+        Self {
+            absolute: true,
+            relative_e: false,
+            feedrate: None,
+        }
+    }
+}
+
+impl MotionModes {
+    /// Update from one (comment-free) command sent to the printer.
+    pub fn observe(&mut self, cmd: &str) {
+        let mut words = cmd.split_whitespace();
+        match words.next() {
+            Some("G90") => self.absolute = true,
+            Some("G91") => self.absolute = false,
+            Some("M82") => self.relative_e = false,
+            Some("M83") => self.relative_e = true,
+            Some("G0") | Some("G1") => {
+                if let Some(f) = words.find_map(|w| w.strip_prefix('F')) {
+                    self.feedrate = f.parse().ok().or(self.feedrate);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Logical position from an `M114` report (`X:10.00 Y:20.00 Z:0.30 E:12.40 Count X:800 …`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub e: f32,
+}
+
+pub fn parse_position(line: &str) -> Option<Position> {
+    // Stepper counts after `Count` repeat the axis letters; only the logical part is wanted.
+    let logical = line.split(" Count").next()?;
+    let axis = |key: &str| -> Option<f32> {
+        logical
+            .split_whitespace()
+            .find_map(|t| t.strip_prefix(key))
+            .and_then(|v| v.parse().ok())
+    };
+    Some(Position {
+        x: axis("X:")?,
+        y: axis("Y:")?,
+        z: axis("Z:")?,
+        e: axis("E:").unwrap_or(0.0),
+    })
+}
+
+/// Retract, lift and move to the park position. Leaves E in relative mode until resume.
+pub fn pause_sequence(park: &ParkConfig) -> Vec<String> {
+    vec![
+        "M83".to_string(),
+        format!("G1 E-{:.2} F{}", park.retract, RETRACT_FEEDRATE),
+        "G91".to_string(),
+        format!("G1 Z{:.2} F{}", park.lift, Z_FEEDRATE),
+        "G90".to_string(),
+        format!("G1 X{:.2} Y{:.2} F{}", park.x, park.y, TRAVEL_FEEDRATE),
+    ]
+}
+
+/// Return to `at`, prime, and restore the job's positioning modes and feedrate.
+pub fn resume_sequence(at: &Position, modes: &MotionModes, park: &ParkConfig) -> Vec<String> {
+    let mut cmds = vec![
+        "G90".to_string(),
+        format!("G1 X{:.3} Y{:.3} F{}", at.x, at.y, TRAVEL_FEEDRATE),
+        format!("G1 Z{:.3} F{}", at.z, Z_FEEDRATE),
+        "M83".to_string(),
+        format!("G1 E{:.2} F{}", park.retract, RETRACT_FEEDRATE),
+        format!("G92 E{:.5}", at.e),
+    ];
+    if !modes.relative_e {
+        cmds.push("M82".to_string());
+    }
+    if !modes.absolute {
+        cmds.push("G91".to_string());
+    }
+    if let Some(f) = modes.feedrate {
+        cmds.push(format!("G1 F{}", f));
+    }
+    cmds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_position_ignores_stepper_counts() {
+        let p = parse_position("X:10.00 Y:20.50 Z:0.30 E:123.40 Count X:800 Y:1640 Z:120").unwrap();
+        assert_eq!(p, Position { x: 10.0, y: 20.5, z: 0.3, e: 123.4 });
+        assert!(parse_position("ok").is_none());
+    }
+
+    #[test]
+    fn test_resume_restores_job_modes() {
+        let mut modes = MotionModes::default();
+        modes.observe("M83");
+        modes.observe("G1 X5 Y5 F1800");
+        let park = ParkConfig { x: 0.0, y: 220.0, lift: 10.0, retract: 2.0 };
+        let at = Position { x: 5.0, y: 5.0, z: 0.2, e: 0.0 };
+
+        let cmds = resume_sequence(&at, &modes, &park);
+        assert!(!cmds.contains(&"M82".to_string()));
+        assert_eq!(cmds.last().map(String::as_str), Some("G1 F1800"));
+    }
+}
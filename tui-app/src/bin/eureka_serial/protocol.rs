@@ -0,0 +1,348 @@
+//! Marlin host protocol helpers: `N<line> <cmd>*<checksum>` framing and reply classification.
+
+use std::collections::BTreeMap;
+
+/// XOR of every byte in `payload` (the part before `*`), as Marlin computes it.
+pub fn checksum(payload: &str) -> u8 {
+    payload.bytes().fold(0u8, |acc, b| acc ^ b)
+}
+
+/// Frame `command` as `N<line> <command>*<checksum>` (no trailing newline).
+pub fn frame(line_number: u32, command: &str) -> String {
+    let payload = format!("N{} {}", line_number, command);
+    let cs = checksum(&payload);
+    format!("{}*{}", payload, cs)
+}
+
+/// Strip `;` comments and surrounding whitespace; `None` when nothing is left to send.
+pub fn clean_line(raw: &str) -> Option<&str> {
+    let code = match raw.find(';') {
+        Some(i) => &raw[..i],
+        None => raw,
+    };
+    let code = code.trim();
+    if code.is_empty() {
+        None
+    } else {
+        Some(code)
+    }
+}
+
+/// One line received from the firmware.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// `ok` / `ok N12 P15 B3` — one command slot freed.
+    Ok,
+    /// `Resend: N` / `rs N` — firmware rejected line `N` and everything after it.
+    Resend(u32),
+    /// Anything else (echo, temperatures, `start`, errors handled elsewhere).
+    Other(String),
+}
+
+pub fn parse_reply(line: &str) -> Reply {
+    let t = line.trim();
+    if t == "ok" || t.starts_with("ok ") {
+        return Reply::Ok;
+    }
+    if let Some(n) = parse_resend(t) {
+        return Reply::Resend(n);
+    }
+    Reply::Other(t.to_string())
+}
+
+/// Marlin sends `Resend: 12` (older builds `Resend:12`); Repetier-style firmware sends `rs 12` / `rs N12`.
+fn parse_resend(t: &str) -> Option<u32> {
+    let lower = t.to_ascii_lowercase();
+    let rest = match lower.strip_prefix("resend") {
+        Some(r) => r.trim_start_matches(':'),
+        None => lower.strip_prefix("rs ")?,
+    };
+    let digits: String = rest
+        .trim()
+        .trim_start_matches('n')
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Marlin reports rejected lines as `Error:checksum mismatch, Last Line: 4` or
+/// `Error:Line Number is not Last Line Number+1, Last Line: 4`, always followed by a resend request.
+/// Those are recovered by the host and are not printer faults.
+pub fn is_transmission_error(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    ["checksum", "line number", "last line"]
+        .iter()
+        .any(|needle| lower.contains(needle))
+}
+
+/// Status lines that change what the host should do, beyond `ok` / resend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Notice {
+    /// The firmware stopped itself (`kill()`, thermal runaway, MAXTEMP, …); no `ok` will follow.
+    Halted,
+    /// Reported error the firmware keeps running after.
+    Error,
+    /// `echo:busy: processing` keepalive during long moves or heating.
+    Busy,
+    /// `echo:busy: paused for user` / `paused for input` (`M0`, `M600` filament change).
+    WaitingForUser,
+}
+
+/// Marlin prefixes these with `Error:`; RepRapFirmware and Repetier report fatal states with `!!`.
+const HALT_MARKERS: [&str; 7] = [
+    "printer halted",
+    "kill() called",
+    "thermal runaway",
+    "maxtemp",
+    "mintemp",
+    "heating failed",
+    "emergency stop",
+];
+
+pub fn classify_notice(line: &str) -> Option<Notice> {
+    let t = line.trim();
+    if t.starts_with("!!") {
+        return Some(Notice::Halted);
+    }
+    let lower = t.to_ascii_lowercase();
+    if lower.starts_with("error") {
+        if HALT_MARKERS.iter().any(|m| lower.contains(m)) {
+            return Some(Notice::Halted);
+        }
+        if is_transmission_error(t) {
+            return None;
+        }
+        return Some(Notice::Error);
+    }
+    let busy = lower.strip_prefix("echo:busy:")?;
+    if busy.contains("paused for") {
+        Some(Notice::WaitingForUser)
+    } else {
+        Some(Notice::Busy)
+    }
+}
+
+/// Pause state announced by the firmware through host action commands (`//action:…`),
+/// e.g. after `M600`, a filament runout or a pause from the printer's own menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HostAction {
+    Paused,
+    Resumed,
+}
+
+pub fn parse_host_action(line: &str) -> Option<HostAction> {
+    let action = line.trim().strip_prefix("//action:")?.trim();
+    match action.split_whitespace().next()? {
+        "pause" | "paused" => Some(HostAction::Paused),
+        "resume" | "resumed" => Some(HostAction::Resumed),
+        _ => None,
+    }
+}
+
+/// Identity and capabilities from the `M115` reply:
+/// `FIRMWARE_NAME:Marlin 2.1.2.1 (Jun  1 2023) SOURCE_CODE_URL:… MACHINE_TYPE:Ender-3 …`
+/// followed by one `Cap:NAME:0|1` line per capability.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FirmwareInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub machine: Option<String>,
+    pub capabilities: BTreeMap<String, bool>,
+}
+
+impl FirmwareInfo {
+    /// Fold one reply line in; returns `false` for lines that are not part of the `M115` report.
+    pub fn absorb(&mut self, line: &str) -> bool {
+        let t = line.trim();
+        if let Some(cap) = t.strip_prefix("Cap:") {
+            if let Some((name, value)) = cap.rsplit_once(':') {
+                self.capabilities.insert(name.trim().to_string(), value.trim() == "1");
+                return true;
+            }
+            return false;
+        }
+        if !t.starts_with("FIRMWARE_NAME:") {
+            return false;
+        }
+        let fields = report_fields(t);
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+                .filter(|v| !v.is_empty())
+        };
+        let full_name = field("FIRMWARE_NAME").unwrap_or_default();
+        let mut words = full_name.split_whitespace();
+        self.name = words.next().map(str::to_string);
+        // Marlin and Prusa put the version after the name; Klipper and RepRapFirmware send it separately.
+        self.version = field("FIRMWARE_VERSION").or_else(|| {
+            words
+                .next()
+                .filter(|w| w.trim_start_matches('v').starts_with(|c: char| c.is_ascii_digit()))
+                .map(str::to_string)
+        });
+        self.machine = field("MACHINE_TYPE");
+        true
+    }
+
+    /// `Some(true/false)` when the firmware reported the capability, `None` when it did not say.
+    pub fn capability(&self, name: &str) -> Option<bool> {
+        self.capabilities.get(name).copied()
+    }
+}
+
+/// Split `KEY:value KEY2:value with spaces` into pairs. A field starts at any token that begins
+/// with an upper-case `KEY:` (RepRapFirmware puts a space after the colon).
+fn report_fields(line: &str) -> Vec<(&str, String)> {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    for token in line.split_whitespace() {
+        let key = token.split_once(':').map(|(k, _)| k).filter(|k| {
+            k.len() > 1 && k.chars().all(|c| c.is_ascii_uppercase() || c == '_')
+        });
+        match (key, fields.last_mut()) {
+            (Some(k), _) => fields.push((k, token[k.len() + 1..].to_string())),
+            (None, Some((_, value))) => {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(token);
+            }
+            (None, None) => {}
+        }
+    }
+    fields
+}
+
+/// Hotend / bed readings from a Marlin temperature report (`T:210.0 /210.0 B:60.1 /60.0 @:0 B@:0`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Temperatures {
+    pub hotend: f32,
+    pub hotend_target: f32,
+    pub bed: Option<f32>,
+    pub bed_target: Option<f32>,
+}
+
+/// Parse a temperature report from `M105` (`ok T:…`), `M155` autoreport or `M109`/`M190` wait output.
+/// The first tool (`T:` or `T0:`) is used as the hotend.
+pub fn parse_temperatures(line: &str) -> Option<Temperatures> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let (hotend, hotend_target) = reading(&tokens, &["T:", "T0:"])?;
+    let bed = reading(&tokens, &["B:"]);
+    Some(Temperatures {
+        hotend,
+        hotend_target: hotend_target.unwrap_or(0.0),
+        bed: bed.map(|(v, _)| v),
+        bed_target: bed.and_then(|(_, t)| t),
+    })
+}
+
+/// `KEY:value /target`, `KEY:value/target` or `KEY:value` for the first matching key.
+fn reading(tokens: &[&str], keys: &[&str]) -> Option<(f32, Option<f32>)> {
+    let i = tokens
+        .iter()
+        .position(|t| keys.iter().any(|k| t.starts_with(k)))?;
+    let value = tokens[i].split_once(':')?.1;
+    let (current, inline_target) = match value.split_once('/') {
+        Some((c, t)) => (c, Some(t)),
+        None => (value, None),
+    };
+    let current: f32 = current.parse().ok()?;
+    let target = match inline_target {
+        Some(t) => t.parse().ok(),
+        None => tokens
+            .get(i + 1)
+            .and_then(|t| t.strip_prefix('/'))
+            .and_then(|t| t.parse().ok()),
+    };
+    Some((current, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_matches_marlin_checksum() {
+        // `N3 T0*57` is the example from the RepRap G-code wiki.
+        assert_eq!(frame(3, "T0"), "N3 T0*57");
+        assert_eq!(frame(0, "M110 N0"), "N0 M110 N0*125");
+    }
+
+    #[test]
+    fn test_clean_line_strips_comments() {
+        assert_eq!(clean_line("G1 X10 ; move"), Some("G1 X10"));
+        assert_eq!(clean_line("   ; only comment"), None);
+        assert_eq!(clean_line(""), None);
+    }
+
+    #[test]
+    fn test_parse_reply_variants() {
+        assert_eq!(parse_reply("ok"), Reply::Ok);
+        assert_eq!(parse_reply("ok N12 P15 B3"), Reply::Ok);
+        assert_eq!(parse_reply("Resend: 42"), Reply::Resend(42));
+        assert_eq!(parse_reply("Resend:7"), Reply::Resend(7));
+        assert_eq!(parse_reply("rs N9"), Reply::Resend(9));
+        assert_eq!(
+            parse_reply("echo:busy: processing"),
+            Reply::Other("echo:busy: processing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_temperatures() {
+        let t = parse_temperatures("ok T:210.3 /210.0 B:59.8 /60.0 @:64 B@:0").unwrap();
+        assert_eq!((t.hotend, t.hotend_target), (210.3, 210.0));
+        assert_eq!((t.bed, t.bed_target), (Some(59.8), Some(60.0)));
+
+        let t = parse_temperatures("T0:25.0/0.0 T1:24.0/0.0 B:24.5/0.0").unwrap();
+        assert_eq!((t.hotend, t.bed), (25.0, Some(24.5)));
+
+        assert!(parse_temperatures("echo:busy: processing").is_none());
+    }
+
+    #[test]
+    fn test_firmware_info_from_m115() {
+        let mut fw = FirmwareInfo::default();
+        assert!(fw.absorb("FIRMWARE_NAME:Marlin 2.1.2.1 (Jun  1 2023 12:00:00) SOURCE_CODE_URL:github.com/MarlinFirmware/Marlin PROTOCOL_VERSION:1.0 MACHINE_TYPE:Ender-3 EXTRUDER_COUNT:1"));
+        assert!(fw.absorb("Cap:AUTOREPORT_TEMP:1"));
+        assert!(fw.absorb("Cap:EMERGENCY_PARSER:0"));
+        assert!(!fw.absorb("ok"));
+        assert_eq!(fw.name.as_deref(), Some("Marlin"));
+        assert_eq!(fw.version.as_deref(), Some("2.1.2.1"));
+        assert_eq!(fw.machine.as_deref(), Some("Ender-3"));
+        assert_eq!(fw.capability("AUTOREPORT_TEMP"), Some(true));
+        assert_eq!(fw.capability("EMERGENCY_PARSER"), Some(false));
+        assert_eq!(fw.capability("SDCARD"), None);
+
+        let mut fw = FirmwareInfo::default();
+        fw.absorb("FIRMWARE_NAME: RepRapFirmware for Duet 3 FIRMWARE_VERSION: 3.4.5 ELECTRONICS: Duet 3 MB6HC");
+        assert_eq!(fw.name.as_deref(), Some("RepRapFirmware"));
+        assert_eq!(fw.version.as_deref(), Some("3.4.5"));
+    }
+
+    #[test]
+    fn test_error_and_host_action_classification() {
+        assert!(is_transmission_error("Error:checksum mismatch, Last Line: 4"));
+        assert!(is_transmission_error("Error:Line Number is not Last Line Number+1, Last Line: 4"));
+        assert!(!is_transmission_error("Error:Thermal Runaway, system stopped! Heater_ID: 0"));
+
+        assert_eq!(
+            classify_notice("Error:Thermal Runaway, system stopped! Heater_ID: 0"),
+            Some(Notice::Halted)
+        );
+        assert_eq!(classify_notice("Error:Printer halted. kill() called!"), Some(Notice::Halted));
+        assert_eq!(classify_notice("!! Heater 1 fault"), Some(Notice::Halted));
+        assert_eq!(classify_notice("Error:checksum mismatch, Last Line: 4"), None);
+        assert_eq!(classify_notice("Error:Probing Failed"), Some(Notice::Error));
+        assert_eq!(classify_notice("echo:busy: processing"), Some(Notice::Busy));
+        assert_eq!(classify_notice("echo:busy: paused for user"), Some(Notice::WaitingForUser));
+        assert_eq!(classify_notice("ok"), None);
+
+        assert_eq!(parse_host_action("//action:paused"), Some(HostAction::Paused));
+        assert_eq!(parse_host_action("//action:resume"), Some(HostAction::Resumed));
+        assert_eq!(parse_host_action("//action:notification Heating..."), None);
+        assert_eq!(parse_host_action("ok"), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Content-addressed cache of Walrus blobs and the G-code sliced from them, so reprinting a Sculpt
+//! skips both the download and the slicer.
+//!
+//! ```text
+//! ~/.cache/eureka/
+//!   blobs/<blob id>                      blob exactly as served by Walrus (ciphertext for Seal models)
+//!   gcode/<blob id>-<fingerprint>.gcode  slicer output for that blob and slicer setup
+//! ```
+//!
+//! Walrus blob ids are content hashes, so an entry never goes stale; the fingerprint (see
+//! `slicer::fingerprint`) covers everything else that changes the G-code. G-code is only cached for
+//! unencrypted models, so nothing derived from a decrypted model lands here. Once the total size
+//! exceeds `cache.max_mb`, the least recently used entries go first (a hit refreshes the mtime).
+
+use anyhow::{anyhow, Context, Result};
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::CacheConfig;
+
+const BLOB_DIR: &str = "blobs";
+const GCODE_DIR: &str = "gcode";
+
+#[derive(Debug, Clone)]
+pub struct BlobCache {
+    root: PathBuf,
+    max_bytes: u64,
+}
+
+impl BlobCache {
+    /// `None` when caching is off (`cache.max_mb: 0`) or there is no cache directory.
+    pub fn open(config: &CacheConfig) -> Option<Self> {
+        if config.max_mb == 0 {
+            return None;
+        }
+        Some(Self {
+            root: config.root()?,
+            max_bytes: config.max_mb.saturating_mul(1024 * 1024),
+        })
+    }
+
+    /// The cached blob, if any.
+    pub fn blob(&self, blob_id: &str) -> Option<PathBuf> {
+        self.lookup(self.root.join(BLOB_DIR).join(entry_name(blob_id).ok()?))
+    }
+
+    pub fn store_blob(&self, blob_id: &str, src: &Path) -> Result<()> {
+        self.insert(&self.root.join(BLOB_DIR).join(entry_name(blob_id)?), src)
+    }
+
+    /// G-code sliced from `blob_id` with the slicer setup `fingerprint`, if any.
+    pub fn gcode(&self, blob_id: &str, fingerprint: &str) -> Option<PathBuf> {
+        self.lookup(self.gcode_path(blob_id, fingerprint).ok()?)
+    }
+
+    pub fn store_gcode(&self, blob_id: &str, fingerprint: &str, src: &Path) -> Result<()> {
+        self.insert(&self.gcode_path(blob_id, fingerprint)?, src)
+    }
+
+    fn gcode_path(&self, blob_id: &str, fingerprint: &str) -> Result<PathBuf> {
+        let name = format!("{}-{}.gcode", entry_name(blob_id)?, entry_name(fingerprint)?);
+        Ok(self.root.join(GCODE_DIR).join(name))
+    }
+
+    fn lookup(&self, path: PathBuf) -> Option<PathBuf> {
+        if !path.is_file() {
+            return None;
+        }
+        // Refresh the LRU position; a read-only cache still serves the hit.
+        let _ = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(SystemTime::now()));
+        Some(path)
+    }
+
+    /// Copy `src` in under `dest` (atomically), then evict down to the size cap.
+    fn insert(&self, dest: &Path, src: &Path) -> Result<()> {
+        let size = std::fs::metadata(src).with_context(|| format!("read {}", src.display()))?.len();
+        if size > self.max_bytes {
+            return Err(anyhow!("{} bytes is over the cache limit (cache.max_mb)", size));
+        }
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let tmp = dest.with_extension("tmp");
+        std::fs::copy(src, &tmp).with_context(|| format!("write {}", tmp.display()))?;
+        std::fs::rename(&tmp, dest).with_context(|| format!("write {}", dest.display()))?;
+        self.evict()
+    }
+
+    fn evict(&self) -> Result<()> {
+        let mut entries = Vec::new();
+        for dir in [BLOB_DIR, GCODE_DIR] {
+            let Ok(listing) = std::fs::read_dir(self.root.join(dir)) else {
+                continue;
+            };
+            for entry in listing.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                // In-flight copies belong to another insert.
+                if path.extension().is_some_and(|e| e == "tmp") {
+                    continue;
+                }
+                match entry.metadata() {
+                    Ok(meta) if meta.is_file() => {
+                        let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                        entries.push((path, meta.len(), used));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for path in lru_victims(entries, self.max_bytes) {
+            std::fs::remove_file(&path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Entries to drop, oldest first, until the rest fits in `max_bytes`.
+fn lru_victims(mut entries: Vec<(PathBuf, u64, SystemTime)>, max_bytes: u64) -> Vec<PathBuf> {
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    entries.sort_by_key(|(_, _, used)| *used);
+    let mut victims = Vec::new();
+    for (path, size, _) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        total -= size;
+        victims.push(path);
+    }
+    victims
+}
+
+/// Walrus blob ids are URL-safe base64; anything else must not become a path.
+fn entry_name(id: &str) -> Result<&str> {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        Ok(id)
+    } else {
+        Err(anyhow!("'{}' cannot be used as a cache key", id))
+    }
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher` its output is fixed, so cache keys survive upgrades.
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_lru_victims() {
+        let at = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let entries = vec![
+            (PathBuf::from("new"), 40, at(30)),
+            (PathBuf::from("old"), 50, at(10)),
+            (PathBuf::from("mid"), 30, at(20)),
+        ];
+        assert_eq!(lru_victims(entries.clone(), 120), Vec::<PathBuf>::new());
+        assert_eq!(lru_victims(entries.clone(), 70), vec![PathBuf::from("old")]);
+        assert_eq!(lru_victims(entries, 39), vec![PathBuf::from("old"), PathBuf::from("mid"), PathBuf::from("new")]);
+
+        assert!(entry_name("Xb3_q-9").is_ok());
+        assert!(entry_name("../etc").is_err());
+        let mut h = StableHasher::default();
+        h.write(b"a");
+        assert_eq!(h.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+}
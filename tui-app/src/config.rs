@@ -0,0 +1,268 @@
+//! Node configuration: `~/.config/eureka/config.yaml` (`dirs::config_dir()`), every key optional.
+//! Environment variables override the file, so one-off runs need no edits.
+//!
+//! ```yaml
+//! pipeline: native           # native (default) | scripts — Gcode-Process.sh   EUREKA_PIPELINE
+//! jobs:
+//!   dir: /srv/eureka/jobs    # per-job directories; default <XDG data dir>/eureka/jobs — EUREKA_JOBS_DIR
+//!   keep: 20                 # oldest job directories beyond this are deleted
+//! cache:
+//!   dir: /srv/eureka/cache   # Walrus blobs and sliced G-code; default <XDG cache dir>/eureka — EUREKA_CACHE_DIR
+//!   max_mb: 1024             # least recently used entries are evicted beyond this; 0 disables the cache
+//! walrus:
+//!   max_blob_mb: 200         # larger blobs are refused
+//!   attempts: 4              # per download, across aggregators, with exponential backoff
+//!   strategy: fastest        # failover (default: in listed order) | fastest — EUREKA_WALRUS_STRATEGY
+//!   aggregators:             # per network; defaults in constants::WALRUS_AGGREGATORS
+//!     testnet:
+//!       - https://aggregator.walrus-testnet.walrus.space
+//!       - https://walrus-agg-test.bucketprotocol.io
+//! seal:                      # per network; without key_servers any server the ciphertext names is used
+//!   testnet:
+//!     threshold: 2           # minimum ciphertext threshold accepted
+//!     timeout_secs: 20       # per decryption, all key servers together, and per probe (default 30)
+//!     key_servers:           # only ciphertext these can decrypt is accepted
+//!       - object_id: "0x<KeyServer object id>"
+//!         url: https://seal.example.com   # probed only; must match the on-chain KeyServer URL
+//! gas:                       # budgets come from simulating each transaction
+//!   margin_pct: 20           # added to the simulated cost
+//!   max_budget: 100000000    # MIST; a transaction estimated above this is not sent
+//!   max_budget_by_call:      # per Move function, instead of max_budget
+//!     withdraw_fees: 20000000
+//!   dust_threshold: 10000000 # MIST; smaller SUI coins are merged by the M key
+//! slicer:
+//!   backend: cura            # prusa (default) | orca | superslicer | cura   — EUREKA_SLICER
+//!   executable: /opt/cura/CuraEngine                                        # EUREKA_SLICER_BIN
+//!   profile: /etc/eureka/ender3.def.json                                    # EUREKA_SLICER_PROFILE
+//!   settings:                # key=value overrides: CuraEngine `-s`, Prusa/SuperSlicer `--key value`;
+//!     layer_height: "0.2"  # refused for orca, whose settings live in the preset files
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::app::printer::pipeline::PipelineMode;
+use crate::slicer::SlicerBackend;
+use crate::walrus::AggregatorStrategy;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EurekaConfig {
+    pub pipeline: PipelineMode,
+    pub jobs: JobsConfig,
+    pub cache: CacheConfig,
+    pub walrus: WalrusConfig,
+    pub seal: SealConfig,
+    pub gas: GasConfig,
+    pub slicer: SlicerConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    pub dir: Option<PathBuf>,
+    pub keep: usize,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self { dir: None, keep: 20 }
+    }
+}
+
+impl JobsConfig {
+    pub fn root(&self) -> Result<PathBuf> {
+        match &self.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => dirs::data_dir()
+                .map(|d| d.join("eureka").join("jobs"))
+                .ok_or_else(|| anyhow!("no data directory for job files; set jobs.dir or EUREKA_JOBS_DIR")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub dir: Option<PathBuf>,
+    pub max_mb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { dir: None, max_mb: 1024 }
+    }
+}
+
+impl CacheConfig {
+    pub fn root(&self) -> Option<PathBuf> {
+        self.dir
+            .clone()
+            .or_else(|| dirs::cache_dir().map(|d| d.join("eureka")))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WalrusConfig {
+    pub max_blob_mb: u64,
+    pub attempts: u32,
+    pub strategy: AggregatorStrategy,
+    /// Aggregator base URLs keyed by network name (`devnet`, `testnet`, `mainnet`).
+    pub aggregators: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for WalrusConfig {
+    fn default() -> Self {
+        Self {
+            max_blob_mb: 200,
+            attempts: 4,
+            strategy: AggregatorStrategy::default(),
+            aggregators: BTreeMap::new(),
+        }
+    }
+}
+
+/// Seal settings keyed by network name (`devnet`, `testnet`, `mainnet`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SealConfig {
+    #[serde(flatten)]
+    pub networks: BTreeMap<String, SealNetworkConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SealNetworkConfig {
+    pub key_servers: Vec<KeyServerConfig>,
+    /// Ciphertext with a lower threshold is refused; `None` accepts the ciphertext's own.
+    pub threshold: Option<u8>,
+    /// Bounds a whole decryption and each probe; the SDK takes no per-server timeout.
+    pub timeout_secs: u64,
+}
+
+impl Default for SealNetworkConfig {
+    fn default() -> Self {
+        Self {
+            key_servers: Vec::new(),
+            threshold: None,
+            timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct KeyServerConfig {
+    /// On-chain `KeyServer` object id, as named in the ciphertext.
+    pub object_id: String,
+    /// Only used for probes. The Seal SDK fetches key shares from the URL in the on-chain object,
+    /// so decryption is refused when the two differ.
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GasConfig {
+    pub margin_pct: u64,
+    /// In MIST.
+    pub max_budget: u64,
+    /// Limits for single `eureka::*` functions, keyed by function name; `merge_coins` for the dust
+    /// merge.
+    pub max_budget_by_call: BTreeMap<String, u64>,
+    /// SUI coins below this many MIST count as dust for merging.
+    pub dust_threshold: u64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            margin_pct: 20,
+            max_budget: 100_000_000,
+            max_budget_by_call: BTreeMap::new(),
+            dust_threshold: 10_000_000,
+        }
+    }
+}
+
+impl GasConfig {
+    pub fn max_budget_for(&self, function: &str) -> u64 {
+        self.max_budget_by_call
+            .get(function)
+            .copied()
+            .unwrap_or(self.max_budget)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SlicerConfig {
+    pub backend: SlicerBackend,
+    /// Binary to run; defaults to the backend's usual name on `PATH`.
+    pub executable: Option<PathBuf>,
+    /// Profile in the backend's own format; defaults to `Gcode-Transmit/main/Ender-3_set.ini`
+    /// for the Prusa-style backends.
+    pub profile: Option<PathBuf>,
+    /// Per-setting overrides. OrcaSlicer takes none on its command line, so it refuses them.
+    pub settings: BTreeMap<String, String>,
+}
+
+impl EurekaConfig {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("eureka").join("config.yaml"))
+    }
+
+    /// Defaults when the file does not exist; an unreadable or invalid file is an error.
+    pub fn load() -> Result<Self> {
+        let mut config = match Self::path() {
+            Some(path) if path.exists() => Self::from_file(&path)?,
+            _ => Self::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("read {}", path.display()))?;
+        serde_yaml::from_str(&raw).map_err(|e| anyhow!("parse {}: {}", path.display(), e))
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(pipeline) = env_value("EUREKA_PIPELINE") {
+            self.pipeline = pipeline
+                .parse()
+                .map_err(|e| anyhow!("EUREKA_PIPELINE: {}", e))?;
+        }
+        if let Some(dir) = env_value("EUREKA_JOBS_DIR") {
+            self.jobs.dir = Some(PathBuf::from(dir));
+        }
+        if let Some(dir) = env_value("EUREKA_CACHE_DIR") {
+            self.cache.dir = Some(PathBuf::from(dir));
+        }
+        if let Some(strategy) = env_value("EUREKA_WALRUS_STRATEGY") {
+            self.walrus.strategy = strategy
+                .parse()
+                .map_err(|e| anyhow!("EUREKA_WALRUS_STRATEGY: {}", e))?;
+        }
+        if let Some(backend) = env_value("EUREKA_SLICER") {
+            self.slicer.backend = backend
+                .parse()
+                .map_err(|e| anyhow!("EUREKA_SLICER: {}", e))?;
+        }
+        if let Some(bin) = env_value("EUREKA_SLICER_BIN") {
+            self.slicer.executable = Some(PathBuf::from(bin));
+        }
+        if let Some(profile) = env_value("EUREKA_SLICER_PROFILE") {
+            self.slicer.profile = Some(PathBuf::from(profile));
+        }
+        Ok(())
+    }
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
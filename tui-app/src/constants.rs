@@ -15,22 +15,34 @@ pub const EUREKA_TESTNET_MOVE_CALL_PACKAGE_ID: &str =
 pub const EUREKA_TESTNET_PRINTER_REGISTRY_ID: &str =
     "0x3498e9fef83b29ef471d3070daf7764f3f9abcc982daa34fdf7fda9b612e9409";
 pub const SCULPT_TESTNET_PACKAGE_ID: &str = "0x51d9c918431258ae6748b50234d0da3d436e6df8e2087fa1446913e390336ab8";
-pub const AGGREGATOR_URL: &str = "https://walrus-agg-test.bucketprotocol.io";
 
 // Global constants
-pub const GAS_BUDGET: u64 = 100_000_000;
-
 #[allow(dead_code)]
 pub const SUI_CLOCK_OBJECT_ID: &str = "0x6";
 
+/// Print job polling, used only while the checkpoint subscription is down; also how often a live
+/// subscription checks that the app is still online on the same printer.
 pub const PRINT_JOB_POLL_INTERVAL_SECS: u64 = 10;
+/// How long to poll after the checkpoint subscription fails before subscribing again.
+pub const CHECKPOINT_RESUBSCRIBE_SECS: u64 = 60;
 pub const RETRY_INTERVAL_SECS: u64 = 5;
 pub const GCODE_CHECK_INTERVAL_MILLIS: u64 = 500;
-/// Poll attempts while waiting for `Gcode-Transmit/main/test.gcode` to appear after PrusaSlicer starts.
+/// Poll attempts while waiting for the job's G-code to appear after the slicer starts.
 /// 1200 × 500ms ≈ 10 minutes (slicing on a Pi can exceed the old limit of ~20s).
 pub const GCODE_WAIT_ATTEMPTS: u32 = 1200;
 pub const PRINT_OUTPUT_MAX_LINES: usize = 1000;
+/// JSON-lines events from eureka-serial, written into the job directory (passed as `EUREKA_EVENT_FILE`).
+pub const SERIAL_EVENT_FILE: &str = "eureka-events.jsonl";
+pub const SERIAL_EVENT_POLL_INTERVAL_MILLIS: u64 = 250;
+/// Unix socket eureka-serial accepts pause / resume / cancel on (passed as `EUREKA_CONTROL_SOCKET`).
+pub const SERIAL_CONTROL_SOCKET: &str = "eureka-control.sock";
 pub const SCULPT_LOAD_DELAY_MILLIS: u64 = 100;
+/// Lifetime of a Seal session key; key servers refuse longer ones.
+pub const SEAL_SESSION_TTL_MINS: u16 = 30;
+/// A cached session key is renewed once less than this is left, so no decryption starts on one
+/// that could expire mid-request.
+pub const SEAL_SESSION_REFRESH_SECS: u64 = 5 * 60;
+pub const SEAL_SESSION_CHECK_INTERVAL_SECS: u64 = 60;
 
 pub const SUI_DECIMALS: f64 = 1_000_000_000.0;
 pub const MESSAGE_AREA_MARGIN: u16 = 4;
@@ -71,4 +83,17 @@ pub const NETWORKS: [(&str, &str); 3] = [
     ("devnet", sui_rpc::Client::DEVNET_FULLNODE),
     ("testnet", sui_rpc::Client::TESTNET_FULLNODE),
     ("mainnet", sui_rpc::Client::MAINNET_FULLNODE),
-];
\ No newline at end of file
+];
+/// Default Walrus aggregators per network, in `NETWORKS` order and in failover order; overridden by
+/// `walrus.aggregators` in config.yaml. Walrus has no devnet, so devnet reads testnet blobs.
+pub const WALRUS_AGGREGATORS: [&[&str]; 3] = [
+    &[
+        "https://walrus-agg-test.bucketprotocol.io",
+        "https://aggregator.walrus-testnet.walrus.space",
+    ],
+    &[
+        "https://walrus-agg-test.bucketprotocol.io",
+        "https://aggregator.walrus-testnet.walrus.space",
+    ],
+    &["https://aggregator.walrus-mainnet.walrus.space"],
+];
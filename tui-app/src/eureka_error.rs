@@ -0,0 +1,141 @@
+//! Move aborts of Eureka transactions, decoded from the execution status instead of matched in
+//! error text. The `eureka` codes mirror the constants in `contract/eureka/sources/eureka.move`;
+//! kiosk codes are those of `sui::kiosk`. Each variant says what went wrong and what to do next.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EurekaError {
+    /// `EPrintJobCompleted` (1)
+    PrintJobCompleted,
+    /// `EPrintJobExists` (2)
+    PrintJobExists,
+    /// `ENotAuthorized` (3)
+    NotAuthorized,
+    /// `EPrintJobNotStarted` (4)
+    PrintJobNotStarted,
+    /// `ENotPrinterOwner` (5)
+    NotPrinterOwner,
+    /// `EInvalidPrinterCap` (6)
+    InvalidPrinterCap,
+    /// `EPrintJobNotFound` (7), or the `print_job` dynamic field missing where the contract does
+    /// not check for it first.
+    PrintJobNotFound,
+    /// `EPrinterIdMismatch` (8)
+    PrinterIdMismatch,
+    /// `kiosk::ENotOwner` (0): the KioskOwnerCap is not for this kiosk.
+    KioskNotOwner,
+    /// `kiosk::EListedExclusively` (4) / `kiosk::EItemIsListed` (9)
+    KioskItemListed,
+    /// `kiosk::EItemNotFound` (11)
+    KioskItemNotFound,
+    /// Any abort in `archimeters::sculpt`; its codes are not mirrored here.
+    Sculpt { function: String, code: u64 },
+    /// Aborts of other modules, and `eureka` codes this build does not know.
+    Other { module: String, function: String, code: u64 },
+}
+
+impl EurekaError {
+    /// Decode an abort with `code` in `module::function`.
+    pub fn from_abort(module: &str, function: &str, code: u64) -> Self {
+        match (module, code) {
+            ("eureka", 1) => Self::PrintJobCompleted,
+            ("eureka", 2) => Self::PrintJobExists,
+            ("eureka", 3) => Self::NotAuthorized,
+            ("eureka", 4) => Self::PrintJobNotStarted,
+            ("eureka", 5) => Self::NotPrinterOwner,
+            ("eureka", 6) => Self::InvalidPrinterCap,
+            ("eureka", 7) => Self::PrintJobNotFound,
+            ("eureka", 8) => Self::PrinterIdMismatch,
+            // EFieldDoesNotExist; the printer's only dynamic object field is its print job.
+            ("dynamic_field", 1) => Self::PrintJobNotFound,
+            ("kiosk", 0) => Self::KioskNotOwner,
+            ("kiosk", 4) | ("kiosk", 9) => Self::KioskItemListed,
+            ("kiosk", 11) => Self::KioskItemNotFound,
+            ("sculpt", code) => Self::Sculpt {
+                function: function.to_string(),
+                code,
+            },
+            (module, code) => Self::Other {
+                module: module.to_string(),
+                function: function.to_string(),
+                code,
+            },
+        }
+    }
+
+    /// What to do about it, phrased for the status line.
+    pub fn recovery(&self) -> &'static str {
+        match self {
+            Self::PrintJobCompleted => "Wait for the completed job to be transferred, or clear it with J if it is stuck.",
+            Self::PrintJobExists => "Finish the current job, or clear a stuck one with J.",
+            Self::NotAuthorized => "The job may have been replaced; wait for the task list to refresh, or clear it with J if it is stuck.",
+            Self::PrintJobNotStarted => "Start the print job (P) before completing it.",
+            Self::NotPrinterOwner => "Switch the Sui client to the address that registered this printer.",
+            Self::InvalidPrinterCap => "Use the wallet that holds this printer's PrinterCap, or register the printer again.",
+            Self::PrintJobNotFound => "Nothing to do: the job was already transferred or cleared. Wait for the task list to refresh.",
+            Self::PrinterIdMismatch => "Clear the stuck job with J.",
+            Self::KioskNotOwner => "Use the wallet that owns the Sculpt's kiosk.",
+            Self::KioskItemListed => "Delist the Sculpt from sale in its kiosk, then print again.",
+            Self::KioskItemNotFound => "The Sculpt was moved or sold; pick another one.",
+            Self::Sculpt { .. } => "Check the Sculpt's printer whitelist and ownership on Archimeters.",
+            Self::Other { .. } => "Retry; if it keeps failing, report the abort above.",
+        }
+    }
+}
+
+impl fmt::Display for EurekaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrintJobCompleted => write!(f, "Print job has already been completed."),
+            Self::PrintJobExists => write!(f, "A print job already exists for this printer."),
+            Self::NotAuthorized => write!(f, "The PrinterCap or Sculpt does not match this printer's print job."),
+            Self::PrintJobNotStarted => write!(f, "Print job has not been started yet."),
+            Self::NotPrinterOwner => write!(f, "The active address is not the printer's owner."),
+            Self::InvalidPrinterCap => write!(f, "The PrinterCap belongs to a different printer."),
+            Self::PrintJobNotFound => write!(f, "The printer has no print job."),
+            Self::PrinterIdMismatch => write!(f, "The print job belongs to a different printer."),
+            Self::KioskNotOwner => write!(f, "The KioskOwnerCap does not match the Sculpt's kiosk."),
+            Self::KioskItemListed => write!(f, "The Sculpt is listed for sale in its kiosk."),
+            Self::KioskItemNotFound => write!(f, "The Sculpt is not in the kiosk."),
+            Self::Sculpt { function, code } => write!(f, "Archimeters sculpt::{} aborted with code {}.", function, code),
+            Self::Other { module, function, code } => write!(f, "{}::{} aborted with code {}.", module, function, code),
+        }
+    }
+}
+
+impl std::error::Error for EurekaError {}
+
+/// `error` for the status line, followed by the recovery step when it is a decoded abort.
+pub fn describe(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<EurekaError>() {
+        Some(abort) => format!("{} {}", abort, abort.recovery()),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_abort() {
+        assert_eq!(
+            EurekaError::from_abort("eureka", "create_and_assign_print_job_internal", 2),
+            EurekaError::PrintJobExists
+        );
+        assert_eq!(EurekaError::from_abort("eureka", "seal_approve", 8), EurekaError::PrinterIdMismatch);
+        assert_eq!(EurekaError::from_abort("kiosk", "borrow_mut", 9), EurekaError::KioskItemListed);
+        assert_eq!(
+            EurekaError::from_abort("sculpt", "add_printer_to_whitelist", 3),
+            EurekaError::Sculpt {
+                function: "add_printer_to_whitelist".to_string(),
+                code: 3
+            }
+        );
+        assert_eq!(
+            EurekaError::from_abort("eureka", "start_print_job", 42).to_string(),
+            "eureka::start_print_job aborted with code 42."
+        );
+    }
+}
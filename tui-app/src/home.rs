@@ -0,0 +1,277 @@
+//! Runtime home: the directory holding `Gcode-Transmit/` (print scripts, slicer profile) and the
+//! control socket.
+//!
+//! Resolved once at startup, first match wins: `--home <dir>`, `EUREKA_HOME`, the XDG data
+//! directory (`~/.local/share/eureka`) when it already holds `Gcode-Transmit/`, the install prefix
+//! (`<exe>/../share/eureka`) when a package put it there, then the XDG data directory. Missing
+//! assets are installed from copies built into the binary, so a packaged release needs no source
+//! checkout. A developer can point `--home` at `tui-app/` to run the scripts from the tree.
+
+use anyhow::{anyhow, Context, Result};
+use std::ffi::OsString;
+use std::fmt;
+use std::hash::Hasher;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::cache::StableHasher;
+
+static HOME: OnceLock<PathBuf> = OnceLock::new();
+
+/// Hash of the bundled scripts that were installed last; they are refreshed when it changes, so a
+/// dev build with an edited script installs it without a version bump.
+const ASSETS_HASH_FILE: &str = ".assets-hash";
+
+struct Asset {
+    path: &'static str,
+    contents: &'static str,
+    executable: bool,
+    /// Meant to be edited by the operator: installed when missing, never replaced.
+    user_owned: bool,
+}
+
+const ASSETS: &[Asset] = &[
+    Asset {
+        path: "Gcode-Transmit/Gcode-Process.sh",
+        contents: include_str!("../Gcode-Transmit/Gcode-Process.sh"),
+        executable: true,
+        user_owned: false,
+    },
+    Asset {
+        path: "Gcode-Transmit/common-device.sh",
+        contents: include_str!("../Gcode-Transmit/common-device.sh"),
+        executable: false,
+        user_owned: false,
+    },
+    Asset {
+        path: "Gcode-Transmit/main/Gcode-Send.sh",
+        contents: include_str!("../Gcode-Transmit/main/Gcode-Send.sh"),
+        executable: true,
+        user_owned: false,
+    },
+    Asset {
+        path: "Gcode-Transmit/main/Gcode-Stop.sh",
+        contents: include_str!("../Gcode-Transmit/main/Gcode-Stop.sh"),
+        executable: true,
+        user_owned: false,
+    },
+    Asset {
+        path: "Gcode-Transmit/main/Ender-3_set.ini",
+        contents: include_str!("../Gcode-Transmit/main/Ender-3_set.ini"),
+        executable: false,
+        user_owned: true,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HomeSource {
+    Flag,
+    Env,
+    DataDir,
+    InstallPrefix,
+}
+
+impl fmt::Display for HomeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HomeSource::Flag => "--home",
+            HomeSource::Env => "EUREKA_HOME",
+            HomeSource::DataDir => "data directory",
+            HomeSource::InstallPrefix => "install prefix",
+        })
+    }
+}
+
+/// What `init` settled on; `installed` lists the assets written on this start.
+pub struct HomeReport {
+    pub path: PathBuf,
+    pub source: HomeSource,
+    pub installed: Vec<&'static str>,
+}
+
+/// Resolve the home, install missing assets and fix it for the rest of the process.
+pub fn init(flag: Option<PathBuf>) -> Result<HomeReport> {
+    let (path, source) = resolve(flag)?;
+    let installed = install_assets(&path, ASSETS).with_context(|| format!("install runtime files into {}", path.display()))?;
+    HOME.set(path.clone())
+        .map_err(|_| anyhow!("runtime home initialised twice"))?;
+    Ok(HomeReport {
+        path,
+        source,
+        installed,
+    })
+}
+
+/// The runtime home. Before `init` (unit tests, helpers) it is resolved without the CLI flag and
+/// nothing is installed.
+pub fn eureka_home() -> &'static Path {
+    HOME.get_or_init(|| {
+        resolve(None)
+            .map(|(path, _)| path)
+            .unwrap_or_else(|_| PathBuf::from("."))
+    })
+}
+
+fn resolve(flag: Option<PathBuf>) -> Result<(PathBuf, HomeSource)> {
+    let prefix = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.parent()?.join("share").join("eureka")));
+    resolve_with(
+        flag,
+        std::env::var_os("EUREKA_HOME"),
+        dirs::data_dir().map(|dir| dir.join("eureka")),
+        prefix,
+    )
+}
+
+/// [`resolve`] with `EUREKA_HOME`, the XDG candidate and the install-prefix candidate passed in.
+fn resolve_with(
+    flag: Option<PathBuf>,
+    env: Option<OsString>,
+    data_dir: Option<PathBuf>,
+    prefix: Option<PathBuf>,
+) -> Result<(PathBuf, HomeSource)> {
+    if let Some(dir) = flag {
+        return Ok((absolute(dir)?, HomeSource::Flag));
+    }
+    if let Some(dir) = env.filter(|v| !v.is_empty()) {
+        return Ok((absolute(PathBuf::from(dir))?, HomeSource::Env));
+    }
+    let holds_assets = |dir: &PathBuf| dir.join("Gcode-Transmit").is_dir();
+    match (data_dir, prefix) {
+        (Some(dir), _) if holds_assets(&dir) => Ok((dir, HomeSource::DataDir)),
+        (_, Some(dir)) if holds_assets(&dir) => Ok((dir, HomeSource::InstallPrefix)),
+        (Some(dir), _) => Ok((dir, HomeSource::DataDir)),
+        (None, Some(dir)) => Ok((dir, HomeSource::InstallPrefix)),
+        (None, None) => Err(anyhow!("no data directory and no install prefix for the runtime home")),
+    }
+}
+
+fn absolute(dir: PathBuf) -> Result<PathBuf> {
+    if dir.is_absolute() {
+        Ok(dir)
+    } else {
+        Ok(std::env::current_dir()?.join(dir))
+    }
+}
+
+/// Hash of the app-owned `assets`, paths and contents.
+fn assets_hash(assets: &[Asset]) -> String {
+    let mut h = StableHasher::default();
+    for asset in assets.iter().filter(|a| !a.user_owned) {
+        h.write(asset.path.as_bytes());
+        h.write(&[0]);
+        h.write(asset.contents.as_bytes());
+        h.write(&[0]);
+    }
+    format!("{:016x}", h.finish())
+}
+
+/// Write every asset that is missing, and refresh the app-owned ones when the bundled copies
+/// differ from those installed last.
+fn install_assets(home: &Path, assets: &[Asset]) -> Result<Vec<&'static str>> {
+    let hash_file = home.join(ASSETS_HASH_FILE);
+    let current = assets_hash(assets);
+    let outdated = std::fs::read_to_string(&hash_file)
+        .map(|v| v.trim() != current)
+        .unwrap_or(true);
+
+    let mut installed = Vec::new();
+    for asset in assets {
+        let target = home.join(asset.path);
+        let exists = target.exists();
+        if exists && (asset.user_owned || !outdated) {
+            continue;
+        }
+        if let Some(dir) = target.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // A tree checkout already has the exact file; leave it (and its git status) alone.
+        if exists && std::fs::read_to_string(&target).is_ok_and(|c| c == asset.contents) {
+            continue;
+        }
+        std::fs::write(&target, asset.contents).with_context(|| format!("write {}", target.display()))?;
+        if asset.executable {
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))?;
+        }
+        installed.push(asset.path);
+    }
+    if outdated {
+        std::fs::write(&hash_file, format!("{}\n", current))?;
+    }
+    Ok(installed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(path: &'static str, contents: &'static str, user_owned: bool) -> Asset {
+        Asset {
+            path,
+            contents,
+            executable: !user_owned,
+            user_owned,
+        }
+    }
+
+    #[test]
+    fn test_install_assets() {
+        let home = std::env::temp_dir().join(format!("eureka-home-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&home);
+        let v1 = [asset("bin/run.sh", "echo 1\n", false), asset("profile.ini", "a=1\n", true)];
+
+        assert_eq!(install_assets(&home, &v1).unwrap(), vec!["bin/run.sh", "profile.ini"]);
+        let mode = std::fs::metadata(home.join("bin/run.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(install_assets(&home, &v1).unwrap().is_empty());
+
+        // A build with a changed script, e.g. a dev build at the same version, replaces it; the
+        // operator's edits to user-owned files stay.
+        std::fs::write(home.join("profile.ini"), "a=2\n").unwrap();
+        let v2 = [asset("bin/run.sh", "echo 2\n", false), asset("profile.ini", "a=1\n", true)];
+        assert_eq!(install_assets(&home, &v2).unwrap(), vec!["bin/run.sh"]);
+        assert_eq!(std::fs::read_to_string(home.join("bin/run.sh")).unwrap(), "echo 2\n");
+        assert_eq!(std::fs::read_to_string(home.join("profile.ini")).unwrap(), "a=2\n");
+
+        // Missing files are installed again even when nothing changed.
+        std::fs::remove_file(home.join("profile.ini")).unwrap();
+        assert_eq!(install_assets(&home, &v2).unwrap(), vec!["profile.ini"]);
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let root = std::env::temp_dir().join(format!("eureka-resolve-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (data, prefix) = (root.join("data"), root.join("share/eureka"));
+        let resolve = |flag: Option<&str>, env: Option<&str>| {
+            resolve_with(
+                flag.map(PathBuf::from),
+                env.map(OsString::from),
+                Some(data.clone()),
+                Some(prefix.clone()),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(resolve(Some("/opt/eureka"), Some("/srv/eureka")), (PathBuf::from("/opt/eureka"), HomeSource::Flag));
+        assert_eq!(resolve(None, Some("/srv/eureka")), (PathBuf::from("/srv/eureka"), HomeSource::Env));
+        assert_eq!(resolve(Some("tui-app"), None).0, std::env::current_dir().unwrap().join("tui-app"));
+
+        // Nothing installed anywhere: a fresh install goes to the writable data directory.
+        assert_eq!(resolve(None, Some("")), (data.clone(), HomeSource::DataDir));
+        // A packaged prefix is used as long as the data directory holds nothing...
+        std::fs::create_dir_all(prefix.join("Gcode-Transmit")).unwrap();
+        assert_eq!(resolve(None, None), (prefix.clone(), HomeSource::InstallPrefix));
+        // ...and a data directory set up earlier still wins over it.
+        std::fs::create_dir_all(data.join("Gcode-Transmit")).unwrap();
+        assert_eq!(resolve(None, None), (data.clone(), HomeSource::DataDir));
+        assert_eq!(
+            resolve_with(None, None, None, Some(root.clone())).unwrap(),
+            (root.clone(), HomeSource::InstallPrefix)
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -8,26 +8,79 @@ use ratatui::{
     backend::CrosstermBackend,
     Terminal,
 };
+use std::path::PathBuf;
 use std::{io, time::Duration};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time;
 
 mod app;
+mod cache;
+mod config;
 mod constants;
+mod eureka_error;
+mod home;
 mod utils;
 mod wallet;
 mod model;
+mod private_file;
 
-use constants::{PRINT_JOB_POLL_INTERVAL_SECS, RETRY_INTERVAL_SECS, SCULPT_LOAD_DELAY_MILLIS};
+use constants::{
+    CHECKPOINT_RESUBSCRIBE_SECS, PRINT_JOB_POLL_INTERVAL_SECS, RETRY_INTERVAL_SECS, SCULPT_LOAD_DELAY_MILLIS,
+    SEAL_SESSION_CHECK_INTERVAL_SECS,
+};
 mod ui;
 mod transactions;
 mod seal;
+mod slicer;
+mod walrus;
+mod workspace;
 
 use app::{App, MessageType, PrintStatus, ScriptStatus, TaskStatus};
+use wallet::{PrinterEvent, PrinterEvents};
+
+const USAGE: &str = "Usage: eureka [--home <dir>]
+
+  --home <dir>   Runtime files (Gcode-Transmit scripts, slicer profile); overrides EUREKA_HOME";
+
+/// `--home <dir>` / `--home=<dir>`; `None` when not given.
+fn parse_args() -> Result<Option<PathBuf>> {
+    let mut home = None;
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        let arg = arg.to_string_lossy().into_owned();
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        } else if arg == "--home" {
+            let dir = args.next().ok_or_else(|| anyhow::anyhow!("--home needs a directory\n\n{}", USAGE))?;
+            home = Some(PathBuf::from(dir));
+        } else if let Some(dir) = arg.strip_prefix("--home=") {
+            home = Some(PathBuf::from(dir));
+        } else {
+            anyhow::bail!("unknown argument '{}'\n\n{}", arg, USAGE);
+        }
+    }
+    Ok(home)
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let home = home::init(parse_args()?)?;
+    if !home.installed.is_empty() {
+        println!(
+            "Installed {} runtime file(s) into {} ({})",
+            home.installed.len(),
+            home.path.display(),
+            home.source
+        );
+    }
+    match private_file::remove_leftovers() {
+        Ok(0) => {}
+        Ok(n) => println!("Wiped {} decrypted file(s) left by a previous run", n),
+        Err(e) => println!("Warning: private runtime directory unavailable: {}", e),
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -72,7 +125,8 @@ async fn run_app<B: ratatui::backend::Backend>(
     let mut printer_id_acquired = false;
     let mut sculpt_loading_started = false;
     
-    start_print_job_polling(Arc::clone(&app));
+    start_print_job_watch(Arc::clone(&app));
+    start_seal_session_refresh(Arc::clone(&app));
     
     loop {
         let app_arc = Arc::clone(&app);
@@ -187,19 +241,38 @@ async fn run_app<B: ratatui::backend::Backend>(
                             return Ok(());
                         },
                         KeyCode::Char('y') => {
-                            if app_guard.is_confirming {
+                            // Same precedence as the help line: the e-stop prompt answers first.
+                            if app_guard.is_confirming_estop {
+                                drop(app_guard);
+                                tokio::spawn(App::emergency_stop(Arc::clone(&app_arc)));
+                            } else if app_guard.is_cancelling_print {
+                                drop(app_guard);
+                                tokio::spawn(App::cancel_print(Arc::clone(&app_arc)));
+                            } else if app_guard.is_confirming {
                                 app_guard.confirm_toggle_immediate();
                                 drop(app_guard);
                                 start_toggle_task(Arc::clone(&app_arc));
                             } else if app_guard.is_harvesting {
                                 app_guard.confirm_harvest();
+                                drop(app_guard);
+                                tokio::spawn(app::printer::blockchain::run_withdraw_fees(Arc::clone(&app_arc)));
+                            } else if app_guard.is_confirming_merge {
+                                app_guard.confirm_merge();
+                                drop(app_guard);
+                                tokio::spawn(app::printer::blockchain::run_merge_dust_coins(Arc::clone(&app_arc)));
                             }
                         }
                         KeyCode::Char('n') => {
-                            if app_guard.is_confirming {
+                            if app_guard.is_confirming_estop {
+                                app_guard.abort_estop();
+                            } else if app_guard.is_cancelling_print {
+                                app_guard.abort_cancel_print();
+                            } else if app_guard.is_confirming {
                                 app_guard.cancel_toggle();
                             } else if app_guard.is_harvesting {
                                 app_guard.cancel_harvest();
+                            } else if app_guard.is_confirming_merge {
+                                app_guard.cancel_merge();
                             } else if app_guard.is_switching_network {
                                 app_guard.cancel_network_switch();
                             } else {
@@ -207,17 +280,38 @@ async fn run_app<B: ratatui::backend::Backend>(
                             }
                         }
                         KeyCode::Char('o') => {
-                            if !app_guard.is_confirming && !app_guard.is_harvesting && !app_guard.is_switching_network {
+                            if !app_guard.prompt_open() {
                                 if app_guard.is_online && app_guard.tasks.iter().any(|task| matches!(task.status, TaskStatus::Active)) {
                                     app_guard.set_message(MessageType::Error, "Cannot switch mode while a print job is in progress. Please complete the current job first.".to_string());
                                 } else {
                                     app_guard.start_toggle_confirm();
+                                    tokio::spawn(app::printer::blockchain::run_estimate_gas(
+                                        Arc::clone(&app_arc),
+                                        app::printer::blockchain::ConfirmedCall::UpdatePrinterStatus,
+                                    ));
                                 }
                             }
                         }
                         KeyCode::Char('h') => {
-                            if !app_guard.is_confirming && !app_guard.is_harvesting && !app_guard.is_switching_network {
+                            if app_guard.is_withdrawing_fees {
+                                app_guard.set_message(MessageType::Info, "Fee withdrawal already in progress".to_string());
+                            } else if !app_guard.prompt_open() {
                                 app_guard.start_harvest_confirm();
+                                tokio::spawn(app::printer::blockchain::run_estimate_gas(
+                                    Arc::clone(&app_arc),
+                                    app::printer::blockchain::ConfirmedCall::WithdrawFees,
+                                ));
+                            }
+                        }
+                        KeyCode::Char('m') => {
+                            if app_guard.is_merging_coins {
+                                app_guard.set_message(MessageType::Info, "Coin merge already in progress".to_string());
+                            } else if !app_guard.prompt_open() && !app_guard.show_diagnostics {
+                                app_guard.start_merge_confirm();
+                                tokio::spawn(app::printer::blockchain::run_estimate_gas(
+                                    Arc::clone(&app_arc),
+                                    app::printer::blockchain::ConfirmedCall::MergeCoins,
+                                ));
                             }
                         }
                         KeyCode::Char('p') => {
@@ -252,6 +346,33 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 App::handle_mock_print_with_printjob(Arc::clone(&app_arc)).await?;
                             }
                         }
+                        KeyCode::Char('z') => {
+                            if !app_guard.is_confirming && !app_guard.is_harvesting && !app_guard.is_switching_network {
+                                drop(app_guard);
+                                tokio::spawn(App::pause_print(Arc::clone(&app_arc)));
+                            }
+                        }
+                        KeyCode::Char('r') => {
+                            if !app_guard.is_confirming && !app_guard.is_harvesting && !app_guard.is_switching_network {
+                                drop(app_guard);
+                                tokio::spawn(App::resume_print(Arc::clone(&app_arc)));
+                            }
+                        }
+                        KeyCode::Char('e') => {
+                            if !app_guard.prompt_open() {
+                                app_guard.start_cancel_print_confirm();
+                            }
+                        }
+                        KeyCode::Char('d') | KeyCode::Char('D') => {
+                            app_guard.show_diagnostics = !app_guard.show_diagnostics;
+                            if app_guard.show_diagnostics {
+                                tokio::spawn(App::check_key_servers(Arc::clone(&app_arc)));
+                            }
+                        }
+                        KeyCode::Char('x') | KeyCode::Char('X') => {
+                            // Always reachable; it closes any other prompt.
+                            app_guard.start_estop_confirm();
+                        }
                         KeyCode::Char('1') | KeyCode::Char('2') | KeyCode::Char('3') => {
                             if app_guard.is_switching_network {
                                 let network_index = match key.code {
@@ -333,62 +454,153 @@ fn start_toggle_task(app: Arc<Mutex<App>>) {
     });
 }
 
-fn start_print_job_polling(app: Arc<Mutex<App>>) {
+/// Renew the cached Seal session key ahead of expiry, so an encrypted job never waits for a signature.
+fn start_seal_session_refresh(app: Arc<Mutex<App>>) {
     tokio::spawn(async move {
-        let poll_interval = time::Duration::from_secs(PRINT_JOB_POLL_INTERVAL_SECS);
-        let mut interval = time::interval(poll_interval);
-
+        let mut interval = time::interval(Duration::from_secs(SEAL_SESSION_CHECK_INTERVAL_SECS));
         loop {
             interval.tick().await;
+            let seal = Arc::clone(&app.lock().await.seal);
+            if let Err(e) = seal.refresh().await {
+                app.lock().await.print_output.push(format!("[SEAL] Session key refresh failed: {}", e));
+            }
+        }
+    });
+}
 
-            let (should_poll, printer_id, wallet) = {
-                let g = app.lock().await;
-                let has_active = g.tasks.iter().any(|task| !task.is_completed());
-                let poll = g.is_online && g.printer_id != "No Printer ID" && !has_active;
-                (
-                    poll,
-                    g.printer_id.clone(),
-                    g.wallet.clone(),
-                )
+/// Follow this printer's print jobs through the checkpoint stream: re-read the job whenever an event
+/// names the printer. While the stream is down, poll every `PRINT_JOB_POLL_INTERVAL_SECS` and
+/// subscribe again after `CHECKPOINT_RESUBSCRIBE_SECS`.
+fn start_print_job_watch(app: Arc<Mutex<App>>) {
+    tokio::spawn(async move {
+        let poll_interval = Duration::from_secs(PRINT_JOB_POLL_INTERVAL_SECS);
+        loop {
+            let Some(target) = watch_target(&app).await else {
+                time::sleep(poll_interval).await;
+                continue;
             };
+            // Catch up on whatever happened before this subscription.
+            refresh_print_job(&app).await;
+            let followed = match PrinterEvents::subscribe(&target.0, &target.1).await {
+                Ok(events) => follow_printer_events(&app, events, &target).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = followed {
+                app.lock().await.print_output.push(format!(
+                    "[LOG] Checkpoint subscription down ({}); polling every {} s",
+                    e, PRINT_JOB_POLL_INTERVAL_SECS
+                ));
+                let resubscribe_at = time::Instant::now() + Duration::from_secs(CHECKPOINT_RESUBSCRIBE_SECS);
+                while time::Instant::now() < resubscribe_at {
+                    time::sleep(poll_interval).await;
+                    refresh_print_job(&app).await;
+                }
+            }
+        }
+    });
+}
 
-            if !should_poll {
+/// RPC URL and printer id to watch: only while online with a registered printer.
+async fn watch_target(app: &Arc<Mutex<App>>) -> Option<(String, String)> {
+    let g = app.lock().await;
+    if !g.is_online || g.printer_id == "No Printer ID" {
+        return None;
+    }
+    Some((g.network_state.get_current_rpc().to_string(), g.printer_id.clone()))
+}
+
+/// Handle events until the stream fails (`Err`) or `target` stops being what to watch (`Ok`).
+async fn follow_printer_events(
+    app: &Arc<Mutex<App>>,
+    mut events: PrinterEvents,
+    target: &(String, String),
+) -> Result<()> {
+    loop {
+        let batch = match time::timeout(Duration::from_secs(PRINT_JOB_POLL_INTERVAL_SECS), events.next()).await {
+            Ok(batch) => batch?,
+            Err(_) => {
+                if watch_target(app).await.as_ref() != Some(target) {
+                    return Ok(());
+                }
                 continue;
             }
-
-            let fetch = wallet.get_active_print_job(&printer_id);
-            match fetch.await {
-                Ok(Some(task)) => {
+        };
+        let mut job_changed = false;
+        for event in batch {
+            match event {
+                PrinterEvent::PrintJobCreated | PrinterEvent::PrintJobCleared => job_changed = true,
+                PrinterEvent::StatusUpdated { online } => {
                     let mut g = app.lock().await;
-                    let task_exists = g.tasks.iter().any(|t| t.id == task.id);
-                    if !task_exists {
-                        g.tasks.insert(0, task.clone());
-                        g.tasks_state.select(Some(0));
-                        g.print_status = PrintStatus::Idle;
-                        g.script_status = ScriptStatus::Idle;
-                        g.set_message(MessageType::Success, format!("Found print task: {}", task.name));
-                    } else if let Some(existing) = g.tasks.iter_mut().find(|t| t.id == task.id) {
-                        *existing = task.clone();
-                        if matches!(g.script_status, ScriptStatus::Running) {
-                            g.print_status = PrintStatus::Printing;
-                        }
+                    // Our own toggle emits this too; only a change made elsewhere differs from local state.
+                    if online != g.is_online && !g.is_toggling_mode {
+                        g.set_message(
+                            MessageType::Info,
+                            format!(
+                                "Printer was set {} on chain by another client; press O to match",
+                                if online { "ONLINE" } else { "OFFLINE" }
+                            ),
+                        );
                     }
                 }
-                Ok(None) => {
-                    let mut g = app.lock().await;
-                    g.print_status = PrintStatus::Idle;
-                    g.script_status = ScriptStatus::Idle;
-                }
-                Err(e) => {
-                    println!("Failed to update print tasks: {:?}", e);
-                    let mut g = app.lock().await;
-                    g.set_message(
-                        MessageType::Error,
-                        format!("Failed to get print task: {}", e),
-                    );
+            }
+        }
+        if job_changed {
+            refresh_print_job(app).await;
+        }
+    }
+}
+
+/// Re-read the printer's print job into the task list, unless a job is already in progress here.
+async fn refresh_print_job(app: &Arc<Mutex<App>>) {
+    let (should_poll, printer_id, wallet) = {
+        let g = app.lock().await;
+        let has_active = g.tasks.iter().any(|task| !task.is_completed());
+        let poll = g.is_online && g.printer_id != "No Printer ID" && !has_active;
+        (
+            poll,
+            g.printer_id.clone(),
+            g.wallet.clone(),
+        )
+    };
+
+    if !should_poll {
+        return;
+    }
+
+    let fetch = wallet.get_active_print_job(&printer_id);
+    match fetch.await {
+        Ok(Some(task)) => {
+            let mut g = app.lock().await;
+            let task_exists = g.tasks.iter().any(|t| t.id == task.id);
+            if !task_exists {
+                g.tasks.insert(0, task.clone());
+                g.tasks_state.select(Some(0));
+                g.print_status = PrintStatus::Idle;
+                g.script_status = ScriptStatus::Idle;
+                g.set_message(MessageType::Success, format!("Found print task: {}", task.name));
+            } else if let Some(existing) = g.tasks.iter_mut().find(|t| t.id == task.id) {
+                *existing = task.clone();
+                if matches!(g.script_status, ScriptStatus::Running) {
+                    g.print_status = PrintStatus::Printing;
                 }
             }
         }
-    });
+        Ok(None) => {
+            let mut g = app.lock().await;
+            // A firmware error stays on screen until the next print starts.
+            if !matches!(g.print_status, PrintStatus::Error(_)) {
+                g.print_status = PrintStatus::Idle;
+            }
+            g.script_status = ScriptStatus::Idle;
+        }
+        Err(e) => {
+            println!("Failed to update print tasks: {:?}", e);
+            let mut g = app.lock().await;
+            g.set_message(
+                MessageType::Error,
+                format!("Failed to get print task: {}", e),
+            );
+        }
+    }
 }
 
@@ -1,50 +1,62 @@
 use crate::app::core::App;
+use crate::app::printer::pipeline::ModelFile;
+use crate::cache::BlobCache;
 use crate::app::print_job::PrintTask;
-use crate::constants::AGGREGATOR_URL;
-use crate::utils::crate_root;
-use crate::seal::{is_file_encrypted, PrintJobDecryptor};
+use crate::private_file::PrivateFile;
+use crate::config::SealNetworkConfig;
+use crate::seal::{identify, ModelBlob, SealService};
 use crate::app::printer::mock::{run_mock_print_script, MockPrintScriptResult};
 use anyhow::Result;
 use seal_sdk_rs::native_sui_sdk::sui_types::base_types::ObjectID as SuiObjectID;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use crate::walrus::{format_bytes, DownloadProgress};
+use crate::workspace::{JobState, JobWorkspace};
 use std::fs;
 use std::path::Path;
 
 /// Download plus optional Seal decrypt (only via `eureka::seal_approve` + PrintJob, matching on-chain rules).
+/// A plain model lands in `job`'s model file; a decrypted one only in the private runtime directory
+/// (see `crate::private_file`). Byte progress goes to `App::download_progress`. The blob comes from
+/// the local cache when present, and is cached as downloaded (ciphertext for Seal models).
 async fn download_model_isolated(
+    app: &Arc<Mutex<App>>,
+    job: &JobWorkspace,
     blob_id: &str,
     seal_resource_id: Option<&str>,
     current_rpc: &str,
     eureka_package_id: &str,
     // Required for encrypted models: (printer_object_id, printer_cap_object_id)
     printer_for_seal: Option<(String, String)>,
-) -> Result<Vec<String>> {
+) -> Result<(Vec<String>, ModelFile)> {
     let mut log = Vec::new();
-    let url = format!("{}/v1/blobs/{}", AGGREGATOR_URL, blob_id);
-    let root = crate_root();
-    let temp_path = root.join("test.stl");
-    let final_path = root.join("Gcode-Transmit").join("test.stl");
-
-    log.push(format!("[LOG] Downloading model from: {}", url));
-
-    let gcode_dir = root.join("Gcode-Transmit");
-    if !Path::new(&gcode_dir).exists() {
-        log.push(format!("[LOG] Creating directory: {}", gcode_dir.display()));
-        fs::create_dir_all(&gcode_dir)?;
-    }
-
-    let status = tokio::process::Command::new("curl")
-        .arg("-s")
-        .arg("-S")
-        .arg(&url)
-        .arg("-o")
-        .arg(&temp_path)
-        .status()
-        .await?;
-
-    if !status.success() {
-        return Err(anyhow::anyhow!("Failed to download 3D model"));
+    let (walrus, cache, seal, key_servers) = {
+        let g = app.lock().await;
+        (g.walrus_client()?, BlobCache::open(&g.config.cache), Arc::clone(&g.seal), g.seal_key_servers())
+    };
+    // Complete (and decrypted) before it appears under the final name.
+    let temp_path = job.model_path().with_extension("stl.part");
+    let final_path = job.model_path();
+
+    let cached = match cache.as_ref().and_then(|c| c.blob(blob_id)) {
+        Some(path) => tokio::fs::copy(&path, &temp_path).await.ok(),
+        None => None,
+    };
+    if let Some(size) = cached {
+        log.push(format!("[CACHE] Blob {} ({}) from the local cache", blob_id, format_bytes(size)));
+    } else {
+        log.push(format!("[LOG] Downloading blob {} from Walrus", blob_id));
+        let downloaded = walrus
+            .download_to_file(blob_id, &temp_path, &mut progress_reporter(app))
+            .await;
+        app.lock().await.download_progress = None;
+        let fetched = downloaded.map_err(|e| anyhow::anyhow!("Failed to download 3D model: {}", e))?;
+        log.push(format!("[LOG] Downloaded {} from {}", format_bytes(fetched.bytes), fetched.aggregator));
+        if let Some(cache) = &cache {
+            if let Err(e) = cache.store_blob(blob_id, &temp_path) {
+                log.push(format!("[CACHE] Blob not cached: {}", e));
+            }
+        }
     }
 
     if let Some(resource_id_str) = seal_resource_id {
@@ -55,21 +67,44 @@ async fn download_model_isolated(
                 "Encrypted models need a registered printer and an on-chain PrintJob; create a print job from your selection before downloading."
             )
         })?;
-        decrypt_model_with_printjob(
+        let decrypted = decrypt_model_with_printjob(
             &temp_path,
+            &format!("{}.stl", job.id()),
             resource_id_str,
+            &seal,
             current_rpc,
             eureka_package_id,
+            &key_servers,
             &printer_id,
             &cap_id,
             &mut log,
         )
-        .await?;
-        log.push("[LOG] ✅ Model decrypted successfully".to_string());
+        .await;
+        // The ciphertext is cached; the job directory keeps no copy of an encrypted model.
+        if let Some(plaintext) = decrypted? {
+            let _ = fs::remove_file(&temp_path);
+            log.push("[LOG] ✅ Model decrypted successfully".to_string());
+            log.push(format!(
+                "[LOG] Decrypted model held in {} until sliced",
+                plaintext.path().display()
+            ));
+            return Ok((log, ModelFile::Decrypted(plaintext)));
+        }
     }
 
+    let data = tokio::fs::read(&temp_path).await?;
+    match identify(&data) {
+        Ok(ModelBlob::Sealed(_)) => {
+            return Err(anyhow::anyhow!(
+                "Blob is Seal ciphertext, but the Sculpt has no seal_resource_id to decrypt it with"
+            ))
+        }
+        Ok(model) => log.push(format!("[LOG] Model: {}", model)),
+        Err(e) => return Err(anyhow::anyhow!("Downloaded blob is not a usable model: {}", e)),
+    }
     fs::rename(&temp_path, &final_path).map_err(|e| anyhow::anyhow!("Failed to move 3D model: {}", e))?;
-    Ok(log)
+    log.push(format!("[LOG] Model saved to: {}", final_path.display()));
+    Ok((log, ModelFile::Job(final_path)))
 }
 
 /// Walrus blob id for the STL and effective Seal id — prefer on-chain `Sculpt.structure` (same source as
@@ -77,8 +112,10 @@ async fn download_model_isolated(
 /// `PrintJob.sculpt_structure` only when we cannot query the Sculpt (no printer id).
 async fn walrus_blob_and_seal_for_online_task(
     task: &PrintTask,
+    seal: &SealService,
     rpc: &str,
     eureka_package_id: &str,
+    key_servers: &SealNetworkConfig,
     printer_id: &str,
     log: &mut Vec<String>,
 ) -> Result<(String, Option<String>), anyhow::Error> {
@@ -101,7 +138,7 @@ async fn walrus_blob_and_seal_for_online_task(
     let printer_oid = SuiObjectID::from_hex_literal(printer_id.trim())
         .map_err(|e| anyhow::anyhow!("Invalid printer_id: {}", e))?;
 
-    let decryptor = PrintJobDecryptor::new(rpc.to_string(), eureka_package_id).await?;
+    let decryptor = seal.decryptor(rpc, eureka_package_id, key_servers).await?;
     let (structure, seal_on_sculpt, _) = decryptor
         .fetch_sculpt_and_objects(sculpt_id, printer_oid)
         .await?;
@@ -117,28 +154,36 @@ async fn walrus_blob_and_seal_for_online_task(
         ));
     }
 
-    let seal = task.seal_resource_id.clone().or(seal_on_sculpt);
-    Ok((structure, seal))
+    let seal_id = task.seal_resource_id.clone().or(seal_on_sculpt);
+    Ok((structure, seal_id))
 }
 
+/// Decrypt the blob at `file_path` into the private file `name`; `None` when the blob turns out to
+/// be plaintext already.
+#[allow(clippy::too_many_arguments)]
 async fn decrypt_model_with_printjob(
     file_path: &Path,
+    name: &str,
     seal_resource_id: &str,
+    seal: &SealService,
     rpc_url: &str,
     eureka_package_id: &str,
+    key_servers: &SealNetworkConfig,
     printer_id: &str,
     printer_cap_id: &str,
     log: &mut Vec<String>,
-) -> Result<()> {
+) -> Result<Option<PrivateFile>> {
     let encrypted_data = tokio::fs::read(file_path).await?;
 
-    if !is_file_encrypted(&encrypted_data) {
-        log.push("[LOG] ⚠️  File looks like plaintext STL; skipping decryption".to_string());
-        return Ok(());
+    match identify(&encrypted_data)? {
+        ModelBlob::Sealed(_) => {}
+        plain => {
+            log.push(format!("[LOG] ⚠️  Blob is a plaintext model ({}); skipping decryption", plain));
+            return Ok(None);
+        }
     }
 
-    log.push("[LOG] 🔐 Initializing PrintJobDecryptor (Seal SDK + JSON-RPC)...".to_string());
-    let decryptor = PrintJobDecryptor::new(rpc_url.to_string(), eureka_package_id).await?;
+    let decryptor = seal.decryptor(rpc_url, eureka_package_id, key_servers).await?;
 
     let printer_oid =
         SuiObjectID::from_hex_literal(printer_id).map_err(|e| anyhow::anyhow!("printer_id: {}", e))?;
@@ -148,9 +193,48 @@ async fn decrypt_model_with_printjob(
     let decrypted = decryptor
         .decrypt_sealed_file_bytes(seal_resource_id, &encrypted_data, printer_oid, cap_oid)
         .await?;
+    match identify(&decrypted) {
+        Ok(ModelBlob::Sealed(_)) => return Err(anyhow::anyhow!("Decrypted data is itself Seal ciphertext")),
+        Ok(model) => log.push(format!("[LOG] Decrypted model: {}", model)),
+        Err(e) => return Err(anyhow::anyhow!("Decrypted data is not a usable model: {}", e)),
+    }
+
+    PrivateFile::create(name, &decrypted).map(Some)
+}
+
+/// Working directory for a downloaded model (see `crate::workspace`); reports the error on `App`.
+async fn create_job(
+    app: &Arc<Mutex<App>>,
+    source_id: &str,
+    name: &str,
+    blob_id: &str,
+    encrypted: bool,
+) -> Option<JobWorkspace> {
+    let mut g = app.lock().await;
+    match JobWorkspace::create(&g.config.jobs, source_id, name, blob_id, encrypted) {
+        Ok(job) => Some(job),
+        Err(e) => {
+            g.set_message(
+                crate::app::MessageType::Error,
+                format!("Could not create job directory: {}", e),
+            );
+            None
+        }
+    }
+}
 
-    tokio::fs::write(file_path, decrypted).await?;
-    Ok(())
+/// Progress callback for `WalrusClient`; skips an update rather than wait for the `App` lock.
+fn progress_reporter(app: &Arc<Mutex<App>>) -> impl FnMut(DownloadProgress) + Send + '_ {
+    move |progress| {
+        if let Ok(mut g) = app.try_lock() {
+            g.download_progress = Some(progress);
+        }
+    }
+}
+
+/// Record a failed download on the job; the directory is kept for inspection.
+fn fail_job(job: &mut JobWorkspace, error: &anyhow::Error) {
+    let _ = job.set_state(JobState::Failed, Some(error.to_string()));
 }
 
 impl App {
@@ -242,7 +326,12 @@ impl App {
                         None
                     };
 
-                    match download_model_isolated(
+                    let Some(mut job) = create_job(&app_clone, &item.id, &item.alias, &item.blob_id, seal.is_some()).await else {
+                        return;
+                    };
+                    let model = match download_model_isolated(
+                        &app_clone,
+                        &job,
                         &item.blob_id,
                         seal,
                         &rpc,
@@ -251,15 +340,17 @@ impl App {
                     )
                     .await
                     {
-                        Ok(mut lines) => {
+                        Ok((mut lines, model)) => {
                             let mut app = app_clone.lock().await;
                             app.print_output.append(&mut lines);
                             app.set_message(
                                 crate::app::MessageType::Success,
                                 "3D model downloaded successfully".to_string(),
                             );
+                            model
                         }
                         Err(e) => {
+                            fail_job(&mut job, &e);
                             let mut app = app_clone.lock().await;
                             app.set_message(
                                 crate::app::MessageType::Error,
@@ -267,9 +358,18 @@ impl App {
                             );
                             return;
                         }
+                    };
+                    if download_only {
+                        let _ = job.set_state(JobState::Ready, None);
+                        // A decrypted model is wiped when `model` drops at the end of this task.
+                        if matches!(model, ModelFile::Decrypted(_)) {
+                            app_clone.lock().await.print_output.push(
+                                "[LOG] Download only: the decrypted model is not kept on disk".to_string(),
+                            );
+                        }
                     }
 
-                    // run print script (not only download)
+                    // run print pipeline (not only download)
                     if !download_only {
                         let has_printer = {
                             let g = app_clone.lock().await;
@@ -289,22 +389,27 @@ impl App {
                         
                         {
                             let mut app = app_clone.lock().await;
-                            app.print_output.push("[LOG] Preparing to run print script".to_string());
+                            app.print_output.push("[LOG] Preparing to run print pipeline".to_string());
                             app.print_status = crate::app::PrintStatus::Printing;
                         }
                         
-                        let print_result = App::run_print_script(Arc::clone(&app_clone)).await;
+                        let print_result = App::run_print_pipeline(Arc::clone(&app_clone), job, model).await;
                         
                         let mut app = app_clone.lock().await;
                         match print_result {
-                            Ok(_) => {
+                            Ok(true) => {
                                 app.print_output.push("[LOG] Print script executed successfully".to_string());
                             },
+                            Ok(false) => {
+                                app.print_output.push("[LOG] Print cancelled".to_string());
+                            },
                             Err(error_msg) => {
                                 app.print_output.push(format!("[LOG] Print script failed: {}", error_msg));
                                 app.set_message(crate::app::MessageType::Error, error_msg);
-                                // Update status to failed when there's an error
-                                app.print_status = crate::app::PrintStatus::Idle;
+                                // Keep a firmware error from `run_print_pipeline`; otherwise back to idle
+                                if !matches!(app.print_status, crate::app::PrintStatus::Error(_)) {
+                                    app.print_status = crate::app::PrintStatus::Idle;
+                                }
                             }
                         }
                     }
@@ -332,7 +437,7 @@ impl App {
                     app.set_message(crate::app::MessageType::Info, format!("Processing print job: {}", task.name));
                 }
                 
-                let (rpc, eureka_pkg) = {
+                let (rpc, eureka_pkg, seal, key_servers) = {
                     let g = app_clone.lock().await;
                     (
                         g.network_state.get_current_rpc().to_string(),
@@ -340,6 +445,8 @@ impl App {
                             .get_current_package_ids()
                             .eureka_package_id
                             .to_string(),
+                        Arc::clone(&g.seal),
+                        g.seal_key_servers(),
                     )
                 };
 
@@ -352,8 +459,10 @@ impl App {
                 let (walrus_blob_id, seal_effective) =
                     match walrus_blob_and_seal_for_online_task(
                         &task,
+                        &seal,
                         &rpc,
                         &eureka_pkg,
+                        &key_servers,
                         &printer_id,
                         &mut resolve_logs,
                     )
@@ -407,7 +516,13 @@ impl App {
                     None
                 };
 
-                match download_model_isolated(
+                let encrypted = seal_for_download.is_some();
+                let Some(mut job) = create_job(&app_clone, &task.id, &task.name, &walrus_blob_id, encrypted).await else {
+                    return;
+                };
+                let model = match download_model_isolated(
+                    &app_clone,
+                    &job,
                     &walrus_blob_id,
                     seal_for_download,
                     &rpc,
@@ -416,7 +531,7 @@ impl App {
                 )
                 .await
                 {
-                    Ok(mut lines) => {
+                    Ok((mut lines, model)) => {
                         let mut app = app_clone.lock().await;
                         app.print_output.append(&mut resolve_logs);
                         app.print_output.append(&mut lines);
@@ -424,8 +539,10 @@ impl App {
                             crate::app::MessageType::Success,
                             "3D model downloaded successfully".to_string(),
                         );
+                        model
                     }
                     Err(e) => {
+                        fail_job(&mut job, &e);
                         let mut app = app_clone.lock().await;
                         app.print_output.append(&mut resolve_logs);
                         app.set_message(
@@ -434,27 +551,41 @@ impl App {
                         );
                         return;
                     }
+                };
+                if download_only {
+                    let _ = job.set_state(JobState::Ready, None);
+                    // A decrypted model is wiped when `model` drops at the end of this task.
+                    if matches!(model, ModelFile::Decrypted(_)) {
+                        app_clone.lock().await.print_output.push(
+                            "[LOG] Download only: the decrypted model is not kept on disk".to_string(),
+                        );
+                    }
                 }
 
                 if !download_only {
                     {
                         let mut app = app_clone.lock().await;
-                        app.print_output.push("[LOG] Preparing to run print script for task".to_string());
+                        app.print_output.push("[LOG] Preparing to run print pipeline for task".to_string());
                         app.print_status = crate::app::PrintStatus::Printing;
                     }
                     
-                    let print_result = App::run_print_script(Arc::clone(&app_clone)).await;
+                    let print_result = App::run_print_pipeline(Arc::clone(&app_clone), job, model).await;
                     
                     let mut app = app_clone.lock().await;
                     match print_result {
-                        Ok(_) => {
-                            app.print_output.push("[LOG] Task print script executed successfully".to_string());
+                        Ok(true) => {
+                            app.print_output.push("[LOG] Task print pipeline executed successfully".to_string());
                             app.set_message(crate::app::MessageType::Success, "Print job started successfully!".to_string());
                         },
+                        Ok(false) => {
+                            app.print_output.push("[LOG] Task print cancelled".to_string());
+                        },
                         Err(error_msg) => {
-                            app.print_output.push(format!("[LOG] Task print script failed: {}", error_msg));
+                            app.print_output.push(format!("[LOG] Task print pipeline failed: {}", error_msg));
                             app.set_message(crate::app::MessageType::Error, format!("Failed to start print job: {}", error_msg));
-                            app.print_status = crate::app::PrintStatus::Idle;
+                            if !matches!(app.print_status, crate::app::PrintStatus::Error(_)) {
+                                app.print_status = crate::app::PrintStatus::Idle;
+                            }
                         }
                     }
                 }
@@ -534,7 +665,7 @@ impl App {
                     }
                 };
 
-                let (rpc, eureka_pkg) = {
+                let (rpc, eureka_pkg, seal, key_servers) = {
                     let app_guard = app_clone.lock().await;
                     (
                         app_guard.network_state.get_current_rpc().to_string(),
@@ -543,19 +674,30 @@ impl App {
                             .get_current_package_ids()
                             .eureka_package_id
                             .to_string(),
+                        Arc::clone(&app_guard.seal),
+                        app_guard.seal_key_servers(),
                     )
                 };
 
-                // Create PrintJob decryptor and perform decryption
-                let decryption_result = match PrintJobDecryptor::new(rpc, &eureka_pkg).await {
+                let walrus = match app_clone.lock().await.walrus_client() {
+                    Ok(walrus) => walrus,
+                    Err(e) => {
+                        let mut app = app_clone.lock().await;
+                        app.set_message(crate::app::MessageType::Error, format!("Walrus client: {}", e));
+                        return;
+                    }
+                };
+
+                // Shared PrintJob decryptor (cached session key) performs the decryption
+                let decryption_result = match seal.decryptor(&rpc, &eureka_pkg, &key_servers).await {
                     Ok(decryptor) => {
                         {
                             let mut app = app_clone.lock().await;
-                            app.print_output.push("[MOCK] PrintJob decryptor initialized".to_string());
+                            app.print_output.push("[MOCK] PrintJob decryptor ready".to_string());
                             app.print_output.push("[MOCK] Starting decryption with PrintJob authorization...".to_string());
                         }
 
-                        decryptor.decrypt_printjob_sculpt(printer_id, printer_cap_id).await
+                        decryptor.decrypt_printjob_sculpt(&walrus, printer_id, printer_cap_id).await
                     },
                     Err(e) => {
                         let mut app = app_clone.lock().await;
@@ -570,35 +712,36 @@ impl App {
                             let mut app = app_clone.lock().await;
                             app.print_output.push("[MOCK] ✅ PrintJob-based decryption successful!".to_string());
                             
-                            let format = if plaintext.starts_with(b"solid") {
-                                "ASCII STL"
-                            } else if plaintext.len() > 84 {
-                                "Binary STL"
-                            } else {
-                                "Unknown"
+                            let format = match identify(&plaintext) {
+                                Ok(model) => model.to_string(),
+                                Err(e) => format!("not a valid model: {}", e),
                             };
                             
                             app.print_output.push(format!("[MOCK] Decrypted STL: {} ({} bytes)", format, plaintext.len()));
                             app.print_output.push("[MOCK] Starting mock print process...".to_string());
                         }
 
-                        // Save decrypted file for mock printing and testing
-                        // Save to mock_print.stl for mock printing
-                        let mock_stl_path = crate_root().join("mock_print.stl");
-                        if let Err(e) = std::fs::write(&mock_stl_path, &plaintext) {
-                            let mut app = app_clone.lock().await;
-                            app.set_message(crate::app::MessageType::Error, format!("Failed to save decrypted STL: {}", e));
-                            return;
-                        }
+                        // Decrypted STL and its G-code only live in the private runtime directory
+                        let private = PrivateFile::create("mock_print.stl", &plaintext)
+                            .and_then(|stl| Ok((stl, PrivateFile::reserve("mock_print.gcode")?)));
+                        let (mock_stl, mock_gcode) = match private {
+                            Ok(files) => files,
+                            Err(e) => {
+                                let mut app = app_clone.lock().await;
+                                app.set_message(crate::app::MessageType::Error, format!("Failed to save decrypted STL: {}", e));
+                                return;
+                            }
+                        };
 
                         {
                             let mut app = app_clone.lock().await;
-                            app.print_output.push(format!("[MOCK] Saved decrypted STL to: {}", mock_stl_path.display()));
+                            app.print_output.push(format!("[MOCK] Decrypted STL held in: {}", mock_stl.path().display()));
                             app.print_output.push("[MOCK] Starting slicing test...".to_string());
                         }
 
                         // Run slicing test (optional - won't fail if PrusaSlicer not available)
-                        let slice_result = App::run_slice_test(Arc::clone(&app_clone)).await;
+                        let slice_result = App::run_slice_test(Arc::clone(&app_clone), mock_stl.path(), mock_gcode.path()).await;
+                        drop((mock_stl, mock_gcode));
                         match slice_result {
                             Ok(_) => {
                                 let mut app = app_clone.lock().await;
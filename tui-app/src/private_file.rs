@@ -0,0 +1,106 @@
+//! Decrypted models, and the G-code sliced from them, never reach persistent storage. They are
+//! written as 0600 files into a private runtime directory (`$XDG_RUNTIME_DIR/eureka`, normally
+//! tmpfs; `<tmp>/eureka-<uid>` otherwise, kept at 0700), handed to the slicer by path, then
+//! overwritten and unlinked when the [`PrivateFile`] is dropped, whichever way the print ends.
+//! `remove_leftovers` clears what a killed process could not.
+
+use anyhow::{bail, Context, Result};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// A file in the runtime directory, wiped on drop.
+#[derive(Debug)]
+pub struct PrivateFile {
+    path: PathBuf,
+}
+
+impl PrivateFile {
+    /// Write `data` into a new 0600 file `name`.
+    pub fn create(name: &str, data: &[u8]) -> Result<Self> {
+        let file = Self::reserve(name)?;
+        let mut out = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&file.path)
+            .with_context(|| format!("create {}", file.path.display()))?;
+        out.write_all(data)
+            .with_context(|| format!("write {}", file.path.display()))?;
+        Ok(file)
+    }
+
+    /// A path for a tool to write into (the slicer's G-code); whatever lands there is wiped with
+    /// the handle.
+    pub fn reserve(name: &str) -> Result<Self> {
+        let path = runtime_dir()?.join(name);
+        wipe(&path);
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PrivateFile {
+    fn drop(&mut self) {
+        wipe(&self.path);
+    }
+}
+
+/// The runtime directory, created 0700 when missing. One that belongs to another user (a shared
+/// `/tmp`) is refused rather than written into.
+pub fn runtime_dir() -> Result<PathBuf> {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    let uid = unsafe { libc::getuid() };
+    let dir = match dirs::runtime_dir() {
+        Some(dir) => dir.join("eureka"),
+        None => std::env::temp_dir().join(format!("eureka-{}", uid)),
+    };
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e).with_context(|| format!("create {}", dir.display())),
+    }
+    let meta = std::fs::symlink_metadata(&dir).with_context(|| format!("stat {}", dir.display()))?;
+    if !meta.is_dir() || meta.uid() != uid {
+        bail!("{} is not a directory owned by this user", dir.display());
+    }
+    if meta.mode() & 0o077 != 0 {
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("chmod 700 {}", dir.display()))?;
+    }
+    Ok(dir)
+}
+
+/// Wipe files left in the runtime directory by a previous run; returns how many there were.
+pub fn remove_leftovers() -> Result<usize> {
+    let dir = runtime_dir()?;
+    let mut removed = 0;
+    for entry in std::fs::read_dir(&dir)?.filter_map(|e| e.ok()) {
+        if entry.file_type().is_ok_and(|t| t.is_file()) {
+            wipe(&entry.path());
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Overwrite with zeros, then unlink. Best effort: a missing file is already gone.
+fn wipe(path: &Path) {
+    if let Ok(mut file) = OpenOptions::new().write(true).open(path) {
+        let zeros = [0u8; 64 * 1024];
+        let mut left = file.metadata().map(|m| m.len()).unwrap_or(0);
+        while left > 0 {
+            let n = left.min(zeros.len() as u64) as usize;
+            if file.write_all(&zeros[..n]).is_err() {
+                break;
+            }
+            left -= n as u64;
+        }
+        let _ = file.sync_data();
+    }
+    let _ = std::fs::remove_file(path);
+}
@@ -1,37 +1,182 @@
-/// Heuristic: treat buffer as cleartext STL if it looks like ASCII or binary STL; otherwise assume encrypted.
-pub fn is_file_encrypted(data: &[u8]) -> bool {
-    if data.len() < 5 {
-        return true;
+//! What a model blob is: Seal ciphertext (a BCS `EncryptedObject`), ASCII STL or binary STL.
+//! Every format is checked structurally, so a binary STL whose header starts with `solid` is
+//! still binary, and random bytes are none of them.
+
+use anyhow::{anyhow, bail, Result};
+use seal_sdk_rs::crypto::EncryptedObject;
+use std::fmt;
+use std::str::SplitAsciiWhitespace;
+
+/// `EncryptedObject.version` written by current Seal clients.
+const SEAL_VERSION: u8 = 0;
+/// BCS prefix of an `EncryptedObject`: the version byte, then the 32-byte package id.
+const SEAL_HEADER_LEN: usize = 33;
+/// 80-byte header plus the little-endian triangle count.
+const BINARY_STL_HEADER_LEN: u64 = 84;
+/// Normal, three vertices and the attribute byte count.
+const BINARY_STL_FACET_LEN: u64 = 50;
+
+pub enum ModelBlob {
+    Sealed(Box<EncryptedObject>),
+    AsciiStl { facets: u32 },
+    BinaryStl { facets: u32 },
+}
+
+impl fmt::Display for ModelBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelBlob::Sealed(_) => write!(f, "Seal ciphertext"),
+            ModelBlob::AsciiStl { facets } => write!(f, "ASCII STL, {} facets", facets),
+            ModelBlob::BinaryStl { facets } => write!(f, "binary STL, {} facets", facets),
+        }
+    }
+}
+
+/// Identify `data`; the error says why it is none of the supported formats.
+pub fn identify(data: &[u8]) -> Result<ModelBlob> {
+    let sealed = match parse_sealed(data) {
+        Ok(object) => return Ok(ModelBlob::Sealed(Box::new(object))),
+        Err(e) => e,
+    };
+    if let Some(facets) = binary_stl_facets(data) {
+        return Ok(ModelBlob::BinaryStl { facets });
+    }
+    if data.starts_with(b"solid") {
+        return match ascii_stl_facets(data) {
+            Ok(facets) => Ok(ModelBlob::AsciiStl { facets }),
+            Err(e) => Err(anyhow!("invalid ASCII STL: {}", e)),
+        };
     }
+    if data.first() == Some(&SEAL_VERSION) {
+        return Err(sealed);
+    }
+    Err(anyhow!(
+        "{} bytes that are neither Seal ciphertext nor STL (binary STL must be 84 + 50 × facets bytes)",
+        data.len()
+    ))
+}
 
-    let header = String::from_utf8_lossy(&data[..5]);
-    if header.starts_with("solid") {
-        return false;
+/// Decode a Seal `EncryptedObject`. The whole buffer must be the object (BCS rejects trailing
+/// bytes), with the supported version, a package id and a non-empty key id.
+pub fn parse_sealed(data: &[u8]) -> Result<EncryptedObject> {
+    if data.len() < SEAL_HEADER_LEN {
+        bail!("{} bytes is too short for a Seal object", data.len());
     }
+    if data[0] != SEAL_VERSION {
+        bail!("unsupported Seal object version {}", data[0]);
+    }
+    if data[1..SEAL_HEADER_LEN].iter().all(|b| *b == 0) {
+        bail!("Seal object has no package id");
+    }
+    let object: EncryptedObject =
+        bcs::from_bytes(data).map_err(|e| anyhow!("not a Seal EncryptedObject: {}", e))?;
+    if object.id.is_empty() {
+        bail!("Seal object has an empty id");
+    }
+    Ok(object)
+}
 
-    if data.len() > 84 {
-        let triangle_count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]);
-        if triangle_count > 0 && triangle_count < 1_000_000 {
-            return false;
+/// Facet count of a binary STL whose size matches it exactly.
+fn binary_stl_facets(data: &[u8]) -> Option<u32> {
+    let count = u32::from_le_bytes(data.get(80..84)?.try_into().ok()?);
+    let expected = BINARY_STL_HEADER_LEN + BINARY_STL_FACET_LEN * u64::from(count);
+    (count > 0 && data.len() as u64 == expected).then_some(count)
+}
+
+/// Facet count of a well-formed ASCII STL:
+/// `solid [name] (facet normal n n n outer loop (vertex x y z){3} endloop endfacet)+ endsolid [name]`.
+fn ascii_stl_facets(data: &[u8]) -> Result<u32> {
+    let text = std::str::from_utf8(data).map_err(|e| anyhow!("not text at byte {}", e.valid_up_to()))?;
+    let mut tokens = text.split_ascii_whitespace();
+    tokens.next();
+    // Skip the solid's name.
+    let mut token = tokens.by_ref().find(|t| is_keyword(t, "facet") || is_keyword(t, "endsolid"));
+    let mut facets = 0u32;
+    loop {
+        match token {
+            Some(t) if is_keyword(t, "endsolid") => break,
+            Some(t) if is_keyword(t, "facet") => {}
+            Some(t) => bail!("facet {}: expected `facet` or `endsolid`, found `{}`", facets + 1, t),
+            None => bail!("missing `endsolid`"),
         }
+        facets += 1;
+        let facet = |e: anyhow::Error| anyhow!("facet {}: {}", facets, e);
+        keyword(&mut tokens, "normal").map_err(facet)?;
+        numbers(&mut tokens, 3).map_err(facet)?;
+        keyword(&mut tokens, "outer").map_err(facet)?;
+        keyword(&mut tokens, "loop").map_err(facet)?;
+        for _ in 0..3 {
+            keyword(&mut tokens, "vertex").map_err(facet)?;
+            numbers(&mut tokens, 3).map_err(facet)?;
+        }
+        keyword(&mut tokens, "endloop").map_err(facet)?;
+        keyword(&mut tokens, "endfacet").map_err(facet)?;
+        token = tokens.next();
+    }
+    if facets == 0 {
+        bail!("no facets");
+    }
+    Ok(facets)
+}
+
+fn is_keyword(token: &str, keyword: &str) -> bool {
+    token.eq_ignore_ascii_case(keyword)
+}
+
+fn keyword(tokens: &mut SplitAsciiWhitespace<'_>, expected: &str) -> Result<()> {
+    match tokens.next() {
+        Some(t) if is_keyword(t, expected) => Ok(()),
+        Some(t) => bail!("expected `{}`, found `{}`", expected, t),
+        None => bail!("expected `{}`, found end of file", expected),
     }
+}
 
-    true
+fn numbers(tokens: &mut SplitAsciiWhitespace<'_>, count: usize) -> Result<()> {
+    for _ in 0..count {
+        let t = tokens.next().ok_or_else(|| anyhow!("expected a number, found end of file"))?;
+        t.parse::<f32>().map_err(|_| anyhow!("expected a number, found `{}`", t))?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const FACET: &str = "facet normal 0 0 1\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 0 1e0 0\n endloop\nendfacet\n";
+
     #[test]
-    fn test_is_file_encrypted_ascii_stl() {
+    fn test_truncated_ascii_stl_is_neither() {
         let stl_data = b"solid cube\n  facet normal 0 0 1\n";
-        assert!(!is_file_encrypted(stl_data));
+        assert!(parse_sealed(stl_data).is_err());
+        assert!(identify(stl_data).is_err());
     }
 
     #[test]
-    fn test_is_file_encrypted_encrypted_data() {
+    fn test_random_bytes_are_not_sealed() {
         let encrypted_data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00];
-        assert!(is_file_encrypted(&encrypted_data));
+        assert!(parse_sealed(&encrypted_data).is_err());
+        let mut header = vec![SEAL_VERSION];
+        header.extend([0u8; 32]);
+        header.extend([7u8; 40]);
+        assert!(parse_sealed(&header).unwrap_err().to_string().contains("package id"));
+        assert!(identify(&header).is_err());
+    }
+
+    #[test]
+    fn test_identify_stl() {
+        let ascii = format!("solid cube\n{}{}endsolid cube\n", FACET, FACET);
+        assert!(matches!(identify(ascii.as_bytes()), Ok(ModelBlob::AsciiStl { facets: 2 })));
+        let truncated = format!("solid cube\n{}", &FACET[..40]);
+        assert!(identify(truncated.as_bytes()).is_err());
+
+        // Binary STL whose header starts with "solid".
+        let mut binary = b"solid exported by some CAD tool".to_vec();
+        binary.resize(80, b' ');
+        binary.extend(2u32.to_le_bytes());
+        binary.resize(84 + 2 * 50, 0);
+        assert!(matches!(identify(&binary), Ok(ModelBlob::BinaryStl { facets: 2 })));
+        binary.push(0);
+        assert!(identify(&binary).is_err());
     }
 }
@@ -0,0 +1,197 @@
+//! Trusted Seal key servers per network (`seal.<network>` in config.yaml). Ciphertext names the
+//! key servers and threshold it was encrypted for; it is only accepted when enough of those are
+//! configured here. Each configured server can be probed on `/v1/service`, for the diagnostics
+//! view and to say which server broke a failed decryption (the SDK reports one error for all).
+//! The SDK fetches key shares from the URL in the on-chain `KeyServer` object, not the configured
+//! one, so a configured server whose object names another URL is refused.
+
+use anyhow::{bail, Result};
+use std::time::{Duration, Instant};
+
+use crate::config::{KeyServerConfig, SealNetworkConfig};
+
+#[derive(Debug, Clone)]
+pub struct KeyServerCheck {
+    pub object_id: String,
+    pub url: String,
+    /// Round trip of the service request, or why it failed.
+    pub result: Result<Duration, String>,
+}
+
+/// Refuse ciphertext for key servers `services` (object ids) with `threshold` that the
+/// configuration does not cover. No configured key servers accepts any.
+pub fn check_ciphertext(services: &[String], threshold: u8, config: &SealNetworkConfig) -> Result<()> {
+    if let Some(min) = config.threshold {
+        if threshold < min {
+            bail!(
+                "ciphertext threshold {} is below the configured minimum {} (seal threshold)",
+                threshold,
+                min
+            );
+        }
+    }
+    if config.key_servers.is_empty() {
+        return Ok(());
+    }
+    let unknown: Vec<&str> = services
+        .iter()
+        .filter(|id| find(config, id).is_none())
+        .map(String::as_str)
+        .collect();
+    let trusted = services.len() - unknown.len();
+    if trusted < usize::from(threshold) {
+        bail!(
+            "ciphertext needs {} of its key servers but only {} are configured (seal key_servers); not configured: {}",
+            threshold,
+            trusted,
+            unknown.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// The configured servers among `services`.
+pub fn configured(services: &[String], config: &SealNetworkConfig) -> Vec<KeyServerConfig> {
+    let mut servers: Vec<KeyServerConfig> = Vec::new();
+    for server in services.iter().filter_map(|id| find(config, id)) {
+        if !servers.contains(server) {
+            servers.push(server.clone());
+        }
+    }
+    servers
+}
+
+/// Refuse `server` when its on-chain `KeyServer` object says `onchain_url`, another URL than the
+/// configured one: decryption would ask a server the probes never see.
+pub fn check_url(server: &KeyServerConfig, onchain_url: &str) -> Result<()> {
+    let configured = server.url.trim().trim_end_matches('/');
+    let onchain = onchain_url.trim().trim_end_matches('/');
+    if !configured.eq_ignore_ascii_case(onchain) {
+        bail!(
+            "key server {} is configured with {} but its on-chain object names {} (seal key_servers url)",
+            server.object_id,
+            configured,
+            onchain
+        );
+    }
+    Ok(())
+}
+
+/// Probe every server in `servers` concurrently.
+pub async fn check_all(servers: &[KeyServerConfig], timeout: Duration) -> Vec<KeyServerCheck> {
+    let http = match reqwest::Client::builder().connect_timeout(timeout).build() {
+        Ok(http) => http,
+        Err(e) => {
+            return servers
+                .iter()
+                .map(|s| failed(s, format!("HTTP client: {}", e)))
+                .collect()
+        }
+    };
+    futures::future::join_all(servers.iter().map(|s| check(&http, s, timeout))).await
+}
+
+/// One line per check, for appending to an error message.
+pub fn report(checks: &[KeyServerCheck]) -> String {
+    checks
+        .iter()
+        .map(|c| match &c.result {
+            Ok(latency) => format!("\n  {} ({}): reachable, {} ms", c.url, c.object_id, latency.as_millis()),
+            Err(e) => format!("\n  {} ({}): {}", c.url, c.object_id, e),
+        })
+        .collect()
+}
+
+async fn check(http: &reqwest::Client, server: &KeyServerConfig, timeout: Duration) -> KeyServerCheck {
+    let url = format!(
+        "{}/v1/service?service_id={}",
+        server.url.trim_end_matches('/'),
+        server.object_id
+    );
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, http.get(&url).send()).await {
+        Err(_) => Err(format!("no response within {} s", timeout.as_secs())),
+        Ok(Err(e)) => Err(describe(&e)),
+        Ok(Ok(response)) if response.status().is_success() => Ok(started.elapsed()),
+        Ok(Ok(response)) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("HTTP {}: {}", status, body.trim().chars().take(160).collect::<String>()))
+        }
+    };
+    KeyServerCheck {
+        object_id: server.object_id.clone(),
+        url: server.url.clone(),
+        result,
+    }
+}
+
+fn failed(server: &KeyServerConfig, error: String) -> KeyServerCheck {
+    KeyServerCheck {
+        object_id: server.object_id.clone(),
+        url: server.url.clone(),
+        result: Err(error),
+    }
+}
+
+/// reqwest's own message ("error sending request") plus the causes that say what went wrong.
+fn describe(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn find<'a>(config: &'a SealNetworkConfig, id: &str) -> Option<&'a KeyServerConfig> {
+    config
+        .key_servers
+        .iter()
+        .find(|s| normalize_id(&s.object_id) == normalize_id(id))
+}
+
+/// Object ids compare as 32-byte values: case, `0x` and leading zeros do not matter.
+fn normalize_id(id: &str) -> String {
+    let hex = id.trim().trim_start_matches("0x").to_ascii_lowercase();
+    format!("{:0>64}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ciphertext() {
+        let server = |id: &str| KeyServerConfig {
+            object_id: id.to_string(),
+            url: format!("https://{}.example", id),
+        };
+        let config = SealNetworkConfig {
+            key_servers: vec![server("0xa1"), server("0x00B2")],
+            threshold: Some(2),
+            ..SealNetworkConfig::default()
+        };
+        let ids = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert!(check_ciphertext(&ids(&["0xA1", "0xb2", "0xc3"]), 2, &config).is_ok());
+        let err = check_ciphertext(&ids(&["0xa1", "0xc3", "0xd4"]), 2, &config).unwrap_err();
+        assert!(err.to_string().contains("0xc3, 0xd4"));
+        assert!(check_ciphertext(&ids(&["0xa1", "0xb2"]), 1, &config).is_err());
+        assert!(check_ciphertext(&ids(&["0xc3"]), 1, &SealNetworkConfig::default()).is_ok());
+        assert_eq!(configured(&ids(&["0xb2", "0xc3", "0xb2"]), &config), vec![server("0x00B2")]);
+    }
+
+    #[test]
+    fn test_check_url() {
+        let server = KeyServerConfig {
+            object_id: "0xa1".to_string(),
+            url: "https://seal.example/".to_string(),
+        };
+        assert!(check_url(&server, "https://SEAL.example").is_ok());
+        let err = check_url(&server, "https://other.example").unwrap_err();
+        assert!(err.to_string().contains("https://other.example"));
+    }
+}
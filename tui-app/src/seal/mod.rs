@@ -1,5 +1,9 @@
 pub mod decryption;
+pub mod key_servers;
 pub mod printjob_decryption;
+pub mod service;
 
-pub use decryption::is_file_encrypted;
+pub use decryption::{identify, ModelBlob};
+pub use key_servers::KeyServerCheck;
 pub use printjob_decryption::PrintJobDecryptor;
+pub use service::SealService;
@@ -6,24 +6,51 @@ use seal_sdk_rs::native_sui_sdk::sui_sdk::wallet_context::WalletContext;
 use seal_sdk_rs::native_sui_sdk::sui_types::Identifier;
 use seal_sdk_rs::native_sui_sdk::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use seal_sdk_rs::native_sui_sdk::sui_types::transaction::{ProgrammableTransaction, ObjectArg};
-use seal_sdk_rs::native_sui_sdk::sui_sdk::rpc_types::{SuiObjectDataOptions, SuiMoveValue};
+use seal_sdk_rs::native_sui_sdk::sui_sdk::rpc_types::{SuiObjectDataOptions, SuiMoveStruct, SuiMoveValue, SuiParsedData};
+use seal_sdk_rs::native_sui_sdk::sui_types::dynamic_field::DynamicFieldName;
+use seal_sdk_rs::native_sui_sdk::sui_types::TypeTag;
 use seal_sdk_rs::generic_types::ObjectID as SealObjectID;
 use seal_sdk_rs::native_sui_sdk::sui_types::base_types::ObjectID as SuiObjectID;
 use seal_sdk_rs::native_sui_sdk::sui_types::object::Owner;
 use std::str::FromStr;
 use std::path::Path;
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 use bcs;
+use crate::config::SealNetworkConfig;
+use crate::constants::{SEAL_SESSION_REFRESH_SECS, SEAL_SESSION_TTL_MINS};
+use super::key_servers;
+use crate::walrus::WalrusClient;
 /// PrintJob-based decryption aligned with on-chain `eureka::seal_approve` (requires PrintJob); SessionKey namespace is the **Eureka package**.
+/// Long-lived (see `super::service`): the wallet is loaded once and the session key reused until it nears expiry.
 pub struct PrintJobDecryptor {
+    rpc_url: String,
     eureka_package_id: SealObjectID,
     sui_client: seal_sdk_rs::native_sui_sdk::sui_sdk::SuiClient,
     seal_client: SealClient,
+    key_servers: SealNetworkConfig,
+    wallet: Mutex<Option<WalletContext>>,
+    session: Mutex<Option<CachedSession>>,
+}
+
+struct CachedSession {
+    key: Arc<SessionKey>,
+    expires_at: Instant,
+}
+
+impl CachedSession {
+    /// Enough time left to start a decryption on.
+    fn is_fresh(&self) -> bool {
+        self.expires_at.saturating_duration_since(Instant::now()) > Duration::from_secs(SEAL_SESSION_REFRESH_SECS)
+    }
 }
 
 impl PrintJobDecryptor {
     /// `rpc_url`: same network as the TUI (Seal SDK uses JSON-RPC). `eureka_package_id`: Eureka package for that network.
-    pub async fn new(rpc_url: String, eureka_package_id: &str) -> Result<Self> {
+    /// `key_servers`: the `seal` settings for that network (trusted servers, threshold, timeout).
+    pub async fn new(rpc_url: String, eureka_package_id: &str, key_servers: SealNetworkConfig) -> Result<Self> {
         if eureka_package_id.is_empty() {
             return Err(anyhow::anyhow!(
                 "Eureka package ID is not configured for this network; PrintJob decryption is unavailable."
@@ -40,9 +67,76 @@ impl PrintJobDecryptor {
         let seal_client = SealClient::new(sui_client.clone());
 
         Ok(Self {
+            rpc_url,
             eureka_package_id,
             sui_client,
             seal_client,
+            key_servers,
+            wallet: Mutex::new(None),
+            session: Mutex::new(None),
+        })
+    }
+
+    /// Whether this decryptor was built for `rpc_url`, `eureka_package_id` and `key_servers`.
+    pub fn serves(&self, rpc_url: &str, eureka_package_id: &str, key_servers: &SealNetworkConfig) -> bool {
+        self.rpc_url == rpc_url
+            && self.key_servers == *key_servers
+            && eureka_package_id
+                .parse::<SealObjectID>()
+                .is_ok_and(|id| id == self.eureka_package_id)
+    }
+
+    /// The cached session key, or a newly signed one when it is missing or about to expire.
+    async fn session_key(&self) -> Result<Arc<SessionKey>> {
+        let mut session = self.session.lock().await;
+        if let Some(cached) = session.as_ref().filter(|c| c.is_fresh()) {
+            return Ok(Arc::clone(&cached.key));
+        }
+        let cached = self.sign_session_key().await?;
+        let key = Arc::clone(&cached.key);
+        *session = Some(cached);
+        Ok(key)
+    }
+
+    /// Renew a session key that is about to expire; `false` when there is none or it is still
+    /// fresh. On failure the key is dropped and the next decryption signs a new one.
+    pub async fn refresh_session_key(&self) -> Result<bool> {
+        let mut session = self.session.lock().await;
+        match session.as_ref() {
+            Some(cached) if !cached.is_fresh() => {}
+            _ => return Ok(false),
+        }
+        match self.sign_session_key().await {
+            Ok(cached) => {
+                *session = Some(cached);
+                Ok(true)
+            }
+            Err(e) => {
+                *session = None;
+                Err(e)
+            }
+        }
+    }
+
+    async fn sign_session_key(&self) -> Result<CachedSession> {
+        let mut slot = self.wallet.lock().await;
+        let mut wallet = match slot.take() {
+            Some(wallet) => wallet,
+            None => {
+                let wallet_path = std::env::var("HOME")
+                    .map_err(|_| anyhow::anyhow!("Cannot find HOME env var"))?
+                    + "/.sui/sui_config/client.yaml";
+                WalletContext::new(Path::new(&wallet_path))?
+            }
+        };
+        let started = Instant::now();
+        // SessionKey IBE namespace must be the same `package_id` stored in the ciphertext
+        // (checked against `eureka_package_id` in `decrypt_sculpt`).
+        let key = SessionKey::new(self.eureka_package_id, SEAL_SESSION_TTL_MINS, &mut wallet).await;
+        *slot = Some(wallet);
+        Ok(CachedSession {
+            key: Arc::new(key?),
+            expires_at: started + Duration::from_secs(u64::from(SEAL_SESSION_TTL_MINS) * 60),
         })
     }
 
@@ -187,6 +281,49 @@ impl PrintJobDecryptor {
         Ok((structure, seal_resource_id, printer_version))
     }
 
+    /// The URL in a `seal::key_server::KeyServer` object, which the SDK fetches key shares from. It
+    /// sits in the object itself for the first release, and in the dynamic field of the object's
+    /// `last_version` since key servers are versioned.
+    async fn fetch_key_server_url(&self, key_server_id: &str) -> Result<String> {
+        let id = SuiObjectID::from_hex_literal(key_server_id)
+            .map_err(|e| anyhow::anyhow!("invalid object id: {}", e))?;
+        let mut options = SuiObjectDataOptions::new();
+        options.show_content = true;
+        let object = self
+            .sui_client
+            .read_api()
+            .get_object_with_options(id, options)
+            .await?
+            .data
+            .ok_or_else(|| anyhow::anyhow!("KeyServer object not found"))?;
+        let fields = move_fields(object.content.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("KeyServer is not a Move object"))?;
+        if let Some(url) = find_url(fields) {
+            return Ok(url);
+        }
+        let version = match fields.get("last_version") {
+            Some(SuiMoveValue::String(v)) => v.clone(),
+            Some(SuiMoveValue::Number(v)) => v.to_string(),
+            _ => return Err(anyhow::anyhow!("KeyServer has neither url nor last_version")),
+        };
+        let versioned = self
+            .sui_client
+            .read_api()
+            .get_dynamic_field_object(
+                id,
+                DynamicFieldName {
+                    type_: TypeTag::U64,
+                    value: serde_json::Value::String(version.clone()),
+                },
+            )
+            .await?
+            .data
+            .ok_or_else(|| anyhow::anyhow!("KeyServer version {} not found", version))?;
+        move_fields(versioned.content.as_ref())
+            .and_then(find_url)
+            .ok_or_else(|| anyhow::anyhow!("KeyServer version {} has no url", version))
+    }
+
     /// Extract Option<String> field from Move struct fields
     fn extract_option_string_field(
         &self,
@@ -205,24 +342,14 @@ impl PrintJobDecryptor {
         }
     }
 
-    /// Download encrypted blob from Walrus (same aggregator base URL as TUI model download).
-    pub async fn download_encrypted_data(&self, blob_id: &str) -> Result<Vec<u8>> {
-        let base = crate::constants::AGGREGATOR_URL.trim_end_matches('/');
-        let url = format!("{}/v1/blobs/{}", base, blob_id);
-        let response = reqwest::get(&url).await?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Failed to download: HTTP {}", response.status()));
-        }
-
-        Ok(response.bytes().await?.to_vec())
+    /// Download encrypted blob from Walrus (same client and limits as the TUI model download).
+    pub async fn download_encrypted_data(&self, walrus: &WalrusClient, blob_id: &str) -> Result<Vec<u8>> {
+        walrus.download(blob_id, &mut |_| {}).await
     }
 
-    /// Parse encrypted data as EncryptedObject
+    /// Parse encrypted data as EncryptedObject (see `decryption::parse_sealed` for the checks)
     pub fn parse_encrypted_object(&self, data: &[u8]) -> Result<seal_sdk_rs::crypto::EncryptedObject> {
-        let encrypted: seal_sdk_rs::crypto::EncryptedObject = bcs::from_bytes(data)
-            .map_err(|e| anyhow::anyhow!("BCS deserialization failed: {}", e))?;
-        Ok(encrypted)
+        super::decryption::parse_sealed(data)
     }
 
     /// Decrypt sculpt using Seal SDK with PrintJob-based authorization
@@ -246,21 +373,17 @@ impl PrintJobDecryptor {
                 self.eureka_package_id
             ));
         }
+        let services: Vec<String> = encrypted.services.iter().map(|(id, _)| id.to_string()).collect();
+        key_servers::check_ciphertext(&services, encrypted.threshold, &self.key_servers)?;
+        for server in key_servers::configured(&services, &self.key_servers) {
+            let onchain_url = self
+                .fetch_key_server_url(&server.object_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Key server {}: {}", server.object_id, e))?;
+            key_servers::check_url(&server, &onchain_url)?;
+        }
 
-        // Load wallet
-        let wallet_path = std::env::var("HOME")
-            .map_err(|_| anyhow::anyhow!("Cannot find HOME env var"))?
-            + "/.sui/sui_config/client.yaml";
-
-        let mut wallet = WalletContext::new(Path::new(&wallet_path))?;
-        
-        // SessionKey IBE namespace must be the same `package_id` stored in the ciphertext.
-        let session_key = SessionKey::new(
-            seal_package_id,
-            10,
-            &mut wallet,
-        )
-        .await?;
+        let session_key = self.session_key().await?;
 
         // Build approval transaction for simplified seal_approve
         let mut builder = ProgrammableTransactionBuilder::new();
@@ -276,6 +399,14 @@ impl PrintJobDecryptor {
         let id_hex = resource_id.strip_prefix("0x").unwrap_or(resource_id);
         let id_bytes = hex::decode(id_hex)
             .map_err(|e| anyhow::anyhow!("Failed to decode hex ID '{}': {}", id_hex, e))?;
+        // Key servers derive the key from package id + this id; any other id cannot decrypt.
+        if encrypted.id != id_bytes {
+            return Err(anyhow::anyhow!(
+                "Seal ciphertext was encrypted for id 0x{}, but the Sculpt's seal_resource_id is 0x{}",
+                hex::encode(&encrypted.id),
+                id_hex
+            ));
+        }
         
         // Argument 0: _id (vector<u8>)
         let id_arg = builder.pure(id_bytes)?;
@@ -316,17 +447,24 @@ impl PrintJobDecryptor {
 
         let approve_ptb: ProgrammableTransaction = builder.finish();
 
-        // Decrypt with detailed error handling
-        let plaintext = self.seal_client
-            .decrypt_object_bytes(
+        let timeout = Duration::from_secs(self.key_servers.timeout_secs);
+        let decrypted = tokio::time::timeout(
+            timeout,
+            self.seal_client.decrypt_object_bytes(
                 &bcs::to_bytes(&encrypted)?,
                 approve_ptb,
                 &session_key,
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Seal SDK decryption failed: {}", e))?;
-
-        Ok(plaintext)
+            ),
+        )
+        .await;
+        let error = match decrypted {
+            Ok(Ok(plaintext)) => return Ok(plaintext),
+            Ok(Err(e)) => format!("Seal SDK decryption failed: {}", e),
+            Err(_) => format!("Seal key servers did not answer within {} s (seal timeout_secs)", timeout.as_secs()),
+        };
+        // The SDK reports one error for all key servers; probe the configured ones to name the culprit.
+        let checks = key_servers::check_all(&key_servers::configured(&services, &self.key_servers), timeout).await;
+        Err(anyhow::anyhow!("{}{}", error, key_servers::report(&checks)))
     }
 
     /// Decrypt already-downloaded Walrus ciphertext using printer + PrinterCap + on-chain PrintJob (`eureka::seal_approve`).
@@ -352,6 +490,7 @@ impl PrintJobDecryptor {
     /// Complete PrintJob-based decryption flow
     pub async fn decrypt_printjob_sculpt(
         &self,
+        walrus: &WalrusClient,
         printer_id: SuiObjectID,
         printer_cap_id: SuiObjectID,
     ) -> Result<Vec<u8>> {
@@ -369,7 +508,7 @@ impl PrintJobDecryptor {
         };
 
         // Step 4: Download and parse encrypted data
-        let encrypted_data = self.download_encrypted_data(&encrypted_blob_id).await?;
+        let encrypted_data = self.download_encrypted_data(walrus, &encrypted_blob_id).await?;
         let encrypted_object = self.parse_encrypted_object(&encrypted_data)?;
 
         // Step 5: Decrypt using Seal SDK
@@ -384,3 +523,28 @@ impl PrintJobDecryptor {
         Ok(plaintext)
     }
 }
+
+fn move_fields(content: Option<&SuiParsedData>) -> Option<&BTreeMap<String, SuiMoveValue>> {
+    match content? {
+        SuiParsedData::MoveObject(obj) => match &obj.fields {
+            SuiMoveStruct::WithFields(fields) => Some(fields),
+            SuiMoveStruct::WithTypes { fields, .. } => Some(fields),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The first `url` string in `fields` or the structs and enum variants nested in them (a dynamic
+/// field's `value`, `KeyServerV2`'s server type).
+fn find_url(fields: &BTreeMap<String, SuiMoveValue>) -> Option<String> {
+    if let Some(SuiMoveValue::String(url)) = fields.get("url") {
+        return Some(url.clone());
+    }
+    fields.values().find_map(|value| match value {
+        SuiMoveValue::Struct(SuiMoveStruct::WithFields(nested))
+        | SuiMoveValue::Struct(SuiMoveStruct::WithTypes { fields: nested, .. }) => find_url(nested),
+        SuiMoveValue::Variant(variant) => find_url(&variant.fields),
+        _ => None,
+    })
+}
@@ -0,0 +1,49 @@
+//! One Seal decryptor for the life of the app. Building a [`PrintJobDecryptor`] connects a Sui
+//! client and loads the wallet; its session key costs a signature. Both are kept here and reused by
+//! every encrypted job, rebuilt only when the network or Eureka package changes. A timer
+//! (`refresh`) renews the session key before it expires, so a job never waits for a signature.
+
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::PrintJobDecryptor;
+use crate::config::SealNetworkConfig;
+
+#[derive(Default)]
+pub struct SealService {
+    current: Mutex<Option<Arc<PrintJobDecryptor>>>,
+}
+
+impl SealService {
+    /// The decryptor for `rpc_url`, `eureka_package_id` and the network's `seal` settings, built on
+    /// first use and after a network switch.
+    pub async fn decryptor(
+        &self,
+        rpc_url: &str,
+        eureka_package_id: &str,
+        key_servers: &SealNetworkConfig,
+    ) -> Result<Arc<PrintJobDecryptor>> {
+        let mut current = self.current.lock().await;
+        if let Some(decryptor) = current.as_ref() {
+            if decryptor.serves(rpc_url, eureka_package_id, key_servers) {
+                return Ok(Arc::clone(decryptor));
+            }
+        }
+        let decryptor = Arc::new(
+            PrintJobDecryptor::new(rpc_url.to_string(), eureka_package_id, key_servers.clone()).await?,
+        );
+        *current = Some(Arc::clone(&decryptor));
+        Ok(decryptor)
+    }
+
+    /// Renew the cached session key if it is close to expiry. Nothing is signed before the first
+    /// encrypted job.
+    pub async fn refresh(&self) -> Result<bool> {
+        let decryptor = self.current.lock().await.clone();
+        match decryptor {
+            Some(decryptor) => decryptor.refresh_session_key().await,
+            None => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::{existing_gcode, require_profile, Slicer};
+
+/// Headless CuraEngine: a `.def.json` machine definition via `-j`, settings as `-s key=value`.
+/// Definitions it inherits from (`fdmprinter`, `creality_base`, …) are looked up next to the
+/// profile through `CURA_ENGINE_SEARCH_PATH`.
+pub struct CuraEngine {
+    executable: PathBuf,
+    definition: PathBuf,
+    settings: BTreeMap<String, String>,
+}
+
+impl CuraEngine {
+    pub fn new(executable: PathBuf, definition: PathBuf, settings: BTreeMap<String, String>) -> Result<Self> {
+        require_profile(&definition, "CuraEngine", ".def.json")?;
+        Ok(Self {
+            executable,
+            definition,
+            settings,
+        })
+    }
+}
+
+impl Slicer for CuraEngine {
+    fn name(&self) -> &'static str {
+        "CuraEngine"
+    }
+
+    fn command(&self, model: &Path, output: &Path, _workdir: &Path) -> Command {
+        let mut cmd = Command::new(&self.executable);
+        if let Some(dir) = self.definition.parent() {
+            cmd.env("CURA_ENGINE_SEARCH_PATH", dir);
+        }
+        cmd.arg("slice").arg("-v").arg("-j").arg(&self.definition);
+        for (key, value) in &self.settings {
+            cmd.arg("-s").arg(format!("{}={}", key, value));
+        }
+        cmd.arg("-l").arg(model).arg("-o").arg(output);
+        cmd
+    }
+
+    fn locate_output(&self, output: &Path, _workdir: &Path) -> Result<PathBuf> {
+        existing_gcode(output, self.name())
+    }
+}
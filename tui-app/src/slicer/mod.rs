@@ -0,0 +1,161 @@
+//! Slicer backends. Each one knows its CLI, the profile format it accepts and where it leaves the
+//! G-code; callers only deal with [`Slicer`] and the [`SlicerConfig`] that picks one.
+
+mod cura;
+mod orca;
+mod prusa;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::process::Command;
+
+use crate::cache::StableHasher;
+use crate::config::SlicerConfig;
+use cura::CuraEngine;
+use orca::OrcaSlicer;
+use prusa::PrusaSlicer;
+
+pub trait Slicer: Send + Sync {
+    /// For logs and error messages.
+    fn name(&self) -> &'static str;
+
+    /// Command that slices `model` into `output`. `workdir` is an empty directory the backend may
+    /// use when its CLI cannot write to an exact file name.
+    fn command(&self, model: &Path, output: &Path, workdir: &Path) -> Command;
+
+    /// Where the G-code ended up after the command exited successfully.
+    fn locate_output(&self, output: &Path, workdir: &Path) -> Result<PathBuf>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlicerBackend {
+    #[default]
+    Prusa,
+    Orca,
+    SuperSlicer,
+    Cura,
+}
+
+impl FromStr for SlicerBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "prusa" | "prusaslicer" | "prusa-slicer" => Ok(Self::Prusa),
+            "orca" | "orcaslicer" | "orca-slicer" => Ok(Self::Orca),
+            "superslicer" | "super-slicer" => Ok(Self::SuperSlicer),
+            "cura" | "curaengine" => Ok(Self::Cura),
+            other => Err(format!(
+                "unknown slicer '{}' (expected prusa, orca, superslicer or cura)",
+                other
+            )),
+        }
+    }
+}
+
+/// Build the configured backend. `default_ini` is the bundled PrusaSlicer-format profile, used by
+/// the Prusa-style backends when no profile is configured.
+pub fn from_config(config: &SlicerConfig, default_ini: &Path) -> Result<Box<dyn Slicer>> {
+    let executable = |default: &str| {
+        config
+            .executable
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(default))
+    };
+    let profile = config.profile.clone();
+    let settings = config.settings.clone();
+    Ok(match config.backend {
+        SlicerBackend::Prusa => Box::new(PrusaSlicer::new(
+            "PrusaSlicer",
+            executable("prusa-slicer"),
+            profile.unwrap_or_else(|| default_ini.to_path_buf()),
+            settings,
+        )?),
+        SlicerBackend::SuperSlicer => Box::new(PrusaSlicer::new(
+            "SuperSlicer",
+            executable("superslicer"),
+            profile.unwrap_or_else(|| default_ini.to_path_buf()),
+            settings,
+        )?),
+        SlicerBackend::Orca => Box::new(OrcaSlicer::new(
+            executable("orca-slicer"),
+            profile.ok_or_else(|| anyhow!("orca slicer needs slicer.profile (a directory of .json presets)"))?,
+            settings,
+        )?),
+        SlicerBackend::Cura => Box::new(CuraEngine::new(
+            executable("CuraEngine"),
+            profile.ok_or_else(|| anyhow!("cura slicer needs slicer.profile (a .def.json machine definition)"))?,
+            settings,
+        )?),
+    })
+}
+
+/// Hex digest of everything besides the model that decides the G-code: backend, binary, profile
+/// contents (every file of a preset directory) and overrides. Keys the G-code cache.
+pub fn fingerprint(config: &SlicerConfig, default_ini: &Path) -> Result<String> {
+    let mut hasher = StableHasher::default();
+    hasher.write(format!("{:?}", config.backend).as_bytes());
+    if let Some(executable) = &config.executable {
+        hasher.write(executable.as_os_str().as_encoded_bytes());
+    }
+    for (key, value) in &config.settings {
+        hasher.write(key.as_bytes());
+        hasher.write(b"=");
+        hasher.write(value.as_bytes());
+        hasher.write(b"\n");
+    }
+    let profile = config.profile.as_deref().unwrap_or(default_ini);
+    let mut files = vec![profile.to_path_buf()];
+    if profile.is_dir() {
+        files = std::fs::read_dir(profile)
+            .with_context(|| format!("read {}", profile.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file())
+            .collect();
+        files.sort();
+    }
+    for file in files {
+        hasher.write(file.file_name().unwrap_or_default().as_encoded_bytes());
+        hasher.write(&std::fs::read(&file).with_context(|| format!("read {}", file.display()))?);
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// The G-code file, provided the slicer actually wrote something.
+fn existing_gcode(path: &Path, slicer: &str) -> Result<PathBuf> {
+    match std::fs::metadata(path) {
+        Ok(m) if m.len() > 0 => Ok(path.to_path_buf()),
+        Ok(_) => bail!("{} wrote an empty file: {}", slicer, path.display()),
+        Err(_) => bail!("{} produced no G-code at {}", slicer, path.display()),
+    }
+}
+
+/// The profile exists and has the file suffix the backend reads (`.ini`, `.def.json`).
+fn require_profile(profile: &Path, slicer: &str, suffix: &str) -> Result<()> {
+    if !profile.exists() {
+        bail!("{} profile not found: {}", slicer, profile.display());
+    }
+    if !profile.to_string_lossy().to_ascii_lowercase().ends_with(suffix) {
+        bail!("{} expects a {} profile, got {}", slicer, suffix, profile.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_names() {
+        assert_eq!("PrusaSlicer".parse(), Ok(SlicerBackend::Prusa));
+        assert_eq!("orca-slicer".parse(), Ok(SlicerBackend::Orca));
+        assert_eq!("CuraEngine".parse(), Ok(SlicerBackend::Cura));
+        assert!("slic3r".parse::<SlicerBackend>().is_err());
+        let config: SlicerConfig = serde_yaml::from_str("backend: superslicer").unwrap();
+        assert_eq!(config.backend, SlicerBackend::SuperSlicer);
+    }
+}
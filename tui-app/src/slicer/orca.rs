@@ -0,0 +1,80 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::{existing_gcode, Slicer};
+
+/// OrcaSlicer's CLI takes JSON presets (machine, process, filament) and writes `plate_<n>.gcode`
+/// into `--outputdir` rather than to a given file name.
+pub struct OrcaSlicer {
+    executable: PathBuf,
+    machine: PathBuf,
+    process: PathBuf,
+    filament: PathBuf,
+}
+
+impl OrcaSlicer {
+    /// `profile` is a directory holding `machine.json` (or `printer.json`), `process.json` and
+    /// `filament.json`, as exported from OrcaSlicer.
+    pub fn new(executable: PathBuf, profile: PathBuf, settings: BTreeMap<String, String>) -> Result<Self> {
+        if !profile.is_dir() {
+            bail!(
+                "OrcaSlicer profile must be a directory with machine.json, process.json and filament.json: {}",
+                profile.display()
+            );
+        }
+        if !settings.is_empty() {
+            bail!("OrcaSlicer does not take individual settings on the command line; put them in the presets");
+        }
+        let preset = |names: &[&str]| -> Result<PathBuf> {
+            names
+                .iter()
+                .map(|n| profile.join(n))
+                .find(|p| p.is_file())
+                .ok_or_else(|| anyhow::anyhow!("{} missing in {}", names.join(" / "), profile.display()))
+        };
+        Ok(Self {
+            machine: preset(&["machine.json", "printer.json"])?,
+            process: preset(&["process.json"])?,
+            filament: preset(&["filament.json"])?,
+            executable,
+        })
+    }
+}
+
+impl Slicer for OrcaSlicer {
+    fn name(&self) -> &'static str {
+        "OrcaSlicer"
+    }
+
+    fn command(&self, model: &Path, _output: &Path, workdir: &Path) -> Command {
+        let mut cmd = Command::new(&self.executable);
+        cmd.arg("--arrange")
+            .arg("1")
+            .arg("--slice")
+            .arg("0")
+            .arg("--load-settings")
+            .arg(format!("{};{}", self.machine.display(), self.process.display()))
+            .arg("--load-filaments")
+            .arg(&self.filament)
+            .arg("--outputdir")
+            .arg(workdir)
+            .arg(model);
+        cmd
+    }
+
+    /// The first plate's G-code, moved to `output`.
+    fn locate_output(&self, output: &Path, workdir: &Path) -> Result<PathBuf> {
+        let mut plates: Vec<PathBuf> = std::fs::read_dir(workdir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|x| x.eq_ignore_ascii_case("gcode")))
+            .collect();
+        plates.sort();
+        let Some(plate) = plates.first() else {
+            bail!("OrcaSlicer produced no G-code in {}", workdir.display());
+        };
+        std::fs::rename(plate, output).or_else(|_| std::fs::copy(plate, output).map(|_| ()))?;
+        existing_gcode(output, self.name())
+    }
+}
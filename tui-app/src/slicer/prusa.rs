@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::{existing_gcode, require_profile, Slicer};
+
+/// PrusaSlicer, and SuperSlicer which kept its CLI: an `.ini` config bundle via `--load`, settings
+/// as `--key value`, and `--output` honored exactly.
+pub struct PrusaSlicer {
+    name: &'static str,
+    executable: PathBuf,
+    profile: PathBuf,
+    settings: BTreeMap<String, String>,
+}
+
+impl PrusaSlicer {
+    pub fn new(
+        name: &'static str,
+        executable: PathBuf,
+        profile: PathBuf,
+        settings: BTreeMap<String, String>,
+    ) -> Result<Self> {
+        require_profile(&profile, name, ".ini")?;
+        Ok(Self {
+            name,
+            executable,
+            profile,
+            settings,
+        })
+    }
+}
+
+impl Slicer for PrusaSlicer {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn command(&self, model: &Path, output: &Path, _workdir: &Path) -> Command {
+        let mut cmd = Command::new(&self.executable);
+        cmd.arg("--export-gcode").arg("--load").arg(&self.profile);
+        for (key, value) in &self.settings {
+            cmd.arg(format!("--{}", key)).arg(value);
+        }
+        cmd.arg("--output").arg(output).arg(model);
+        cmd
+    }
+
+    fn locate_output(&self, output: &Path, _workdir: &Path) -> Result<PathBuf> {
+        existing_gcode(output, self.name)
+    }
+}
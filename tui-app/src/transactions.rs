@@ -1,38 +1,155 @@
 use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 use sui_crypto::ed25519::Ed25519PrivateKey;
 use sui_crypto::SuiSigner;
+use sui_rpc::proto::sui::rpc::v2::execution_error::ErrorDetails;
+use sui_rpc::proto::sui::rpc::v2::BalanceChange;
 use sui_rpc::proto::sui::rpc::v2::ExecuteTransactionRequest;
+use sui_rpc::proto::sui::rpc::v2::ExecutedTransaction;
+use sui_rpc::proto::sui::rpc::v2::ExecutionError;
 use sui_rpc::proto::sui::rpc::v2::GetObjectRequest;
+use sui_rpc::proto::sui::rpc::v2::SimulateTransactionRequest;
 use sui_rpc::Client as GrpcClient;
 use sui_sdk_types::Address;
 use sui_sdk_types::Identifier;
+use sui_sdk_types::ObjectReference;
 use sui_transaction_builder::{
     Argument, Error as TxBuilderError, Function, ObjectInput, TransactionBuilder as TxBuilder,
 };
 use tokio::sync::Mutex;
 use tokio::time::timeout;
 
-use crate::constants::{GAS_BUDGET, SUI_CLOCK_OBJECT_ID};
-use crate::wallet::read_mask;
+use crate::config::GasConfig;
+use crate::constants::SUI_CLOCK_OBJECT_ID;
+use crate::eureka_error::EurekaError;
+use crate::wallet::{list_sui_coins, merge_plan, read_mask, select_gas_coins, SuiCoin};
 use crate::utils::NetworkState;
 
 const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
 const OBJECT_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Computation units added before the margin, as the Sui SDKs do, so a call whose cost shifts
+/// slightly between simulation and execution still fits.
+const GAS_SAFE_OVERHEAD: u64 = 1000;
+/// Budget a transaction is first simulated with, in MIST, when the cap and the wallet allow that
+/// much. It only has to cover the simulation; the signed budget comes from its result.
+const SIMULATION_BUDGET: u64 = 10_000_000;
 
-fn format_tx_builder_error(e: TxBuilderError) -> String {
-    match e {
+/// Budget each `eureka::*` function was last signed with in this process.
+static SIGNED_BUDGETS: std::sync::Mutex<BTreeMap<String, u64>> = std::sync::Mutex::new(BTreeMap::new());
+
+/// Gas a print job needs from start to payout, for the balance warning.
+pub fn job_lifecycle_reserve(gas: &GasConfig) -> u64 {
+    let signed = SIGNED_BUDGETS.lock().unwrap_or_else(|e| e.into_inner());
+    lifecycle_reserve(&signed, gas)
+}
+
+/// The budgets starting a job (the kiosk variant when it costs more), completing and transferring
+/// it were last signed with. A call not signed yet counts at the budget it is first simulated with.
+fn lifecycle_reserve(signed: &BTreeMap<String, u64>, gas: &GasConfig) -> u64 {
+    let budget = |function: &str| {
+        signed
+            .get(function)
+            .copied()
+            .unwrap_or_else(|| SIMULATION_BUDGET.min(gas.max_budget_for(function)))
+    };
+    budget("start_print_job").max(budget("start_print_job_from_kiosk"))
+        + budget("complete_print_job")
+        + budget("transfer_completed_print_job")
+}
+
+/// `tb.build` simulates the transaction as well, and reports an abort only as text. That text is
+/// decoded into [`EurekaError`] like [`execution_failure`] does, so callers can still `downcast_ref`.
+fn build_failure(e: TxBuilderError) -> anyhow::Error {
+    let text = match e {
         TxBuilderError::Input(msg) => msg,
         TxBuilderError::SimulationFailure(s) => s.to_string(),
         other => format!("{:?}", other),
+    };
+    match move_abort_in(&text) {
+        Some((module, function, code)) => EurekaError::from_abort(module, function, code).into(),
+        None => anyhow!("PTB build/simulate: {}", text),
+    }
+}
+
+/// `(module, function, code)` of a `MoveAbort(MoveLocation { module: ModuleId { .., name:
+/// Identifier("eureka") }, .., function_name: Some("start_print_job") }, 2)` in `text`.
+fn move_abort_in(text: &str) -> Option<(&str, &str, u64)> {
+    let abort = &text[text.find("MoveAbort(")?..];
+    let module = quoted_after(abort, "name: Identifier(\"")?;
+    let function = quoted_after(abort, "function_name: Some(\"").unwrap_or("");
+    let location_end = abort.find("function_name: ")?;
+    let (_, code) = abort[location_end..].split_once("}, ")?;
+    let code = code.split(')').next()?.trim().parse().ok()?;
+    Some((module, function, code))
+}
+
+fn quoted_after<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = text.find(prefix)? + prefix.len();
+    let len = text[start..].find('"')?;
+    Some(&text[start..start + len])
+}
+
+/// Simulated cost of a transaction and the budget it is signed with, in MIST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// Computation plus storage minus the storage rebate.
+    pub cost: u64,
+    pub budget: u64,
+}
+
+/// Budget from simulated `computation`, `storage` and `rebate`: the larger of the net cost and the
+/// computation cost (a rebate is only paid after the computation is covered), plus the overhead,
+/// plus `margin_pct`; refused above `max_budget`.
+fn gas_estimate(
+    (computation, storage, rebate): (u64, u64, u64),
+    gas_price: u64,
+    margin_pct: u64,
+    max_budget: u64,
+) -> Result<GasEstimate> {
+    let cost = (computation + storage).saturating_sub(rebate);
+    let base = cost.max(computation) + GAS_SAFE_OVERHEAD * gas_price;
+    let budget = base + base * margin_pct / 100;
+    if budget > max_budget {
+        return Err(anyhow!(
+            "estimated gas budget {} MIST exceeds the cap of {} MIST (gas.max_budget / gas.max_budget_by_call)",
+            budget,
+            max_budget
+        ));
     }
+    Ok(GasEstimate { cost, budget })
+}
+
+/// Budget to simulate with when `coins` hold `held` MIST: enough for any ordinary call, but never
+/// more than the wallet holds, so the cap does not decide how much SUI a wallet needs.
+fn simulation_budget(max_budget: u64, held: u64) -> u64 {
+    SIMULATION_BUDGET.min(max_budget).min(held)
+}
+
+/// The estimate for a simulated run that `used` (computation, storage, rebate), and the coins out
+/// of `coins` that pay its budget.
+fn budget_gas(
+    coins: &[SuiCoin],
+    used: (u64, u64, u64),
+    gas_price: u64,
+    margin_pct: u64,
+    max_budget: u64,
+) -> Result<(GasEstimate, Vec<SuiCoin>)> {
+    let estimate = gas_estimate(used, gas_price, margin_pct, max_budget)?;
+    let gas = select_gas_coins(coins, estimate.budget)?;
+    Ok((estimate, gas))
+}
+
+fn held(coins: &[SuiCoin]) -> u64 {
+    coins.iter().map(|c| c.balance).sum()
 }
 
 pub struct TransactionExecutor {
     rpc: Arc<Mutex<GrpcClient>>,
     signer: Ed25519PrivateKey,
     sender: Address,
+    gas: GasConfig,
 }
 
 impl TransactionExecutor {
@@ -41,6 +158,7 @@ impl TransactionExecutor {
             rpc,
             signer,
             sender,
+            gas: GasConfig::default(),
         }
     }
 
@@ -102,6 +220,12 @@ impl TransactionExecutor {
     }
 
     async fn sign_and_execute(&self, transaction: sui_sdk_types::Transaction) -> Result<String> {
+        let executed = self.execute(transaction).await?;
+        digest_of(&executed)
+    }
+
+    /// Sign and execute `transaction`; its effects and balance changes once it succeeded.
+    async fn execute(&self, transaction: sui_sdk_types::Transaction) -> Result<ExecutedTransaction> {
         let sig = self
             .signer
             .sign_transaction(&transaction)
@@ -125,14 +249,42 @@ impl TransactionExecutor {
 
         let inner = response.into_inner();
         if !inner.transaction().effects().status().success() {
-            let err = inner.transaction().effects().status().error().clone();
-            return Err(anyhow!("Transaction failed: {:?}", err));
+            return Err(execution_failure("Transaction failed", inner.transaction().effects().status().error()));
         }
-        inner
-            .transaction()
-            .digest_opt()
-            .map(|s| s.to_string())
-            .ok_or_else(|| anyhow!("missing digest in response"))
+        Ok(inner.transaction().clone())
+    }
+
+    /// Gas charged by a simulated run of `transaction`: (computation, storage, rebate).
+    async fn simulate_gas(&self, transaction: &sui_sdk_types::Transaction) -> Result<(u64, u64, u64)> {
+        let fut = async {
+            let mut c = self.rpc.lock().await;
+            let resp = c
+                .execution_client()
+                .simulate_transaction(
+                    SimulateTransactionRequest::new(transaction.clone().into())
+                        .with_read_mask(read_mask("transaction.effects.status,transaction.effects.gas_used")),
+                )
+                .await
+                .map_err(|e| anyhow!("simulate: {}", e))?
+                .into_inner();
+            let effects = resp.transaction().effects();
+            if !effects.status().success() {
+                return Err(execution_failure("Simulation failed", effects.status().error()));
+            }
+            let gas = effects.gas_used();
+            Ok((gas.computation_cost(), gas.storage_cost(), gas.storage_rebate()))
+        };
+        timeout(TRANSACTION_TIMEOUT, fut)
+            .await
+            .map_err(|_| anyhow!("Transaction simulation timeout"))?
+    }
+
+    fn collect_args(object_inputs: Vec<ObjectInput>, pure_args: Vec<Vec<u8>>) -> Vec<EurekaPtbArg> {
+        object_inputs
+            .into_iter()
+            .map(EurekaPtbArg::Object)
+            .chain(pure_args.into_iter().map(EurekaPtbArg::Pure))
+            .collect()
     }
 
     async fn run_eureka_ptb(
@@ -142,11 +294,7 @@ impl TransactionExecutor {
         object_inputs: Vec<ObjectInput>,
         pure_args: Vec<Vec<u8>>,
     ) -> Result<String> {
-        let args: Vec<EurekaPtbArg> = object_inputs
-            .into_iter()
-            .map(EurekaPtbArg::Object)
-            .chain(pure_args.into_iter().map(EurekaPtbArg::Pure))
-            .collect();
+        let args = Self::collect_args(object_inputs, pure_args);
         self.run_eureka_ptb_ordered(package, function, args).await
     }
 
@@ -157,9 +305,25 @@ impl TransactionExecutor {
         function: &str,
         args: Vec<EurekaPtbArg>,
     ) -> Result<String> {
-        let mut tb = TxBuilder::new();
-        tb.set_sender(self.sender);
-        tb.set_gas_budget(GAS_BUDGET);
+        let (tx, estimate) = self.prepare_eureka_ptb(package, function, args).await?;
+        let digest = self.sign_and_execute(tx).await?;
+        SIGNED_BUDGETS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(function.to_string(), estimate.budget);
+        Ok(digest)
+    }
+
+    /// The call, budgeted from its simulation and paid with coins that cover that budget.
+    async fn prepare_eureka_ptb(
+        &self,
+        package: Address,
+        function: &str,
+        args: Vec<EurekaPtbArg>,
+    ) -> Result<(sui_sdk_types::Transaction, GasEstimate)> {
+        let max_budget = self.gas.max_budget_for(function);
+        let coins = self.sui_coins().await?;
+        let mut tb = self.tx_builder();
 
         let mut call_args: Vec<Argument> = Vec::with_capacity(args.len());
         for a in args {
@@ -175,17 +339,152 @@ impl TransactionExecutor {
             Identifier::new(function)?,
         );
         tb.move_call(f, call_args);
+        self.build_with_estimate(tb, function, &coins, max_budget).await
+    }
+
+    /// Merge up to `MAX_MERGE_COINS` dust coins into the gas coin. Digest and number of coins
+    /// merged away; `None` when there is no dust.
+    async fn merge_dust_coins(&self) -> Result<Option<(String, usize)>> {
+        let Some((tx, _, merged)) = self.prepare_merge_dust_coins().await? else {
+            return Ok(None);
+        };
+        let digest = self.sign_and_execute(tx).await?;
+        Ok(Some((digest, merged)))
+    }
 
-        let tx = {
+    /// The merge transaction, its estimate and the number of coins it merges away.
+    async fn prepare_merge_dust_coins(&self) -> Result<Option<(sui_sdk_types::Transaction, GasEstimate, usize)>> {
+        let max_budget = self.gas.max_budget_for("merge_coins");
+        let coins = self.sui_coins().await?;
+        let budget = simulation_budget(max_budget, held(&coins));
+        let Some((gas, dust)) = merge_plan(&coins, budget, self.gas.dust_threshold)
+            .map_err(|e| anyhow!("merge_coins: {}", e))?
+        else {
+            return Ok(None);
+        };
+        let mut tb = self.tx_builder();
+        let target = tb.gas();
+        let sources: Vec<Argument> = dust.iter().map(|c| tb.object(coin_input(c))).collect();
+        tb.merge_coins(target, sources);
+        // Gas is paid from the plan's gas coins only, so no dust coin is both paid with and merged.
+        let (tx, estimate) = self.build_with_estimate(tb, "merge_coins", &gas, max_budget).await?;
+        // Extra gas coins are merged into the first one as well.
+        let merged = dust.len() + tx.gas_payment.objects.len() - 1;
+        Ok(Some((tx, estimate, merged)))
+    }
+
+    async fn sui_coins(&self) -> Result<Vec<SuiCoin>> {
+        timeout(OBJECT_FETCH_TIMEOUT, list_sui_coins(&self.rpc, self.sender))
+            .await
+            .map_err(|_| anyhow!("Timeout listing SUI coins"))?
+    }
+
+    fn tx_builder(&self) -> TxBuilder {
+        let mut tb = TxBuilder::new();
+        tb.set_sender(self.sender);
+        tb
+    }
+
+    /// Build paying with `coins` (chosen by [`select_gas_coins`], so the SDK does not pick coins
+    /// itself), simulate at [`simulation_budget`], then sign for the estimate: coins are picked
+    /// again for the estimated budget, and `max_budget` only refuses a transaction that costs more.
+    async fn build_with_estimate(
+        &self,
+        mut tb: TxBuilder,
+        label: &str,
+        coins: &[SuiCoin],
+        max_budget: u64,
+    ) -> Result<(sui_sdk_types::Transaction, GasEstimate)> {
+        let label_err = |e: anyhow::Error| anyhow!("{}: {}", label, e);
+        let affordable = max_budget.min(held(coins));
+        let budget = simulation_budget(max_budget, held(coins));
+        let gas = select_gas_coins(coins, budget).map_err(label_err)?;
+        tb.set_gas_budget(budget);
+        tb.add_gas_objects(gas.iter().map(coin_input));
+        let mut tx = {
             let mut c = self.rpc.lock().await;
-            tb.build(&mut *c)
-                .await
-                .map_err(|e| anyhow!("PTB build/simulate: {}", format_tx_builder_error(e)))?
+            tb.build(&mut *c).await.map_err(build_failure)?
+        };
+        let used = match self.simulate_gas(&tx).await {
+            // Most likely out of gas at the provisional budget: once more with all the wallet can pay.
+            Err(e) if budget < affordable && e.downcast_ref::<EurekaError>().is_none() => {
+                let gas = select_gas_coins(coins, affordable).map_err(label_err)?;
+                pay_with(&mut tx, &gas, affordable);
+                self.simulate_gas(&tx).await?
+            }
+            used => used?,
         };
-        self.sign_and_execute(tx).await
+        let (estimate, gas) = budget_gas(coins, used, tx.gas_payment.price, self.gas.margin_pct, max_budget)
+            .map_err(label_err)?;
+        pay_with(&mut tx, &gas, estimate.budget);
+        Ok((tx, estimate))
+    }
+}
+
+/// A Move abort as [`EurekaError`] (so callers can `downcast_ref` it), anything else as text.
+fn execution_failure(stage: &str, error: &ExecutionError) -> anyhow::Error {
+    if let Some(ErrorDetails::Abort(abort)) = &error.error_details {
+        let location = abort.location.as_ref();
+        let module = location.and_then(|l| l.module.as_deref()).unwrap_or("");
+        let function = location.and_then(|l| l.function_name.as_deref()).unwrap_or("");
+        if let Some(code) = abort.abort_code {
+            return EurekaError::from_abort(module, function, code).into();
+        }
+    }
+    match error.description.as_deref() {
+        Some(description) => anyhow!("{}: {}", stage, description),
+        None => anyhow!("{}: {:?}", stage, error),
     }
 }
 
+fn digest_of(executed: &ExecutedTransaction) -> Result<String> {
+    executed
+        .digest_opt()
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow!("missing digest in response"))
+}
+
+/// SUI that `withdraw_fees` moved to `sender`: the sender's SUI balance change, which is net of the
+/// gas it paid, plus that gas (computation, storage, rebate).
+fn withdrawn_fees(
+    balance_changes: &[BalanceChange],
+    sender: &Address,
+    (computation, storage, rebate): (u64, u64, u64),
+) -> Result<u128> {
+    let sui: Address = "0x2".parse().map_err(|e| anyhow!("0x2: {}", e))?;
+    let mut delta: i128 = 0;
+    for change in balance_changes {
+        let owner = change.address.as_deref().and_then(|a| a.parse::<Address>().ok());
+        let is_sui = change
+            .coin_type
+            .as_deref()
+            .and_then(|t| t.strip_suffix("::sui::SUI"))
+            .and_then(|a| a.parse::<Address>().ok())
+            == Some(sui);
+        if owner.as_ref() == Some(sender) && is_sui {
+            let amount = change.amount.as_deref().unwrap_or("0");
+            delta += amount
+                .parse::<i128>()
+                .map_err(|e| anyhow!("balance change {}: {}", amount, e))?;
+        }
+    }
+    let gas = computation as i128 + storage as i128 - rebate as i128;
+    u128::try_from(delta + gas).map_err(|_| anyhow!("withdraw_fees lowered the sender's SUI by more than its gas"))
+}
+
+fn coin_input(coin: &SuiCoin) -> ObjectInput {
+    ObjectInput::owned(coin.id, coin.version, coin.digest)
+}
+
+/// Replace the gas payment of a built transaction.
+fn pay_with(tx: &mut sui_sdk_types::Transaction, gas: &[SuiCoin], budget: u64) {
+    tx.gas_payment.objects = gas
+        .iter()
+        .map(|c| ObjectReference::new(c.id, c.version, c.digest))
+        .collect();
+    tx.gas_payment.budget = budget;
+}
+
 enum EurekaPtbArg {
     Object(ObjectInput),
     Pure(Vec<u8>),
@@ -213,6 +512,12 @@ impl TransactionBuilder {
         }
     }
 
+    /// Budgeting settings (`gas` in config.yaml); defaults otherwise.
+    pub fn with_gas(mut self, gas: GasConfig) -> Self {
+        self.executor.gas = gas;
+        self
+    }
+
     pub fn with_eureka_package(mut self, package_id: Address) -> Self {
         self.eureka_package_override = Some(package_id);
         self
@@ -313,6 +618,19 @@ impl TransactionBuilder {
             .await
     }
 
+    /// Simulated cost of a call, for showing before the user confirms it.
+    async fn estimate_eureka_call(
+        &self,
+        function: &str,
+        object_inputs: Vec<ObjectInput>,
+        pure_args: Vec<Vec<u8>>,
+    ) -> Result<GasEstimate> {
+        let package = self.resolve_eureka_package_id()?;
+        let args = TransactionExecutor::collect_args(object_inputs, pure_args);
+        let (_, estimate) = self.executor.prepare_eureka_ptb(package, function, args).await?;
+        Ok(estimate)
+    }
+
     async fn execute_eureka_call_ordered(
         &self,
         function: &str,
@@ -385,12 +703,67 @@ impl TransactionBuilder {
         printer_cap_id: Address,
         printer_id: Address,
     ) -> Result<String> {
+        let args = self.printer_cap_and_printer_args(printer_cap_id, printer_id).await?;
+        self.execute_eureka_call("update_printer_status", args, vec![])
+            .await
+    }
+
+    pub async fn estimate_update_printer_status(
+        &self,
+        printer_cap_id: Address,
+        printer_id: Address,
+    ) -> Result<GasEstimate> {
+        let args = self.printer_cap_and_printer_args(printer_cap_id, printer_id).await?;
+        self.estimate_eureka_call("update_printer_status", args, vec![])
+            .await
+    }
+
+    /// `(printer_cap, printer)`, the leading arguments of most printer-owner calls.
+    async fn printer_cap_and_printer_args(
+        &self,
+        printer_cap_id: Address,
+        printer_id: Address,
+    ) -> Result<Vec<ObjectInput>> {
         let cap_arg = self.create_printer_cap_arg(printer_cap_id).await?;
         let printer_arg = self.create_shared_object_arg(printer_id, true).await?;
-        self.execute_eureka_call("update_printer_status", vec![cap_arg, printer_arg], vec![])
+        Ok(vec![cap_arg, printer_arg])
+    }
+
+    /// `eureka::withdraw_fees`: moves the printer's fee pool, as it is when the transaction runs, to
+    /// the sender. Digest and the amount withdrawn, read from the sender's balance change.
+    pub async fn withdraw_fees(&self, printer_cap_id: Address, printer_id: Address) -> Result<(String, u128)> {
+        let args = self.printer_cap_and_printer_args(printer_cap_id, printer_id).await?;
+        let package = self.resolve_eureka_package_id()?;
+        let args = TransactionExecutor::collect_args(args, vec![]);
+        let (tx, _) = self.executor.prepare_eureka_ptb(package, "withdraw_fees", args).await?;
+        let executed = self.executor.execute(tx).await?;
+        let gas = executed.effects().gas_used();
+        let amount = withdrawn_fees(
+            &executed.balance_changes,
+            &self.executor.sender,
+            (gas.computation_cost(), gas.storage_cost(), gas.storage_rebate()),
+        )?;
+        Ok((digest_of(&executed)?, amount))
+    }
+
+    pub async fn estimate_withdraw_fees(&self, printer_cap_id: Address, printer_id: Address) -> Result<GasEstimate> {
+        let args = self.printer_cap_and_printer_args(printer_cap_id, printer_id).await?;
+        self.estimate_eureka_call("withdraw_fees", args, vec![])
             .await
     }
 
+    /// Merge the wallet's dust SUI coins (`gas.dust_threshold`) into one. Digest and number of
+    /// coins merged away; `None` when there is nothing to merge.
+    pub async fn merge_dust_coins(&self) -> Result<Option<(String, usize)>> {
+        self.executor.merge_dust_coins().await
+    }
+
+    /// Simulated cost of [`Self::merge_dust_coins`]; `None` when there is nothing to merge.
+    pub async fn estimate_merge_dust_coins(&self) -> Result<Option<GasEstimate>> {
+        let prepared = self.executor.prepare_merge_dust_coins().await?;
+        Ok(prepared.map(|(_, estimate, _)| estimate))
+    }
+
     pub async fn start_print_job(
         &self,
         printer_cap_id: Address,
@@ -546,3 +919,123 @@ impl TransactionBuilder {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_rpc::proto::sui::rpc::v2::{MoveAbort, MoveLocation};
+
+    #[test]
+    fn test_gas_estimate() {
+        // 1M computation, 2M storage, 1.5M rebate at 1000 MIST/unit overhead price.
+        let estimate = gas_estimate((1_000_000, 2_000_000, 1_500_000), 1000, 20, 100_000_000).unwrap();
+        assert_eq!(estimate.cost, 1_500_000);
+        assert_eq!(estimate.budget, (1_500_000 + 1_000_000) * 120 / 100);
+
+        // A rebate larger than the storage charge still leaves the computation budgeted.
+        let estimate = gas_estimate((1_000_000, 0, 3_000_000), 1000, 0, 100_000_000).unwrap();
+        assert_eq!(estimate.cost, 0);
+        assert_eq!(estimate.budget, 2_000_000);
+
+        assert!(gas_estimate((1_000_000, 0, 0), 1000, 0, 1_999_999).is_err());
+    }
+
+    #[test]
+    fn test_execution_failure() {
+        let abort = ExecutionError {
+            error_details: Some(ErrorDetails::Abort(MoveAbort {
+                abort_code: Some(2),
+                location: Some(MoveLocation {
+                    module: Some("eureka".to_string()),
+                    function_name: Some("create_and_assign_print_job_internal".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let err = execution_failure("Simulation failed", &abort);
+        assert_eq!(err.downcast_ref::<EurekaError>(), Some(&EurekaError::PrintJobExists));
+
+        let out_of_gas = ExecutionError {
+            description: Some("InsufficientGas".to_string()),
+            ..Default::default()
+        };
+        let err = execution_failure("Simulation failed", &out_of_gas);
+        assert!(err.downcast_ref::<EurekaError>().is_none());
+        assert_eq!(err.to_string(), "Simulation failed: InsufficientGas");
+    }
+
+    #[test]
+    fn test_move_abort_in() {
+        let text = "Transaction failed: MoveAbort(MoveLocation { module: ModuleId { address: 0x9, \
+                    name: Identifier(\"eureka\") }, function: 12, instruction: 40, \
+                    function_name: Some(\"create_and_assign_print_job_internal\") }, 2) in command 0";
+        assert_eq!(
+            move_abort_in(text),
+            Some(("eureka", "create_and_assign_print_job_internal", 2))
+        );
+        assert_eq!(move_abort_in("InsufficientGas"), None);
+    }
+
+    #[test]
+    fn test_withdrawn_fees() {
+        let sender: Address = "0xa1".parse().unwrap();
+        let change = |address: &str, coin_type: &str, amount: i128| BalanceChange {
+            address: Some(address.to_string()),
+            coin_type: Some(coin_type.to_string()),
+            amount: Some(amount.to_string()),
+        };
+        // 0.5 SUI withdrawn for 1.2M MIST of gas; the printer's pool and other coins do not count.
+        let changes = vec![
+            change("0x00a1", "0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI", 498_800_000),
+            change("0xa1", "0x9::usdc::USDC", -5),
+            change("0xb2", "0x2::sui::SUI", 7),
+        ];
+        let gas = (1_000_000, 2_000_000, 1_800_000);
+        assert_eq!(withdrawn_fees(&changes, &sender, gas).unwrap(), 500_000_000);
+        // An empty pool: only the gas left the wallet.
+        let gas_only = [change("0xa1", "0x2::sui::SUI", -1_000_000)];
+        assert_eq!(withdrawn_fees(&gas_only, &sender, (1_000_000, 0, 0)).unwrap(), 0);
+        assert!(withdrawn_fees(&[change("0xa1", "0x2::sui::SUI", -2_000_000)], &sender, (1_000_000, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn test_lifecycle_reserve() {
+        let gas = GasConfig::default();
+        let mut signed = BTreeMap::new();
+        assert_eq!(lifecycle_reserve(&signed, &gas), 3 * SIMULATION_BUDGET);
+
+        signed.insert("start_print_job".to_string(), 4_000_000);
+        signed.insert("start_print_job_from_kiosk".to_string(), 6_000_000);
+        signed.insert("complete_print_job".to_string(), 3_000_000);
+        signed.insert("transfer_completed_print_job".to_string(), 2_000_000);
+        assert_eq!(lifecycle_reserve(&signed, &gas), 11_000_000);
+    }
+
+    fn coin(n: u8, balance: u64) -> SuiCoin {
+        SuiCoin {
+            id: Address::new([n; 32]),
+            version: 1,
+            digest: sui_sdk_types::Digest::new([n; 32]),
+            balance,
+        }
+    }
+
+    #[test]
+    fn test_budget_below_cap() {
+        // 0.035 SUI: less than the 0.1 SUI cap, more than the call needs.
+        let coins = vec![coin(1, 30_000_000), coin(2, 5_000_000)];
+        let max_budget = 100_000_000;
+        assert!(select_gas_coins(&coins, max_budget).is_err());
+        assert_eq!(simulation_budget(max_budget, held(&coins)), SIMULATION_BUDGET);
+        assert_eq!(simulation_budget(max_budget, 4_000_000), 4_000_000);
+
+        let (estimate, gas) = budget_gas(&coins, (1_000_000, 2_000_000, 1_500_000), 1000, 20, max_budget).unwrap();
+        assert_eq!(estimate.budget, 3_000_000);
+        assert_eq!(gas, vec![coin(2, 5_000_000)]);
+
+        let err = budget_gas(&coins[1..], (10_000_000, 0, 0), 1000, 20, max_budget).unwrap_err();
+        assert!(err.to_string().contains("below the gas budget"));
+    }
+}
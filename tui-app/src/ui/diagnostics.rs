@@ -0,0 +1,153 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+use crate::app::App;
+use crate::walrus::{format_bytes, HostState};
+
+/// Walrus aggregator health and Seal key server reachability for the current network, drawn over
+/// the main view (D toggles it).
+pub fn draw_diagnostics(f: &mut Frame, app: &App) {
+    let popup = centered(f.size(), 90, 24);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(14), Constraint::Min(6)])
+        .split(popup);
+    let (area, seal_area) = (chunks[0], chunks[1]);
+    let title = format!(
+        " WALRUS AGGREGATORS ({}, {}) ",
+        app.network_state.get_current_network().to_uppercase(),
+        app.config.walrus.strategy
+    );
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let hosts = app.walrus_aggregators();
+    let order = app.walrus_health.order(&hosts, app.config.walrus.strategy);
+    let rows: Vec<Row> = order
+        .iter()
+        .map(|host| {
+            let stats = app.walrus_health.stats(host);
+            let (state, color) = match stats.state() {
+                HostState::Untried => ("untried", Color::DarkGray),
+                HostState::Healthy => ("healthy", Color::Green),
+                HostState::Degraded => ("degraded", Color::Yellow),
+                HostState::Down => ("down", Color::Red),
+            };
+            let latency = stats
+                .latency
+                .map(|l| format!("{} ms", l.as_millis()))
+                .unwrap_or_else(|| "-".to_string());
+            Row::new(vec![
+                Cell::from(host.trim_start_matches("https://").to_string()),
+                Cell::from(Span::styled(state, Style::default().fg(color))),
+                Cell::from(stats.successes.to_string()),
+                Cell::from(stats.failures.to_string()),
+                Cell::from(latency),
+                Cell::from(format_bytes(stats.bytes)),
+                Cell::from(stats.last_error.unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let header = Row::new(vec!["HOST", "STATE", "OK", "FAILED", "LATENCY", "RECEIVED", "LAST ERROR"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(40),
+        Constraint::Length(9),
+        Constraint::Length(4),
+        Constraint::Length(7),
+        Constraint::Length(9),
+        Constraint::Length(10),
+        Constraint::Min(10),
+    ];
+    let table = Table::new(rows, widths).header(header).block(block);
+
+    f.render_widget(Clear, popup);
+    f.render_widget(table, area);
+    draw_key_servers(f, app, seal_area);
+
+    let hint = Rect::new(popup.x + 1, popup.y + popup.height.saturating_sub(2), popup.width.saturating_sub(2), 1);
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("D", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(" close  ·  latency is the average time to first byte"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray))
+    .alignment(Alignment::Center);
+    f.render_widget(footer, hint);
+}
+
+/// Result of the key server probe started when the view opened.
+fn draw_key_servers(f: &mut Frame, app: &App, area: Rect) {
+    let config = app.seal_key_servers();
+    let threshold = config
+        .threshold
+        .map(|t| format!("threshold ≥ {}", t))
+        .unwrap_or_else(|| "ciphertext threshold".to_string());
+    let block = Block::default()
+        .title(format!(" SEAL KEY SERVERS ({}, timeout {} s) ", threshold, config.timeout_secs))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    if config.key_servers.is_empty() {
+        let note = Paragraph::new("None configured: the key servers named in each ciphertext are used as-is.")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(note, area);
+        return;
+    }
+
+    let rows: Vec<Row> = config
+        .key_servers
+        .iter()
+        .map(|server| {
+            let check = app
+                .key_server_checks
+                .iter()
+                .flatten()
+                .find(|c| c.url == server.url && c.object_id == server.object_id);
+            let (state, color, detail) = match check.map(|c| &c.result) {
+                None => ("checking", Color::DarkGray, String::new()),
+                Some(Ok(latency)) => ("reachable", Color::Green, format!("{} ms", latency.as_millis())),
+                Some(Err(e)) => ("failed", Color::Red, e.clone()),
+            };
+            Row::new(vec![
+                Cell::from(server.url.trim_start_matches("https://").to_string()),
+                Cell::from(crate::utils::shorten_id(&server.object_id)),
+                Cell::from(Span::styled(state, Style::default().fg(color))),
+                Cell::from(detail),
+            ])
+        })
+        .collect();
+    let header = Row::new(vec!["URL", "OBJECT", "STATE", "LATENCY / ERROR"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(40),
+        Constraint::Length(22),
+        Constraint::Length(10),
+        Constraint::Min(10),
+    ];
+    f.render_widget(Table::new(rows, widths).header(header).block(block), area);
+}
+
+/// A `width`% wide box of `height` rows in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let w = area.width * width / 100;
+    let h = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - w) / 2,
+        area.y + (area.height - h) / 2,
+        w,
+        h,
+    )
+}
@@ -1,6 +1,6 @@
 use ratatui::Frame;
 use crate::app::App;
-use super::{registration, main_view};
+use super::{registration, main_view, diagnostics};
 
 /// Main entry point for UI rendering
 /// Decides whether to show registration or main UI
@@ -9,5 +9,8 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         registration::draw_registration(f, app);
     } else {
         main_view::draw_main(f, app);
+        if app.show_diagnostics {
+            diagnostics::draw_diagnostics(f, app);
+        }
     }
 } 
\ No newline at end of file
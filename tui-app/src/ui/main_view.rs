@@ -8,7 +8,8 @@ use ratatui::{
 use std::time::{SystemTime, UNIX_EPOCH};
 use crate::app::print_job::TaskStatus;
 use crate::app::App;
-use crate::utils::format_sui_balance;
+use crate::utils::{format_sui_balance, format_sui_fee};
+use crate::wallet::dust_coins;
 use super::status_display::{render_online_active_task, render_offline_printer};
 use super::animations::{render_eureka_animation, render_tech_animation, render_ambient_noise};
 use super::ascii_arts::UiConstants;
@@ -198,8 +199,12 @@ fn render_network_status(f: &mut Frame, app: &App, area: Rect, primary_color: Co
 }
 
 fn render_printer_id(f: &mut Frame, app: &App, area: Rect, primary_color: Color, secondary_color: Color) {
+    let title = match &app.printer_firmware {
+        Some(firmware) => format!("PRINTER ID ─ {}", firmware.label().to_uppercase()),
+        None => "PRINTER ID".to_string(),
+    };
     let printer_block = Block::default()
-        .title("PRINTER ID")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(primary_color));
@@ -221,16 +226,34 @@ fn render_balance_and_rewards(f: &mut Frame, app: &App, area: Rect, primary_colo
         ])
         .split(area);
 
-    // SUI balance display
+    // SUI balance display. Yellow while it covers one more print job (start, complete, transfer)
+    // but not two, red once it cannot cover the next one.
+    let reserve = u128::from(crate::transactions::job_lifecycle_reserve(&app.config.gas));
+    let (balance_title, balance_color) = if app.sui_balance < reserve {
+        (format!("SUI BALANCE - LOW, job needs {}", format_sui_balance(reserve)), Some(Color::Red))
+    } else if app.sui_balance < 2 * reserve {
+        ("SUI BALANCE - 1 JOB LEFT".to_string(), Some(Color::Yellow))
+    } else {
+        ("SUI BALANCE".to_string(), None)
+    };
+    let dust = dust_coins(&app.sui_coins, app.config.gas.dust_threshold).len();
     let sui_block = Block::default()
-        .title("SUI BALANCE")
+        .title(balance_title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(primary_color));
-    
-    let sui_text = Paragraph::new(format_sui_balance(app.sui_balance))
+        .border_style(Style::default().fg(balance_color.unwrap_or(primary_color)));
+
+    let mut sui_line = format_sui_balance(app.sui_balance);
+    if app.is_merging_coins {
+        sui_line.push_str(" (merging...)");
+    } else if dust > 0 {
+        sui_line.push_str(&format!(" ({} coins, {} dust)", app.sui_coins.len(), dust));
+    } else if !app.sui_coins.is_empty() {
+        sui_line.push_str(&format!(" ({} coins)", app.sui_coins.len()));
+    }
+    let sui_text = Paragraph::new(sui_line)
         .block(sui_block)
-        .style(Style::default().fg(secondary_color))
+        .style(Style::default().fg(balance_color.unwrap_or(secondary_color)))
         .alignment(Alignment::Left);
     
     f.render_widget(sui_text, balance_reward_chunks[0]);
@@ -244,11 +267,13 @@ fn render_balance_and_rewards(f: &mut Frame, app: &App, area: Rect, primary_colo
     
     let reward_text = if app.is_harvesting {
         "Harvest? (Y/N)".to_string()
+    } else if app.is_withdrawing_fees {
+        "Withdrawing...".to_string()
     } else {
         app.harvestable_rewards.clone()
     };
     
-    let reward_style = if app.is_harvesting {
+    let reward_style = if app.is_harvesting || app.is_withdrawing_fees {
         Style::default().fg(Color::Yellow)
     } else {
         Style::default().fg(secondary_color)
@@ -441,7 +466,39 @@ fn render_help_controls(f: &mut Frame, app: &App, area: Rect, dim_color: Color,
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(dim_color));
     
-    let help_text = if app.is_confirming {
+    // Same precedence as the Y/N keys in main.rs: the e-stop prompt wins.
+    let help_text = if app.is_confirming_estop {
+        vec![
+            Line::from(vec![
+                Span::styled("EMERGENCY STOP (M112)? ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::styled("Y", Style::default().fg(Color::Yellow)),
+                Span::raw(": Halt printer now"),
+                Span::raw("  |  "),
+                Span::styled("N", Style::default().fg(Color::Yellow)),
+                Span::raw(": Cancel"),
+            ]),
+            // Without the emergency parser Marlin only sees M112 once the queued commands ahead of it run.
+            if app.printer_firmware.as_ref().and_then(|fw| fw.capability("EMERGENCY_PARSER")) == Some(false) {
+                Line::from(Span::styled(
+                    "Firmware has no EMERGENCY_PARSER: M112 waits behind queued moves/heating",
+                    Style::default().fg(Color::Red),
+                ))
+            } else {
+                Line::from("")
+            },
+        ]
+    } else if app.is_cancelling_print {
+        vec![
+            Line::from(vec![
+                Span::styled("Cancel print? ", Style::default().fg(Color::Yellow)),
+                Span::styled("Y", Style::default().fg(Color::Yellow)),
+                Span::raw(": Cool down & home XY"),
+                Span::raw("  |  "),
+                Span::styled("N", Style::default().fg(Color::Yellow)),
+                Span::raw(": Keep printing"),
+            ]),
+        ]
+    } else if app.is_confirming {
         vec![
             Line::from(vec![
                 Span::styled("Y", Style::default().fg(Color::Yellow)),
@@ -449,6 +506,8 @@ fn render_help_controls(f: &mut Frame, app: &App, area: Rect, dim_color: Color,
                 Span::raw("  |  "),
                 Span::styled("N", Style::default().fg(Color::Yellow)),
                 Span::raw(": Cancel"),
+                Span::raw("  |  "),
+                gas_estimate_span(app),
             ]),
         ]
     } else if app.is_harvesting {
@@ -459,6 +518,21 @@ fn render_help_controls(f: &mut Frame, app: &App, area: Rect, dim_color: Color,
                 Span::raw("  |  "),
                 Span::styled("N", Style::default().fg(Color::Yellow)),
                 Span::raw(": Cancel"),
+                Span::raw("  |  "),
+                gas_estimate_span(app),
+            ]),
+        ]
+    } else if app.is_confirming_merge {
+        vec![
+            Line::from(vec![
+                Span::styled("Merge dust coins? ", Style::default().fg(Color::Yellow)),
+                Span::styled("Y", Style::default().fg(Color::Yellow)),
+                Span::raw(": Confirm"),
+                Span::raw("  |  "),
+                Span::styled("N", Style::default().fg(Color::Yellow)),
+                Span::raw(": Cancel"),
+                Span::raw("  |  "),
+                gas_estimate_span(app),
             ]),
         ]
     } else if app.is_switching_network {
@@ -498,11 +572,26 @@ fn render_help_controls(f: &mut Frame, app: &App, area: Rect, dim_color: Color,
                 Span::raw("   "),
             ]),
             Line::from(vec![
+                Span::styled("Z", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
+                Span::raw(" PAUSE PRINT"),
+                Span::raw("   "),
+                Span::styled("R", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
+                Span::raw(" RESUME PRINT"),
+                Span::raw("   "),
+                Span::styled("X", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::raw(" E-STOP"),
+                Span::raw("   "),
                 Span::styled("J", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
                 Span::raw(" CLEAR STUCK PRINTJOB (DEV)"),
                 Span::raw("   "),
                 Span::styled("T", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
                 Span::raw(" MOCK PRINT / DECRYPT TEST"),
+                Span::raw("   "),
+                Span::styled("D", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
+                Span::raw(" DIAGNOSTICS"),
+                Span::raw("   "),
+                Span::styled("M", Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)),
+                Span::raw(" MERGE COINS"),
             ]),
         ]
     };
@@ -515,6 +604,19 @@ fn render_help_controls(f: &mut Frame, app: &App, area: Rect, dim_color: Color,
     f.render_widget(help, area);
 }
 
+/// Simulated fee of the transaction the open prompt will sign.
+fn gas_estimate_span(app: &App) -> Span<'static> {
+    match &app.gas_estimate {
+        None => Span::styled("Est. fee: simulating...", Style::default().fg(Color::DarkGray)),
+        Some(Ok(estimate)) => Span::raw(format!(
+            "Est. fee: {} (budget {})",
+            format_sui_fee(estimate.cost),
+            format_sui_fee(estimate.budget)
+        )),
+        Some(Err(e)) => Span::styled(format!("Est. fee unavailable: {}", e), Style::default().fg(Color::Red)),
+    }
+}
+
 /// Renders the print output log
 fn render_print_output(f: &mut Frame, app: &App, area: Rect, primary_color: Color, secondary_color: Color) {
     let output_block = Block::default()
@@ -5,6 +5,7 @@ mod main_view;
 mod registration;
 mod status_display;
 mod draw;
+mod diagnostics;
 mod utils;
 mod animations;
 mod ascii_arts;
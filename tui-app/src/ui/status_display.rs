@@ -11,6 +11,103 @@ use super::ascii_arts::{PRINTER_ACTIVE_FRAMES, PRINTER_IDLE_FRAMES};
 use super::utils::split_ascii_art;
 use super::animations::get_printer_animation_frames;
 
+/// `HOTEND 210/210°C  BED 60/60°C` from the latest eureka-serial report, if one has arrived.
+fn temperature_line(app: &App, dim_color: Color, value_color: Color) -> Option<Line<'static>> {
+    let temps = app.telemetry.temperatures?;
+    Some(Line::from(vec![
+        Span::styled("HOTEND ", Style::default().fg(dim_color)),
+        Span::styled(temps.format_hotend(), Style::default().fg(value_color).add_modifier(Modifier::BOLD)),
+        Span::styled("  BED ", Style::default().fg(dim_color)),
+        Span::styled(temps.format_bed(), Style::default().fg(value_color).add_modifier(Modifier::BOLD)),
+    ]).alignment(Alignment::Center))
+}
+
+/// `DOWNLOAD [■■■□□□□□□□] 31%  1.2 MB / 3.9 MB` while a model is fetched from Walrus.
+fn download_line(app: &App, dim_color: Color, value_color: Color) -> Option<Line<'static>> {
+    let progress = app.download_progress?;
+    let mut spans = vec![Span::styled("DOWNLOAD ", Style::default().fg(dim_color))];
+    if let Some(fraction) = progress.fraction() {
+        let filled = (fraction * 10.0).round() as usize;
+        spans.push(Span::styled(
+            format!("[{}{}] {:.0}%  ", "■".repeat(filled), "□".repeat(10 - filled), fraction * 100.0),
+            Style::default().fg(value_color),
+        ));
+    }
+    spans.push(Span::styled(progress.to_string(), Style::default().fg(value_color).add_modifier(Modifier::BOLD)));
+    Some(Line::from(spans).alignment(Alignment::Center))
+}
+
+/// `[■■■■□□□□□□] 42%  L12/80  ETA 1h05m` from eureka-serial progress events, once streaming has
+/// started. Percentage, layer and ETA come from the G-code analysis when it is available.
+fn progress_line(app: &App, dim_color: Color, value_color: Color) -> Option<Line<'static>> {
+    let progress = app.telemetry.progress()?;
+    let layer = match app.telemetry.layer() {
+        Some((current, Some(count))) => format!("  L{}/{}", current, count),
+        Some((current, None)) => format!("  L{}", current),
+        None => String::new(),
+    };
+    let filled = (progress * 10.0).round() as usize;
+    let bar = format!("[{}{}]", "■".repeat(filled), "□".repeat(10 - filled));
+    let (label, detail) = if app.telemetry.paused {
+        ("  ", "PAUSED".to_string())
+    } else {
+        match app.telemetry.eta() {
+            Some(eta) => {
+                let secs = eta.as_secs();
+                ("  ETA ", format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60))
+            }
+            None => ("", String::new()),
+        }
+    };
+    Some(Line::from(vec![
+        Span::styled(bar, Style::default().fg(value_color)),
+        Span::styled(format!(" {:.0}%", progress * 100.0), Style::default().fg(value_color).add_modifier(Modifier::BOLD)),
+        Span::styled(layer, Style::default().fg(value_color)),
+        Span::styled(label, Style::default().fg(dim_color)),
+        Span::styled(detail, Style::default().fg(Color::Yellow)),
+    ]).alignment(Alignment::Center))
+}
+
+/// The firmware's own message when the last job failed on a printer error.
+fn print_error_line(app: &App) -> Option<Line<'static>> {
+    let PrintStatus::Error(message) = &app.print_status else {
+        return None;
+    };
+    Some(Line::from(vec![
+        Span::styled("⚠ ", Style::default().fg(Color::Red)),
+        Span::styled(message.clone(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+    ]).alignment(Alignment::Center))
+}
+
+/// Pause / resume / stop keys for a running print, or the pending cancel / e-stop confirmation.
+fn print_control_hint(app: &App, dim_color: Color, highlight_color: Color) -> Line<'static> {
+    if app.is_confirming_estop {
+        return Line::from(vec![
+            Span::styled("EMERGENCY STOP? (Y/N)", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        ]).alignment(Alignment::Center);
+    }
+    if matches!(app.print_status, PrintStatus::Error(_)) {
+        return Line::from(vec![
+            Span::styled("Reset the printer, then press ", Style::default().fg(dim_color)),
+            Span::styled("P", Style::default().fg(highlight_color)),
+            Span::styled(" to print again", Style::default().fg(dim_color)),
+        ]).alignment(Alignment::Center);
+    }
+    if app.is_cancelling_print {
+        return Line::from(vec![
+            Span::styled("Cancel print? (Y/N)", Style::default().fg(Color::Yellow)),
+        ]).alignment(Alignment::Center);
+    }
+    let (key, action) = if app.telemetry.paused { ("R", " to resume, ") } else { ("Z", " to pause, ") };
+    Line::from(vec![
+        Span::styled("Press ", Style::default().fg(dim_color)),
+        Span::styled(key, Style::default().fg(highlight_color)),
+        Span::styled(action, Style::default().fg(dim_color)),
+        Span::styled("E", Style::default().fg(highlight_color)),
+        Span::styled(" to stop printing", Style::default().fg(dim_color)),
+    ]).alignment(Alignment::Center)
+}
+
 /// Renders the online mode active task display
 pub fn render_online_active_task(f: &mut Frame, app: &mut App, area: Rect, time: u64) {
     let animation_frame = (time % 4) as usize;
@@ -88,6 +185,12 @@ pub fn render_online_active_task(f: &mut Frame, app: &mut App, area: Rect, time:
             ]).alignment(Alignment::Center),
         ]);
 
+        task_info.extend(temperature_line(app, dim_color, Color::LightRed));
+
+        task_info.extend(download_line(app, dim_color, secondary_color));
+        task_info.extend(progress_line(app, dim_color, secondary_color));
+        task_info.extend(print_error_line(app));
+
         // Add action hint based on printer status
         if !matches!(app.print_status, PrintStatus::Printing) {
             task_info.push(Line::from(vec![
@@ -96,11 +199,7 @@ pub fn render_online_active_task(f: &mut Frame, app: &mut App, area: Rect, time:
                 Span::styled(" to start printing", Style::default().fg(dim_color)),
             ]).alignment(Alignment::Center));
         } else {
-            task_info.push(Line::from(vec![
-                Span::styled("Press ", Style::default().fg(dim_color)),
-                Span::styled("E", Style::default().fg(highlight_color)),
-                Span::styled(" to stop printing", Style::default().fg(dim_color)),
-            ]).alignment(Alignment::Center));
+            task_info.push(print_control_hint(app, dim_color, highlight_color));
         }
 
         let task_widget = Paragraph::new(task_info)
@@ -136,6 +235,8 @@ pub fn render_online_active_task(f: &mut Frame, app: &mut App, area: Rect, time:
                 Span::styled("◈ Waiting for tasks ⦿", Style::default().fg(dim_color)),
             ]).alignment(Alignment::Center),
         ]);
+        idle_text.extend(temperature_line(app, dim_color, secondary_color));
+        idle_text.extend(download_line(app, dim_color, secondary_color));
         
         let idle_widget = Paragraph::new(idle_text)
             .style(Style::default())
@@ -224,6 +325,12 @@ pub fn render_offline_printer(f: &mut Frame, app: &mut App, area: Rect, time: u6
             ]).alignment(Alignment::Center),
         ]);
 
+        task_info.extend(temperature_line(app, dim_color, Color::LightRed));
+
+        task_info.extend(download_line(app, dim_color, secondary_color));
+        task_info.extend(progress_line(app, dim_color, secondary_color));
+        task_info.extend(print_error_line(app));
+
         // Add a notice for online mode
         task_info.push(Line::from(vec![
             Span::styled("Switch to ", Style::default().fg(dim_color)),
@@ -270,6 +377,10 @@ pub fn render_offline_printer(f: &mut Frame, app: &mut App, area: Rect, time: u6
                 Span::styled("T", Style::default().fg(Color::Yellow)),
                 Span::styled(" for decryption test", Style::default().fg(dim_color)),
             ]).alignment(Alignment::Center),
+        ]);
+        idle_text.extend(temperature_line(app, dim_color, secondary_color));
+        idle_text.extend(download_line(app, dim_color, secondary_color));
+        idle_text.extend(vec![
             // Add some empty lines for better centering
             Line::from("").alignment(Alignment::Center),
             Line::from("").alignment(Alignment::Center),
@@ -316,12 +427,14 @@ pub fn render_offline_printer(f: &mut Frame, app: &mut App, area: Rect, time: u6
                         Span::styled("PRINT COUNT: ", Style::default().fg(dim_color)),
                         Span::styled(sculpt.printed_count.to_string(), Style::default().fg(secondary_color)),
                     ]).alignment(Alignment::Center),
+                ]);
+                printing_text.extend(temperature_line(app, dim_color, Color::LightRed));
+                printing_text.extend(download_line(app, dim_color, secondary_color));
+                printing_text.extend(progress_line(app, dim_color, secondary_color));
+                printing_text.extend(print_error_line(app));
+                printing_text.extend(vec![
                     Line::from("").alignment(Alignment::Center),
-                    Line::from(vec![
-                        Span::styled("Press ", Style::default().fg(dim_color)),
-                        Span::styled("E", Style::default().fg(highlight_color)),
-                        Span::styled(" to stop printing", Style::default().fg(dim_color)),
-                    ]).alignment(Alignment::Center),
+                    print_control_hint(app, dim_color, highlight_color),
                     Line::from("").alignment(Alignment::Center),
                     Line::from("").alignment(Alignment::Center),
                 ]);
@@ -1,5 +1,4 @@
 use anyhow::Result;
-use std::path::PathBuf;
 use std::sync::Arc;
 use sui_sdk_types::Address;
 use sui_rpc::Client as GrpcClient;
@@ -9,12 +8,6 @@ use crate::constants::{NETWORKS, NETWORK_PACKAGE_IDS, NetworkPackageIds, SUI_DEC
 use crate::wallet::load_active_signer;
 use dirs::home_dir;
 
-/// `tui-app/` directory at compile time. Use for `Gcode-Transmit`, `mock_print.stl`, etc., so paths stay
-/// correct when the process cwd is `target/debug` or elsewhere.
-pub fn crate_root() -> PathBuf {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-}
-
 pub fn shorten_id(id: &str) -> String {
     if id.len() > 16 {
         format!("{}...{}", &id[..10], &id[id.len() - 8..])
@@ -27,6 +20,11 @@ pub fn format_sui_balance(amount: u128) -> String {
     format!("{:.2} SUI", amount as f64 / SUI_DECIMALS)
 }
 
+/// Gas fees are fractions of a cent; two decimals would show them all as 0.00.
+pub fn format_sui_fee(mist: u64) -> String {
+    format!("{:.4} SUI", mist as f64 / SUI_DECIMALS)
+}
+
 #[allow(dead_code)]
 pub fn format_sui_amount(amount: u128, decimals: u64) -> String {
     format!("{:.2}", amount as f64 / 10_f64.powi(decimals as i32))
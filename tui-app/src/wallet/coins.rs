@@ -0,0 +1,162 @@
+//! The wallet's SUI coins and which of them pay for gas. Payments and fee withdrawals leave a printer
+//! address with many small coins; left to itself the transaction builder may gather dozens of them
+//! for one budget, or stall looking. Gas is picked here instead, and dust can be merged away.
+
+use anyhow::{anyhow, bail, Result};
+use futures::TryStreamExt;
+use sui_rpc::proto::sui::rpc::v2::ListOwnedObjectsRequest;
+use sui_sdk_types::{Address, Digest};
+use tokio::sync::Mutex;
+
+use super::client::Wallet;
+use super::read_mask;
+
+const SUI_COIN_TYPE: &str = "0x2::coin::Coin<0x2::sui::SUI>";
+
+/// Sui's limit on gas payment objects (`max_gas_payment_objects`).
+pub const MAX_GAS_COINS: usize = 256;
+/// Coins merged by one maintenance transaction.
+pub const MAX_MERGE_COINS: usize = 500;
+
+/// One `Coin<SUI>` object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiCoin {
+    pub id: Address,
+    pub version: u64,
+    pub digest: Digest,
+    /// In MIST.
+    pub balance: u64,
+}
+
+impl Wallet {
+    pub async fn get_sui_coins(&self, address: Address) -> Result<Vec<SuiCoin>> {
+        list_sui_coins(&self.rpc, address).await
+    }
+}
+
+/// Every SUI coin `owner` holds, largest first.
+pub(crate) async fn list_sui_coins(rpc: &Mutex<sui_rpc::Client>, owner: Address) -> Result<Vec<SuiCoin>> {
+    let client = rpc.lock().await;
+    let req = ListOwnedObjectsRequest::default()
+        .with_owner(owner.to_string())
+        .with_object_type(SUI_COIN_TYPE.to_string())
+        .with_page_size(200)
+        .with_read_mask(read_mask("object_id,version,digest,balance"));
+
+    let mut coins = Vec::new();
+    let stream = client.list_owned_objects(req);
+    tokio::pin!(stream);
+    while let Some(obj) = stream.try_next().await? {
+        let id = obj
+            .object_id_opt()
+            .ok_or_else(|| anyhow!("coin without object_id"))?
+            .parse()
+            .map_err(|e| anyhow!("coin id: {}", e))?;
+        let digest = obj
+            .digest_opt()
+            .ok_or_else(|| anyhow!("coin {} without digest", id))?
+            .parse()
+            .map_err(|e| anyhow!("coin digest: {}", e))?;
+        coins.push(SuiCoin {
+            id,
+            version: obj.version_opt().ok_or_else(|| anyhow!("coin {} without version", id))?,
+            digest,
+            balance: obj.balance_opt().unwrap_or(0),
+        });
+    }
+    coins.sort_by_key(|c| std::cmp::Reverse(c.balance));
+    Ok(coins)
+}
+
+/// Gas coins for `budget` out of `coins`: the smallest coin that covers it alone, otherwise the
+/// largest coins until they do.
+pub fn select_gas_coins(coins: &[SuiCoin], budget: u64) -> Result<Vec<SuiCoin>> {
+    if let Some(coin) = coins.iter().filter(|c| c.balance >= budget).min_by_key(|c| c.balance) {
+        return Ok(vec![coin.clone()]);
+    }
+    let mut sorted: Vec<&SuiCoin> = coins.iter().collect();
+    sorted.sort_by_key(|c| std::cmp::Reverse(c.balance));
+    let mut selected = Vec::new();
+    let mut total: u64 = 0;
+    for coin in sorted.into_iter().take(MAX_GAS_COINS) {
+        selected.push(coin.clone());
+        total = total.saturating_add(coin.balance);
+        if total >= budget {
+            return Ok(selected);
+        }
+    }
+    let held: u64 = coins.iter().map(|c| c.balance).sum();
+    if held >= budget {
+        bail!(
+            "gas budget {} MIST needs more than {} of the wallet's {} SUI coins; merge dust coins first",
+            budget,
+            MAX_GAS_COINS,
+            coins.len()
+        );
+    }
+    bail!(
+        "wallet holds {} MIST in {} SUI coins, below the gas budget of {} MIST",
+        held,
+        coins.len(),
+        budget
+    )
+}
+
+/// Coins below `dust_threshold` MIST.
+pub fn dust_coins(coins: &[SuiCoin], dust_threshold: u64) -> Vec<SuiCoin> {
+    coins.iter().filter(|c| c.balance < dust_threshold).cloned().collect()
+}
+
+/// Gas coins and the dust coins to merge into them, for a merge transaction with `budget`. `None`
+/// when there is no dust outside the gas payment.
+pub fn merge_plan(
+    coins: &[SuiCoin],
+    budget: u64,
+    dust_threshold: u64,
+) -> Result<Option<(Vec<SuiCoin>, Vec<SuiCoin>)>> {
+    let gas = select_gas_coins(coins, budget)?;
+    let merged: Vec<SuiCoin> = dust_coins(coins, dust_threshold)
+        .into_iter()
+        .filter(|c| !gas.contains(c))
+        .take(MAX_MERGE_COINS)
+        .collect();
+    if merged.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((gas, merged)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(n: u8, balance: u64) -> SuiCoin {
+        SuiCoin {
+            id: Address::new([n; 32]),
+            version: 1,
+            digest: Digest::new([n; 32]),
+            balance,
+        }
+    }
+
+    #[test]
+    fn test_select_gas_coins() {
+        let coins = vec![coin(1, 500), coin(2, 120), coin(3, 100), coin(4, 30)];
+        assert_eq!(select_gas_coins(&coins, 110).unwrap(), vec![coin(2, 120)]);
+        assert_eq!(select_gas_coins(&coins, 600).unwrap(), vec![coin(1, 500), coin(2, 120)]);
+        assert!(select_gas_coins(&coins, 1_000).is_err());
+
+        let dust: Vec<SuiCoin> = (0..=255).chain(0..=0).map(|n| coin(n, 1)).collect();
+        let err = select_gas_coins(&dust, 257).unwrap_err();
+        assert!(err.to_string().contains("merge dust coins"));
+    }
+
+    #[test]
+    fn test_merge_plan() {
+        let coins = vec![coin(1, 500), coin(2, 120), coin(3, 8), coin(4, 5)];
+        let (gas, merged) = merge_plan(&coins, 400, 10).unwrap().unwrap();
+        assert_eq!(gas, vec![coin(1, 500)]);
+        assert_eq!(merged, vec![coin(3, 8), coin(4, 5)]);
+        assert!(merge_plan(&coins[..2], 400, 10).unwrap().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! Eureka events for one printer, read from the gRPC checkpoint stream. A new or cleared print job
+//! shows up within a checkpoint instead of at the next poll, and the node is not asked to list the
+//! Printer's dynamic fields every few seconds.
+
+use anyhow::{anyhow, Result};
+use futures::{Stream, TryStreamExt};
+use serde_json::Value as Json;
+use std::pin::Pin;
+use sui_rpc::proto::sui::rpc::v2::{SubscribeCheckpointsRequest, SubscribeCheckpointsResponse};
+use sui_sdk_types::Address;
+
+use super::move_json::prost_value_to_json;
+use super::read_mask;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrinterEvent {
+    /// `eureka::PrintJobCreated`
+    PrintJobCreated,
+    /// `eureka::PrintJobCleared`
+    PrintJobCleared,
+    /// `eureka::PrinterStatusUpdated`
+    StatusUpdated { online: bool },
+}
+
+type CheckpointStream = Pin<Box<dyn Stream<Item = Result<SubscribeCheckpointsResponse>> + Send>>;
+
+pub struct PrinterEvents {
+    // Kept for the life of the stream. The subscription has its own client: the shared one sits
+    // behind the app's mutex, which a long-lived stream must not hold.
+    _client: sui_rpc::Client,
+    stream: CheckpointStream,
+    printer_id: Address,
+}
+
+impl PrinterEvents {
+    pub async fn subscribe(rpc_url: &str, printer_id: &str) -> Result<Self> {
+        let printer_id: Address = printer_id
+            .parse()
+            .map_err(|e| anyhow!("Invalid printer ID format: {}", e))?;
+        let mut client = sui_rpc::Client::new(rpc_url).map_err(|e| anyhow!("gRPC client: {}", e))?;
+        let stream = client
+            .subscription_client()
+            .subscribe_checkpoints(SubscribeCheckpointsRequest::default().with_read_mask(read_mask(
+                "sequence_number,transactions.events.events.event_type,transactions.events.events.json",
+            )))
+            .await
+            .map_err(|e| anyhow!("subscribe_checkpoints: {}", e))?
+            .into_inner()
+            .map_err(|e| anyhow!("checkpoint stream: {}", e));
+        Ok(Self {
+            _client: client,
+            stream: Box::pin(stream),
+            printer_id,
+        })
+    }
+
+    /// The printer's events from the next checkpoint that has any. Fails when the stream breaks or
+    /// ends.
+    pub async fn next(&mut self) -> Result<Vec<PrinterEvent>> {
+        loop {
+            let response = self
+                .stream
+                .try_next()
+                .await?
+                .ok_or_else(|| anyhow!("checkpoint stream ended"))?;
+            let found: Vec<PrinterEvent> = response
+                .checkpoint()
+                .transactions
+                .iter()
+                .flat_map(|tx| tx.events().events.iter())
+                .filter_map(|event| {
+                    let json = prost_value_to_json(event.json.as_ref()?.as_ref());
+                    printer_event(event.event_type_opt()?, &json, &self.printer_id)
+                })
+                .collect();
+            if !found.is_empty() {
+                return Ok(found);
+            }
+        }
+    }
+}
+
+/// The event of type `event_type` (`0x…::eureka::<Name>`) with `json` contents, if it is one of
+/// ours about `printer_id`. The package is not compared: the printer id is enough, and this way
+/// events of upgraded packages still match.
+fn printer_event(event_type: &str, json: &Json, printer_id: &Address) -> Option<PrinterEvent> {
+    let (_, name) = event_type.rsplit_once("::eureka::")?;
+    let event_printer: Address = json.get("printer_id")?.as_str()?.parse().ok()?;
+    if &event_printer != printer_id {
+        return None;
+    }
+    match name {
+        "PrintJobCreated" => Some(PrinterEvent::PrintJobCreated),
+        "PrintJobCleared" => Some(PrinterEvent::PrintJobCleared),
+        "PrinterStatusUpdated" => Some(PrinterEvent::StatusUpdated {
+            online: json.get("new_status")?.as_bool()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_printer_event() {
+        let ours: Address = "0xa1".parse().unwrap();
+        let created = json!({ "job_id": "0xb2", "printer_id": "0x00a1", "alias": "vase" });
+        assert_eq!(
+            printer_event("0x9::eureka::PrintJobCreated", &created, &ours),
+            Some(PrinterEvent::PrintJobCreated)
+        );
+        let status = json!({ "printer_id": "0xa1", "new_status": true });
+        assert_eq!(
+            printer_event("0x9::eureka::PrinterStatusUpdated", &status, &ours),
+            Some(PrinterEvent::StatusUpdated { online: true })
+        );
+
+        let other_printer = json!({ "printer_id": "0xc3", "sculpt_id": "0xd4" });
+        assert_eq!(printer_event("0x9::eureka::PrintJobCleared", &other_printer, &ours), None);
+        assert_eq!(printer_event("0x9::eureka::PrintJobCompleted", &created, &ours), None);
+        assert_eq!(printer_event("0x9::other::PrintJobCreated", &created, &ours), None);
+    }
+}
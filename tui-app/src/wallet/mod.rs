@@ -8,8 +8,13 @@ mod utils;
 mod kiosk;
 mod move_json;
 mod keystore;
+mod coins;
+mod events;
 
 pub use types::{SculptItem, PrinterInfo};
 pub use client::Wallet;
 pub use keystore::load_active_signer;
+pub use coins::{dust_coins, merge_plan, select_gas_coins, SuiCoin};
+pub(crate) use coins::list_sui_coins;
+pub use events::{PrinterEvent, PrinterEvents};
 pub(crate) use field_mask::read_mask;
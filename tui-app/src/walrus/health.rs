@@ -0,0 +1,169 @@
+//! Per-aggregator success and latency, shared by every download so the next one starts with the
+//! host most likely to answer.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Consecutive failures after which a host is tried last.
+const DOWN_AFTER_FAILURES: u32 = 3;
+/// Weight of the newest sample in the latency average.
+const LATENCY_WEIGHT: f64 = 0.3;
+
+/// How `walrus.aggregators` are ordered for each download.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregatorStrategy {
+    /// Configured order.
+    #[default]
+    Failover,
+    /// Lowest average time to first byte; hosts never tried go first so each gets measured.
+    Fastest,
+}
+
+impl FromStr for AggregatorStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "failover" | "ordered" => Ok(Self::Failover),
+            "fastest" => Ok(Self::Fastest),
+            other => Err(format!("unknown aggregator strategy '{}' (expected failover or fastest)", other)),
+        }
+    }
+}
+
+impl fmt::Display for AggregatorStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Failover => "failover",
+            Self::Fastest => "fastest",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostState {
+    Untried,
+    Healthy,
+    /// Failed since its last success.
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HostStats {
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    /// Moving average of the time until response headers arrived.
+    pub latency: Option<Duration>,
+    pub bytes: u64,
+    pub last_error: Option<String>,
+}
+
+impl HostStats {
+    pub fn state(&self) -> HostState {
+        if self.consecutive_failures >= DOWN_AFTER_FAILURES {
+            HostState::Down
+        } else if self.consecutive_failures > 0 {
+            HostState::Degraded
+        } else if self.successes > 0 {
+            HostState::Healthy
+        } else {
+            HostState::Untried
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AggregatorHealth {
+    hosts: Mutex<HashMap<String, HostStats>>,
+}
+
+impl AggregatorHealth {
+    pub fn record_success(&self, host: &str, latency: Duration, bytes: u64) {
+        self.update(host, |s| {
+            s.successes += 1;
+            s.consecutive_failures = 0;
+            s.bytes += bytes;
+            s.latency = Some(match s.latency {
+                Some(avg) => avg.mul_f64(1.0 - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT),
+                None => latency,
+            });
+        });
+    }
+
+    /// The host misbehaved (unreachable, 5xx, stalled).
+    pub fn record_failure(&self, host: &str, error: &str) {
+        self.update(host, |s| {
+            s.failures += 1;
+            s.consecutive_failures += 1;
+            s.last_error = Some(error.to_string());
+        });
+    }
+
+    /// The host answered but could not serve this blob (4xx); not held against its health.
+    pub fn record_miss(&self, host: &str, error: &str) {
+        self.update(host, |s| s.last_error = Some(error.to_string()));
+    }
+
+    pub fn stats(&self, host: &str) -> HostStats {
+        self.lock().get(host).cloned().unwrap_or_default()
+    }
+
+    /// `hosts` in the order to try them.
+    pub fn order(&self, hosts: &[String], strategy: AggregatorStrategy) -> Vec<String> {
+        let stats: Vec<HostStats> = hosts.iter().map(|h| self.stats(h)).collect();
+        rank(&stats, strategy)
+            .into_iter()
+            .map(|i| hosts[i].clone())
+            .collect()
+    }
+
+    fn update(&self, host: &str, f: impl FnOnce(&mut HostStats)) {
+        f(self.lock().entry(host.to_string()).or_default());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, HostStats>> {
+        self.hosts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Indices into `stats` by preference. Hosts that are down always go last, but are still tried.
+fn rank(stats: &[HostStats], strategy: AggregatorStrategy) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..stats.len()).collect();
+    match strategy {
+        AggregatorStrategy::Failover => order.sort_by_key(|&i| stats[i].state() == HostState::Down),
+        AggregatorStrategy::Fastest => order.sort_by_key(|&i| {
+            (
+                stats[i].state() == HostState::Down,
+                stats[i].latency.unwrap_or(Duration::ZERO),
+            )
+        }),
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let ms = |n| Some(Duration::from_millis(n));
+        let slow = HostStats { successes: 3, latency: ms(900), ..Default::default() };
+        let down = HostStats { failures: 3, consecutive_failures: 3, latency: ms(50), ..Default::default() };
+        let fast = HostStats { successes: 1, latency: ms(120), ..Default::default() };
+        let untried = HostStats::default();
+        let stats = [slow, down, fast, untried];
+
+        assert_eq!(rank(&stats, AggregatorStrategy::Failover), vec![0, 2, 3, 1]);
+        assert_eq!(rank(&stats, AggregatorStrategy::Fastest), vec![3, 2, 0, 1]);
+        assert_eq!(stats[1].state(), HostState::Down);
+        assert_eq!(stats[3].state(), HostState::Untried);
+    }
+}
@@ -0,0 +1,341 @@
+//! Walrus aggregator client. Blobs are streamed (to a file or into memory), capped at
+//! `walrus.max_blob_mb`, and rejected when an aggregator answers 200 with an error page instead of
+//! blob data. Transport errors, stalls, 5xx and 429 move on to the next aggregator (with
+//! exponential backoff once every one has been tried); a 4xx drops that aggregator for the blob.
+
+mod health;
+
+pub use health::{AggregatorHealth, AggregatorStrategy, HostState, HostStats};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::config::WalrusConfig;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// No bytes for this long and the attempt is abandoned (and retried).
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(16);
+/// How much of the body is kept to recognise an error page.
+const SNIFF_BYTES: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub received: u64,
+    /// From `Content-Length`, when the aggregator sends one.
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|t| *t > 0)
+            .map(|t| (self.received as f64 / t as f64).min(1.0))
+    }
+}
+
+impl fmt::Display for DownloadProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "{} / {}", format_bytes(self.received), format_bytes(total)),
+            None => write!(f, "{}", format_bytes(self.received)),
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{:.0} KB", (bytes as f64 / 1024.0).ceil())
+    }
+}
+
+/// What the next attempt should do.
+enum Failure {
+    /// Try again, on the next aggregator.
+    Retry(anyhow::Error),
+    /// This aggregator cannot serve the blob; try the others.
+    Skip(anyhow::Error),
+    /// No aggregator can help (size limit, local write error).
+    Fatal(anyhow::Error),
+}
+
+/// Where a blob came from.
+pub struct Fetched {
+    pub bytes: u64,
+    pub aggregator: String,
+}
+
+pub struct WalrusClient {
+    http: reqwest::Client,
+    aggregators: Vec<String>,
+    strategy: AggregatorStrategy,
+    health: Arc<AggregatorHealth>,
+    max_bytes: u64,
+    attempts: u32,
+}
+
+impl WalrusClient {
+    /// `aggregators` are base URLs for the current network, in configured order.
+    pub fn new(config: &WalrusConfig, aggregators: Vec<String>, health: Arc<AggregatorHealth>) -> Result<Self> {
+        if aggregators.is_empty() {
+            return Err(anyhow!("no Walrus aggregator configured for this network (walrus.aggregators)"));
+        }
+        let http = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .context("HTTP client")?;
+        Ok(Self {
+            http,
+            aggregators: aggregators
+                .into_iter()
+                .map(|a| a.trim_end_matches('/').to_string())
+                .collect(),
+            strategy: config.strategy,
+            health,
+            max_bytes: config.max_blob_mb.saturating_mul(1024 * 1024),
+            attempts: config.attempts.max(1),
+        })
+    }
+
+    /// Stream `blob_id` into `dest`; a partial file is removed on failure.
+    pub async fn download_to_file(
+        &self,
+        blob_id: &str,
+        dest: &Path,
+        progress: &mut (dyn FnMut(DownloadProgress) + Send),
+    ) -> Result<Fetched> {
+        let result = self
+            .fetch(blob_id, progress, || {
+                std::fs::File::create(dest).map(tokio::fs::File::from_std)
+            })
+            .await;
+        match result {
+            Ok((_, fetched)) => Ok(fetched),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(dest).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// The whole blob in memory (Seal ciphertext has to be parsed in one piece anyway).
+    pub async fn download(&self, blob_id: &str, progress: &mut (dyn FnMut(DownloadProgress) + Send)) -> Result<Vec<u8>> {
+        self.fetch(blob_id, progress, || Ok(Vec::new()))
+            .await
+            .map(|(data, _)| data)
+    }
+
+    /// Up to `attempts` tries across the aggregators, in `strategy` order; `open` gives a fresh
+    /// (empty) sink for every attempt.
+    async fn fetch<W: AsyncWrite + Unpin>(
+        &self,
+        blob_id: &str,
+        progress: &mut (dyn FnMut(DownloadProgress) + Send),
+        mut open: impl FnMut() -> std::io::Result<W>,
+    ) -> Result<(W, Fetched)> {
+        let mut hosts = self.health.order(&self.aggregators, self.strategy);
+        // Last error per aggregator, for the final message.
+        let mut errors: Vec<(String, String)> = Vec::new();
+        let mut next = 0;
+        let mut round = 0;
+        for _ in 0..self.attempts {
+            if hosts.is_empty() {
+                break;
+            }
+            if next >= hosts.len() {
+                next = 0;
+                round += 1;
+                tokio::time::sleep(backoff(round)).await;
+            }
+            let host = hosts[next].clone();
+            let url = format!("{}/v1/blobs/{}", host, blob_id);
+            let mut sink = open().context("open download target")?;
+            let error = match self.attempt(&url, &mut sink, progress).await {
+                Ok((bytes, latency)) => {
+                    self.health.record_success(&host, latency, bytes);
+                    return Ok((sink, Fetched { bytes, aggregator: host }));
+                }
+                Err(Failure::Fatal(e)) => return Err(anyhow!("{}: {}", url, e)),
+                Err(Failure::Retry(e)) => {
+                    self.health.record_failure(&host, &e.to_string());
+                    next += 1;
+                    e
+                }
+                Err(Failure::Skip(e)) => {
+                    self.health.record_miss(&host, &e.to_string());
+                    hosts.remove(next);
+                    e
+                }
+            };
+            errors.retain(|(h, _)| *h != host);
+            errors.push((host, error.to_string()));
+        }
+        let detail: Vec<String> = errors.iter().map(|(h, e)| format!("{}: {}", h, e)).collect();
+        Err(anyhow!(
+            "blob {} unavailable after {} attempts ({})",
+            blob_id,
+            self.attempts,
+            detail.join("; ")
+        ))
+    }
+
+    /// One GET; on success the size and the time until the response headers arrived.
+    async fn attempt<W: AsyncWrite + Unpin>(
+        &self,
+        url: &str,
+        sink: &mut W,
+        progress: &mut (dyn FnMut(DownloadProgress) + Send),
+    ) -> Result<(u64, Duration), Failure> {
+        let started = Instant::now();
+        let mut response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| Failure::Retry(anyhow!("request failed: {}", e)))?;
+        let latency = started.elapsed();
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let error = anyhow!("HTTP {}{}", status, body_summary(&body));
+            return Err(if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                Failure::Retry(error)
+            } else {
+                Failure::Skip(error)
+            });
+        }
+
+        let total = response.content_length();
+        if let Some(total) = total.filter(|t| *t > self.max_bytes) {
+            return Err(Failure::Fatal(anyhow!(
+                "blob is {}, over the {} limit (walrus.max_blob_mb)",
+                format_bytes(total),
+                format_bytes(self.max_bytes)
+            )));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        let mut received = 0u64;
+        let mut head = Vec::with_capacity(SNIFF_BYTES);
+        progress(DownloadProgress { received, total });
+        loop {
+            let chunk = match tokio::time::timeout(STALL_TIMEOUT, response.chunk()).await {
+                Err(_) => {
+                    return Err(Failure::Retry(anyhow!(
+                        "no data for {}s after {}",
+                        STALL_TIMEOUT.as_secs(),
+                        format_bytes(received)
+                    )))
+                }
+                Ok(Err(e)) => return Err(Failure::Retry(anyhow!("transfer interrupted: {}", e))),
+                Ok(Ok(None)) => break,
+                Ok(Ok(Some(chunk))) => chunk,
+            };
+            received += chunk.len() as u64;
+            if received > self.max_bytes {
+                return Err(Failure::Fatal(anyhow!(
+                    "blob exceeds the {} limit (walrus.max_blob_mb)",
+                    format_bytes(self.max_bytes)
+                )));
+            }
+            let room = SNIFF_BYTES.saturating_sub(head.len());
+            head.extend_from_slice(&chunk[..room.min(chunk.len())]);
+            sink.write_all(&chunk)
+                .await
+                .map_err(|e| Failure::Fatal(anyhow!("write failed: {}", e)))?;
+            progress(DownloadProgress { received, total });
+        }
+        sink.flush()
+            .await
+            .map_err(|e| Failure::Fatal(anyhow!("write failed: {}", e)))?;
+
+        if let Some(total) = total.filter(|t| received < *t) {
+            return Err(Failure::Retry(anyhow!(
+                "connection closed after {} of {}",
+                format_bytes(received),
+                format_bytes(total)
+            )));
+        }
+        if received == 0 {
+            return Err(Failure::Skip(anyhow!("aggregator returned an empty blob")));
+        }
+        if let Some(kind) = error_body(content_type.as_deref(), &head) {
+            return Err(Failure::Skip(anyhow!(
+                "aggregator returned {} instead of blob data{}",
+                kind,
+                body_summary(&String::from_utf8_lossy(&head))
+            )));
+        }
+        Ok((received, latency))
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(1 << (attempt - 1).min(8))
+        .min(BACKOFF_MAX)
+}
+
+/// An error page served with a success status, judged by content type and the first bytes.
+fn error_body(content_type: Option<&str>, head: &[u8]) -> Option<&'static str> {
+    let content_type = content_type.unwrap_or("").to_ascii_lowercase();
+    let text = String::from_utf8_lossy(head);
+    let start = text.trim_start().to_ascii_lowercase();
+    if content_type.starts_with("text/html") || start.starts_with("<!doctype html") || start.starts_with("<html") {
+        return Some("an HTML page");
+    }
+    if content_type.starts_with("application/json") || (start.starts_with('{') && start.contains("\"error")) {
+        return Some("a JSON response");
+    }
+    None
+}
+
+/// `: <first line>` of an error body, or nothing when it is empty.
+fn body_summary(body: &str) -> String {
+    let line = body.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    if line.is_empty() {
+        return String::new();
+    }
+    let short: String = line.chars().take(160).collect();
+    format!(": {}", short)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body_detection() {
+        assert_eq!(error_body(Some("text/html; charset=utf-8"), b"whatever"), Some("an HTML page"));
+        assert_eq!(error_body(Some("application/octet-stream"), b"  <!DOCTYPE html><html>"), Some("an HTML page"));
+        assert_eq!(error_body(None, br#"{"error":{"code":404,"message":"blob not found"}}"#), Some("a JSON response"));
+        assert_eq!(error_body(Some("application/octet-stream"), b"solid cube\n facet normal"), None);
+        assert_eq!(error_body(Some("application/octet-stream"), &[0u8, 1, 2, 0xff]), None);
+    }
+
+    #[test]
+    fn test_backoff_and_progress() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(30), BACKOFF_MAX);
+        let p = DownloadProgress { received: 3 * 512 * 1024, total: Some(6 * 1024 * 1024) };
+        assert_eq!(p.fraction(), Some(0.25));
+        assert_eq!(p.to_string(), "1.5 MB / 6.0 MB");
+        assert_eq!(format_bytes(1000), "1 KB");
+    }
+}
@@ -0,0 +1,211 @@
+//! Per-job working directories: `<jobs dir>/<job id>/` holds everything one print touches.
+//!
+//! ```text
+//! ~/.local/share/eureka/jobs/1760000000123-9c4e2a1f/
+//!   job.json             JobMetadata, rewritten on every state change
+//!   model.stl            downloaded (and decrypted) model
+//!   model.gcode          slicer output
+//!   eureka-events.jsonl  eureka-serial events (EUREKA_EVENT_FILE)
+//!   print.log            the print log as shown in the TUI, written when the job ends
+//! ```
+//!
+//! Cleanup: a completed or cancelled job drops its model and G-code (`EUREKA_KEEP_GCODE` keeps the
+//! G-code); a failed job keeps everything for inspection. An encrypted job never has `model.stl`
+//! or `model.gcode`: both live in the private runtime directory (see `crate::private_file`). Creating a job prunes the
+//! oldest directories beyond `jobs.keep`.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::JobsConfig;
+use crate::constants::SERIAL_EVENT_FILE;
+
+const METADATA_FILE: &str = "job.json";
+const MODEL_FILE: &str = "model.stl";
+const GCODE_FILE: &str = "model.gcode";
+const LOG_FILE: &str = "print.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Downloading,
+    /// Model on disk, not printed (download-only).
+    Ready,
+    Printing,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobMetadata {
+    pub id: String,
+    pub name: String,
+    /// On-chain object the job came from (PrintJob or Sculpt id).
+    pub source_id: String,
+    pub blob_id: String,
+    /// Seal-encrypted model; its G-code is never cached.
+    #[serde(default)]
+    pub encrypted: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub state: JobState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobWorkspace {
+    dir: PathBuf,
+    metadata: JobMetadata,
+}
+
+impl JobWorkspace {
+    /// New directory for a job from `source_id`, after pruning old ones.
+    pub fn create(config: &JobsConfig, source_id: &str, name: &str, blob_id: &str, encrypted: bool) -> Result<Self> {
+        let root = config.root()?;
+        std::fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+        // Best effort: a job we cannot delete must not stop the next print.
+        let _ = prune(&root, config.keep.saturating_sub(1));
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let base = job_id(now.as_millis(), source_id);
+        let mut id = base.clone();
+        let mut n = 1;
+        while root.join(&id).exists() {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+        let dir = root.join(&id);
+        std::fs::create_dir(&dir).with_context(|| format!("create {}", dir.display()))?;
+
+        let job = Self {
+            dir,
+            metadata: JobMetadata {
+                id,
+                name: name.to_string(),
+                source_id: source_id.to_string(),
+                blob_id: blob_id.to_string(),
+                encrypted,
+                created_at: now.as_secs(),
+                updated_at: now.as_secs(),
+                state: JobState::Downloading,
+                error: None,
+            },
+        };
+        job.write_metadata()?;
+        Ok(job)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.metadata.id
+    }
+
+    pub fn blob_id(&self) -> &str {
+        &self.metadata.blob_id
+    }
+
+    pub fn encrypted(&self) -> bool {
+        self.metadata.encrypted
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn model_path(&self) -> PathBuf {
+        self.dir.join(MODEL_FILE)
+    }
+
+    pub fn gcode_path(&self) -> PathBuf {
+        self.dir.join(GCODE_FILE)
+    }
+
+    pub fn event_path(&self) -> PathBuf {
+        self.dir.join(SERIAL_EVENT_FILE)
+    }
+
+    pub fn set_state(&mut self, state: JobState, error: Option<String>) -> Result<()> {
+        self.metadata.state = state;
+        self.metadata.error = error;
+        self.metadata.updated_at = unix_now();
+        self.write_metadata()
+    }
+
+    /// Record how the print ended, save the log and apply the cleanup rules.
+    pub fn finish(&mut self, state: JobState, error: Option<String>, log: &[String]) -> Result<()> {
+        let mut text = log.join("\n");
+        text.push('\n');
+        std::fs::write(self.dir.join(LOG_FILE), text).context("write print.log")?;
+        self.set_state(state, error)?;
+        if state != JobState::Failed {
+            let _ = std::fs::remove_file(self.model_path());
+            if std::env::var_os("EUREKA_KEEP_GCODE").is_none() {
+                let _ = std::fs::remove_file(self.gcode_path());
+            }
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.metadata)?;
+        let tmp = self.dir.join(format!("{}.tmp", METADATA_FILE));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, self.dir.join(METADATA_FILE))
+            .with_context(|| format!("write {}", self.dir.join(METADATA_FILE).display()))
+    }
+}
+
+/// `<unix milliseconds>-<last 8 id characters>`, so directory names sort by creation time.
+fn job_id(created_ms: u128, source_id: &str) -> String {
+    let clean: Vec<char> = source_id
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let tail: String = clean[clean.len().saturating_sub(8)..].iter().collect();
+    if tail.is_empty() {
+        format!("{}-local", created_ms)
+    } else {
+        format!("{}-{}", created_ms, tail.to_ascii_lowercase())
+    }
+}
+
+/// Remove job directories beyond the newest `keep`. Only directories with a `job.json` count, so
+/// a misconfigured `jobs.dir` never loses unrelated files.
+fn prune(root: &Path, keep: usize) -> Result<()> {
+    let mut jobs: Vec<PathBuf> = std::fs::read_dir(root)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(METADATA_FILE).is_file())
+        .collect();
+    jobs.sort();
+    let excess = jobs.len().saturating_sub(keep);
+    for dir in &jobs[..excess] {
+        std::fs::remove_dir_all(dir).map_err(|e| anyhow!("{}: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_id() {
+        assert_eq!(
+            job_id(1_760_000_000_123, "0x1737bb093b90783dfe0e0056df602bdfa42fc417d91fed1e02a27a889C4E2A1F"),
+            "1760000000123-9c4e2a1f"
+        );
+        assert_eq!(job_id(5, "ab/c"), "5-abc");
+        assert_eq!(job_id(5, ""), "5-local");
+    }
+}